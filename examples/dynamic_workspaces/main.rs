@@ -32,6 +32,9 @@ fn my_layouts() -> Vec<Layout> {
         gapless: true,
         follow_focus: true,
         allow_wrapping: false,
+        border_width: None,
+        main_aspect_ratio: None,
+        cycle_exclude: false,
     };
 
     vec![
@@ -70,6 +70,9 @@ fn main() -> Result<()> {
         gapless: true,
         follow_focus: true,
         allow_wrapping: false,
+        border_width: None,
+        main_aspect_ratio: None,
+        cycle_exclude: false,
     };
 
     // Default number of clients in the main layout area
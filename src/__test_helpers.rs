@@ -207,7 +207,7 @@ __impl_stub_xcon! {
         }
     }
     conn: {
-        fn mock_is_managed_client(&self, c: &Client) -> bool {
+        fn mock_is_managed_client(&self, c: &Client, _: &[&str]) -> bool {
             !self.unmanaged_ids.contains(&c.id())
         }
     }
@@ -268,6 +268,11 @@ __impl_stub_xcon! {
         }
     }
     client_handler: {
+        fn mock_kill_client(&self, id: Xid) -> Result<()> {
+            self.add_call("kill_client", strings!(id));
+            Ok(())
+        }
+
         fn mock_focus_client(&self, id: Xid) -> Result<()> {
             self.add_call("focus_client", strings!(id));
             Ok(())
@@ -298,6 +303,11 @@ __impl_stub_xcon! {
             self.add_call("raise_client", strings!(id));
             Ok(())
         }
+
+        fn mock_lower_client(&self, id: Xid) -> Result<()> {
+            self.add_call("lower_client", strings!(id));
+            Ok(())
+        }
     }
     event_handler: {
         fn mock_send_client_event(&self, msg: ClientMessage) -> Result<()> {
@@ -315,7 +325,7 @@ __impl_stub_xcon! {
         }
     }
     conn: {
-        fn mock_is_managed_client(&self, c: &Client) -> bool {
+        fn mock_is_managed_client(&self, c: &Client, _: &[&str]) -> bool {
             self.add_call("is_managed_client", strings!(c.id()));
             true
         }
@@ -66,6 +66,16 @@ pub fn focus_or_spawn<X: XConn>(
     })
 }
 
+/**
+ * Re-detect the connected screens and reposition displayed [Workspace]s as needed.
+ *
+ * Screen changes are normally picked up automatically from RandR events but this is useful as a
+ * key binding to force a refresh after manually reconfiguring monitors (e.g. with `xrandr`).
+ */
+pub fn detect_screens<X: XConn>() -> KeyEventHandler<X> {
+    Box::new(|wm: &mut WindowManager<X>| wm.detect_screens())
+}
+
 /**
  * Detect the current monitor set up and arrange the monitors if needed using [xrandr][1].
  *
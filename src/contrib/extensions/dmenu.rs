@@ -169,7 +169,7 @@ impl DMenu {
 
         let mut raw = String::new();
         proc.stdout
-            .ok_or_else(|| PenroseError::SpawnProc("failed to spawn dmenu".into()))?
+            .ok_or_else(|| PenroseError::SpawnProc("unable to get stdout handle for dmenu".into()))?
             .read_to_string(&mut raw)?;
         let choice = raw.trim();
 
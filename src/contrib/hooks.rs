@@ -240,8 +240,11 @@ where
 /// started.
 ///
 /// This hook will run at startup and try to position clients on the workspace they are on when
-/// penrose was started / restarted. It is not currently able to preserve positions or workspace
-/// settings.
+/// penrose was started / restarted. Clients without a valid _NET_WM_DESKTOP property are placed
+/// according to the `existing_client_placement` [Config][1] field. It is not currently able to
+/// preserve positions or workspace settings.
+///
+/// [1]: crate::core::config::Config
 #[derive(Debug)]
 pub struct ManageExistingClients {}
 
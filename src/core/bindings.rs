@@ -1,6 +1,10 @@
 //! Setting up and responding to user defined key/mouse bindings
 use crate::{
-    core::{data_types::Point, manager::WindowManager, xconnection::Xid},
+    core::{
+        data_types::Point,
+        manager::WindowManager,
+        xconnection::{XConn, Xid},
+    },
     PenroseError, Result,
 };
 
@@ -52,6 +56,30 @@ pub enum KeyPress {
     Left,
     /// Right
     Right,
+    /// F1
+    F1,
+    /// F2
+    F2,
+    /// F3
+    F3,
+    /// F4
+    F4,
+    /// F5
+    F5,
+    /// F6
+    F6,
+    /// F7
+    F7,
+    /// F8
+    F8,
+    /// F9
+    F9,
+    /// F10
+    F10,
+    /// F11
+    F11,
+    /// F12
+    F12,
 }
 
 #[cfg(feature = "keysyms")]
@@ -71,6 +99,18 @@ impl TryFrom<XKeySym> for KeyPress {
             XKeySym::XK_Down | XKeySym::XK_KP_Down => KeyPress::Down,
             XKeySym::XK_Left | XKeySym::XK_KP_Left => KeyPress::Left,
             XKeySym::XK_Right | XKeySym::XK_KP_Right => KeyPress::Right,
+            XKeySym::XK_F1 | XKeySym::XK_KP_F1 => KeyPress::F1,
+            XKeySym::XK_F2 | XKeySym::XK_KP_F2 => KeyPress::F2,
+            XKeySym::XK_F3 | XKeySym::XK_KP_F3 => KeyPress::F3,
+            XKeySym::XK_F4 | XKeySym::XK_KP_F4 => KeyPress::F4,
+            XKeySym::XK_F5 => KeyPress::F5,
+            XKeySym::XK_F6 => KeyPress::F6,
+            XKeySym::XK_F7 => KeyPress::F7,
+            XKeySym::XK_F8 => KeyPress::F8,
+            XKeySym::XK_F9 => KeyPress::F9,
+            XKeySym::XK_F10 => KeyPress::F10,
+            XKeySym::XK_F11 => KeyPress::F11,
+            XKeySym::XK_F12 => KeyPress::F12,
             s => KeyPress::Utf8(s.as_utf8_string()?),
         })
     }
@@ -158,6 +198,152 @@ impl TryFrom<&str> for ModifierKey {
     }
 }
 
+impl From<ModifierKey> for KeyCodeMask {
+    fn from(m: ModifierKey) -> KeyCodeMask {
+        match m {
+            ModifierKey::Shift => 1 << 0,
+            ModifierKey::Ctrl => 1 << 2,
+            ModifierKey::Alt => 1 << 3,
+            ModifierKey::Meta => 1 << 6,
+        }
+    }
+}
+
+/// Parse a user friendly key binding such as `M-S-j` into a [KeyCode].
+///
+/// Bindings are of the form `<MOD>-<key name>`, where any number of modifiers may be given.
+/// Modifiers are parsed using [ModifierKey::try_from] (`C`, `A`, `S` and `M` for Ctrl, Alt,
+/// Shift and Meta respectively) and the key name is looked up in `codes`: see
+/// [keycodes_from_xmodmap][1] for the names that are available.
+///
+/// # Errors
+/// Fails with [PenroseError::UnknownKeyName] if the key name is not in `codes` and
+/// [PenroseError::UnknownModifier] if a modifier is not recognised. The key name is checked
+/// first, so a binding with both problems is reported as having an unknown key name.
+///
+/// [1]: crate::core::helpers::keycodes_from_xmodmap
+pub fn parse_key_binding(spec: &str, codes: &CodeMap) -> Result<KeyCode> {
+    let mut parts: Vec<&str> = spec.split('-').collect();
+    let name = parts.pop().unwrap_or_default();
+
+    let code = codes
+        .get(name)
+        .ok_or_else(|| PenroseError::UnknownKeyName(name.into()))?;
+    let mask = parts.into_iter().try_fold(0, |mask, s| {
+        ModifierKey::try_from(s).map(|m| mask | KeyCodeMask::from(m))
+    })?;
+
+    Ok(KeyCode { mask, code: *code })
+}
+
+/// Check user key and mouse bindings for bindings that would conflict with one another.
+///
+/// As bindings are held in a `HashMap`, binding the same [KeyCode] twice replaces the first
+/// handler without warning. This catches the cases that can't be seen from the keys of the maps
+/// alone: key bindings that only differ by one of the given `lock_modifiers` (which are ignored
+/// when matching key presses) and mouse bindings whose [MouseState] modifiers only differ in
+/// order or repetition.
+///
+/// # Errors
+/// Fails with [PenroseError::Raw] describing each set of conflicting bindings.
+pub fn validate_bindings<X: XConn>(
+    bindings: &KeyBindings<X>,
+    mouse_bindings: &MouseBindings<X>,
+    lock_modifiers: &[LockModifier],
+) -> Result<()> {
+    let lock_mask = lock_modifiers
+        .iter()
+        .fold(0, |mask, &m| mask | KeyCodeMask::from(m));
+    let mut keys: HashMap<KeyCode, Vec<KeyCode>> = HashMap::new();
+    for k in bindings.keys() {
+        keys.entry(k.ignoring_modifier(lock_mask))
+            .or_default()
+            .push(*k);
+    }
+
+    let mut buttons: HashMap<_, Vec<String>> = HashMap::new();
+    for (kind, state) in mouse_bindings.keys() {
+        let mut modifiers = state.modifiers.clone();
+        modifiers.sort();
+        modifiers.dedup();
+        buttons
+            .entry((*kind, state.button, modifiers))
+            .or_default()
+            .push(format!("{:?} {:?}", kind, state));
+    }
+
+    let key_conflicts = keys.into_values().filter(|ks| ks.len() > 1).map(|mut ks| {
+        ks.sort_by_key(|k| (k.mask, k.code));
+        ks.iter()
+            .map(|k| format!("{:?}", k))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    });
+    let button_conflicts = buttons
+        .into_values()
+        .filter(|bs| bs.len() > 1)
+        .map(|mut bs| {
+            bs.sort();
+            bs.join(" / ")
+        });
+
+    let mut conflicts: Vec<String> = key_conflicts.chain(button_conflicts).collect();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    conflicts.sort();
+
+    Err(perror!("conflicting bindings: {}", conflicts.join(", ")))
+}
+
+/// Lock modifiers that can be ignored when matching key bindings
+#[derive(Debug, EnumIter, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LockModifier {
+    /// Caps Lock (the X `Lock` modifier)
+    CapsLock,
+    /// Num Lock (conventionally the X `Mod2` modifier)
+    NumLock,
+}
+
+impl From<LockModifier> for KeyCodeMask {
+    fn from(m: LockModifier) -> KeyCodeMask {
+        match m {
+            LockModifier::CapsLock => 1 << 1,
+            LockModifier::NumLock => 1 << 4,
+        }
+    }
+}
+
+/// The modifier masks that need to be grabbed alongside each binding so that it is still
+/// triggered while any combination of the given [LockModifier]s is active.
+///
+/// The returned masks always include `0` (no lock modifiers held).
+///
+/// # Example
+///
+/// ```
+/// use penrose::core::bindings::{lock_modifier_masks, LockModifier};
+///
+/// assert_eq!(lock_modifier_masks(&[]), vec![0]);
+/// assert_eq!(
+///     lock_modifier_masks(&[LockModifier::CapsLock, LockModifier::NumLock]),
+///     vec![0, 1 << 1, 1 << 4, (1 << 1) | (1 << 4)]
+/// );
+/// ```
+pub fn lock_modifier_masks(modifiers: &[LockModifier]) -> Vec<KeyCodeMask> {
+    let mut masks = vec![0];
+    for &m in modifiers {
+        let mask = KeyCodeMask::from(m);
+        let with_m: Vec<KeyCodeMask> = masks.iter().map(|&existing| existing | mask).collect();
+        masks.extend(with_m);
+    }
+    masks.sort_unstable();
+    masks.dedup();
+
+    masks
+}
+
 /// A mouse state specification indicating the button and modifiers held
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -174,6 +360,38 @@ impl MouseState {
         modifiers.sort();
         Self { button, modifiers }
     }
+
+    /// The X modifier bitmask for this [MouseState]
+    pub fn mask(&self) -> KeyCodeMask {
+        self.modifiers
+            .iter()
+            .fold(0, |acc, &val| acc | KeyCodeMask::from(val))
+    }
+
+    /// The X button ID for this [MouseState]
+    pub fn button(&self) -> u8 {
+        self.button.into()
+    }
+
+    /// Whether or not this [MouseState] matches `other` once the modifiers in `ignoring` have
+    /// been masked out of both.
+    ///
+    /// Modifiers are compared as a set, so the order that they are held in does not matter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use penrose::core::bindings::{KeyCodeMask, ModifierKey, MouseButton, MouseState};
+    ///
+    /// let binding = MouseState::new(MouseButton::Left, vec![ModifierKey::Meta]);
+    /// let held = MouseState::new(MouseButton::Left, vec![ModifierKey::Meta, ModifierKey::Shift]);
+    ///
+    /// assert!(!held.matches(&binding, 0));
+    /// assert!(held.matches(&binding, KeyCodeMask::from(ModifierKey::Shift)));
+    /// ```
+    pub fn matches(&self, other: &MouseState, ignoring: KeyCodeMask) -> bool {
+        self.button == other.button && self.mask() & !ignoring == other.mask() & !ignoring
+    }
 }
 
 /// The types of mouse events represented by a MouseEvent
@@ -224,3 +442,153 @@ impl MouseEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::__test_helpers::{test_key_bindings, test_mouse_bindings, TestWM, EXIT_CODE};
+
+    fn test_codes() -> CodeMap {
+        [("j", 44), ("Return", 36)]
+            .iter()
+            .map(|&(name, code)| (name.to_string(), code))
+            .collect()
+    }
+
+    test_cases! {
+        parse_key_binding;
+        args: (spec: &str, expected: KeyCode);
+
+        case: bare_key => ("j", KeyCode { mask: 0, code: 44 });
+        case: single_modifier => ("M-Return", KeyCode { mask: 1 << 6, code: 36 });
+        case: multiple_modifiers => ("C-A-S-j", KeyCode { mask: 0b1101, code: 44 });
+
+        body: {
+            assert_eq!(parse_key_binding(spec, &test_codes()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_key_binding_with_unknown_modifier_is_an_error() {
+        match parse_key_binding("X-j", &test_codes()) {
+            Err(PenroseError::UnknownModifier(m)) => assert_eq!(m, "X"),
+            res => panic!("expected UnknownModifier, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn parse_key_binding_with_unknown_key_name_is_an_error() {
+        match parse_key_binding("M-k", &test_codes()) {
+            Err(PenroseError::UnknownKeyName(k)) => assert_eq!(k, "k"),
+            res => panic!("expected UnknownKeyName, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn parse_key_binding_checks_the_key_name_before_modifiers() {
+        match parse_key_binding("X-k", &test_codes()) {
+            Err(PenroseError::UnknownKeyName(k)) => assert_eq!(k, "k"),
+            res => panic!("expected UnknownKeyName, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn validate_bindings_accepts_distinct_bindings() {
+        let locks = [LockModifier::NumLock];
+        assert!(validate_bindings(&test_key_bindings(), &test_mouse_bindings(), &locks).is_ok());
+    }
+
+    test_cases! {
+        validate_bindings_lock_modifiers;
+        args: (locks: Vec<LockModifier>, conflicting: bool);
+
+        case: lock_is_ignored => (vec![LockModifier::NumLock], true);
+        case: other_lock_is_ignored => (vec![LockModifier::CapsLock], false);
+        case: no_locks_ignored => (vec![], false);
+
+        body: {
+            let mut bindings = test_key_bindings();
+            let numlock = KeyCodeMask::from(LockModifier::NumLock);
+            bindings.insert(
+                KeyCode {
+                    mask: EXIT_CODE.mask | numlock,
+                    ..EXIT_CODE
+                },
+                Box::new(|wm: &mut TestWM| wm.exit()),
+            );
+
+            let res = validate_bindings(&bindings, &test_mouse_bindings(), &locks);
+            assert_eq!(res.is_err(), conflicting);
+        }
+    }
+
+    #[test]
+    fn validate_bindings_detects_mouse_bindings_with_reordered_modifiers() {
+        let mut mouse_bindings = test_mouse_bindings();
+        for modifiers in [
+            vec![ModifierKey::Meta, ModifierKey::Shift],
+            vec![ModifierKey::Shift, ModifierKey::Meta],
+        ] {
+            let state = MouseState {
+                button: MouseButton::Left,
+                modifiers,
+            };
+            mouse_bindings.insert(
+                (MouseEventKind::Press, state),
+                Box::new(|_: &mut TestWM, _: &MouseEvent| Ok(())),
+            );
+        }
+
+        assert!(validate_bindings(&test_key_bindings(), &mouse_bindings, &[]).is_err());
+    }
+
+    test_cases! {
+        mouse_state_matches;
+        args: (held: Vec<ModifierKey>, bound: Vec<ModifierKey>, ignoring: KeyCodeMask, matches: bool);
+
+        case: identical => (vec![ModifierKey::Meta], vec![ModifierKey::Meta], 0, true);
+        case: reordered => (
+            vec![ModifierKey::Shift, ModifierKey::Meta], vec![ModifierKey::Meta, ModifierKey::Shift], 0, true
+        );
+        case: extra_modifier => (vec![ModifierKey::Meta, ModifierKey::Alt], vec![ModifierKey::Meta], 0, false);
+        case: extra_ignored_modifier => (
+            vec![ModifierKey::Meta, ModifierKey::Alt], vec![ModifierKey::Meta], 1 << 3, true
+        );
+        case: lock_modifiers_ignored => (
+            vec![ModifierKey::Meta],
+            vec![ModifierKey::Meta],
+            KeyCodeMask::from(LockModifier::NumLock) | KeyCodeMask::from(LockModifier::CapsLock),
+            true
+        );
+
+        body: {
+            let held = MouseState { button: MouseButton::Left, modifiers: held };
+            let bound = MouseState { button: MouseButton::Left, modifiers: bound };
+
+            assert_eq!(held.matches(&bound, ignoring), matches);
+        }
+    }
+
+    #[test]
+    fn mouse_states_with_different_buttons_do_not_match() {
+        let held = MouseState::new(MouseButton::Left, vec![ModifierKey::Meta]);
+        let bound = MouseState::new(MouseButton::Right, vec![ModifierKey::Meta]);
+
+        assert!(!held.matches(&bound, 0));
+    }
+
+    #[cfg(feature = "keysyms")]
+    test_cases! {
+        keypress_from_xkeysym;
+        args: (sym: XKeySym, expected: KeyPress);
+
+        case: f1 => (XKeySym::XK_F1, KeyPress::F1);
+        case: keypad_f1 => (XKeySym::XK_KP_F1, KeyPress::F1);
+        case: keypad_f4 => (XKeySym::XK_KP_F4, KeyPress::F4);
+        case: f12 => (XKeySym::XK_F12, KeyPress::F12);
+
+        body: {
+            assert_eq!(KeyPress::try_from(sym).unwrap(), expected);
+        }
+    }
+}
@@ -1,5 +1,20 @@
 //! Metadata around X clients and manipulating them
-use crate::core::xconnection::{Atom, Prop, WmHints, WmNormalHints, XClientProperties, Xid};
+use crate::core::{
+    data_types::Region,
+    xconnection::{Atom, Prop, WmHints, WmNormalHints, XClientProperties, Xid},
+};
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Source of the creation order for clients: shared across all WindowManager instances
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+// Ensure that clients created from here on out are ordered after `seq` (used when restoring
+// clients from serialised state)
+#[cfg(feature = "serde")]
+pub(crate) fn ensure_sequence_after(seq: u64) {
+    NEXT_SEQUENCE.fetch_max(seq + 1, Ordering::SeqCst);
+}
 
 /**
  * Meta-data around a client window that we are handling.
@@ -18,13 +33,27 @@ pub struct Client {
     pub(crate) wm_protocols: Vec<String>, // Can't use Atom as it could be something arbitrary
     pub(crate) wm_hints: Option<WmHints>,
     pub(crate) wm_normal_hints: Option<WmNormalHints>,
+    pub(crate) floating_region: Option<Region>,
+    pub(crate) last_region: Option<Region>,
     // state flags
     pub(crate) accepts_focus: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) above: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) below: bool,
     pub(crate) floating: bool,
     pub(crate) fullscreen: bool,
     pub(crate) mapped: bool,
     pub(crate) urgent: bool,
     pub(crate) wm_managed: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) focus_override: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) sequence: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) origin_workspace: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) pre_maximize_region: Option<Region>,
 }
 
 impl Client {
@@ -63,6 +92,11 @@ impl Client {
             Ok(Prop::Atom(protocols)) => protocols,
             _ => vec![],
         };
+        let wm_state = match conn.get_prop(id, Atom::NetWmState.as_ref()) {
+            Ok(Prop::Atom(atoms)) => atoms,
+            _ => vec![],
+        };
+        let (above, below) = stacking_from_wm_state(&wm_state);
 
         Self {
             id,
@@ -73,12 +107,20 @@ impl Client {
             wm_protocols,
             wm_hints,
             wm_normal_hints,
+            floating_region: None,
+            last_region: None,
             floating,
             accepts_focus,
+            above,
+            below,
             fullscreen: false,
             mapped: false,
             urgent: false,
             wm_managed: true,
+            focus_override: None,
+            sequence: NEXT_SEQUENCE.fetch_add(1, Ordering::SeqCst),
+            origin_workspace: None,
+            pre_maximize_region: None,
         }
     }
 
@@ -87,6 +129,12 @@ impl Client {
         self.id
     }
 
+    /// A number reflecting the order in which clients were created: clients created later always
+    /// have a larger sequence number.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
     /// The WM_CLASS property of this client
     pub fn wm_class(&self) -> &str {
         self.class()
@@ -102,11 +150,86 @@ impl Client {
         self.fullscreen
     }
 
+    /// Whether or not this client has requested to be kept above its peers
+    /// (`_NET_WM_STATE_ABOVE`)
+    pub fn is_above(&self) -> bool {
+        self.above
+    }
+
+    /// Whether or not this client has requested to be kept below its peers
+    /// (`_NET_WM_STATE_BELOW`)
+    pub fn is_below(&self) -> bool {
+        self.below
+    }
+
+    /// Whether or not this client should be given input focus directly. Clients that do not
+    /// accept focus are sent a `WM_TAKE_FOCUS` message instead.
+    ///
+    /// This is parsed from the client's `WM_HINTS` unless it has been overridden using
+    /// [set_focusable][Client::set_focusable].
+    pub fn accepts_focus(&self) -> bool {
+        self.focus_override.unwrap_or(self.accepts_focus)
+    }
+
+    /// Override whether or not this client should be given input focus directly, ignoring the
+    /// hints it has set. Passing `None` reverts to using the parsed hints.
+    pub fn set_focusable(&mut self, focusable: Option<bool>) {
+        self.focus_override = focusable;
+    }
+
+    /// Whether or not this client is currently marked as urgent and wants the user's attention
+    pub fn is_urgent(&self) -> bool {
+        self.urgent
+    }
+
+    /// The raw `_NET_WM_WINDOW_TYPE` values set by this client, including any that are not
+    /// known to penrose.
+    pub fn wm_type(&self) -> &[String] {
+        &self.wm_type
+    }
+
+    /// Whether or not this client has set the given `_NET_WM_WINDOW_TYPE`
+    pub fn has_window_type(&self, window_type: Atom) -> bool {
+        self.wm_type.iter().any(|t| t == window_type.as_ref())
+    }
+
+    /// Whether or not this client is a dialog (`_NET_WM_WINDOW_TYPE_DIALOG`)
+    pub fn is_dialog(&self) -> bool {
+        self.has_window_type(Atom::NetWindowTypeDialog)
+    }
+
+    /// Whether or not this client is a dock or panel (`_NET_WM_WINDOW_TYPE_DOCK`)
+    pub fn is_dock(&self) -> bool {
+        self.has_window_type(Atom::NetWindowTypeDock)
+    }
+
+    /// Whether or not this client is a splash screen (`_NET_WM_WINDOW_TYPE_SPLASH`)
+    pub fn is_splash(&self) -> bool {
+        self.has_window_type(Atom::NetWindowTypeSplash)
+    }
+
+    /// Whether or not this client is a utility window such as a palette or toolbox
+    /// (`_NET_WM_WINDOW_TYPE_UTILITY`)
+    pub fn is_utility(&self) -> bool {
+        self.has_window_type(Atom::NetWindowTypeUtility)
+    }
+
+    /// Whether or not this client has listed the given protocol in `WM_PROTOCOLS`
+    pub fn supports_protocol(&self, protocol: Atom) -> bool {
+        self.wm_protocols.iter().any(|p| p == protocol.as_ref())
+    }
+
     /// The current workspace index that this client is showing on
     pub fn workspace(&self) -> usize {
         self.workspace
     }
 
+    /// The workspace index that this client was first placed on when it was mapped, if it has
+    /// been placed on a workspace yet.
+    pub fn origin_workspace(&self) -> Option<usize> {
+        self.origin_workspace
+    }
+
     /// Mark this window as being on a new workspace
     pub fn set_workspace(&mut self, workspace: usize) {
         self.workspace = workspace
@@ -117,6 +240,21 @@ impl Client {
         self.floating = floating
     }
 
+    /// The last position this client was given while floating (if any)
+    pub fn floating_region(&self) -> Option<Region> {
+        self.floating_region
+    }
+
+    /// Whether or not this client is currently maximized while floating
+    pub fn is_maximized(&self) -> bool {
+        self.pre_maximize_region.is_some()
+    }
+
+    /// The region this client was last positioned at by the active layout (if any)
+    pub fn last_region(&self) -> Option<Region> {
+        self.last_region
+    }
+
     pub(crate) fn set_name(&mut self, name: impl Into<String>) {
         self.wm_name = name.into()
     }
@@ -136,3 +274,91 @@ impl Client {
         self.wm_managed = true;
     }
 }
+
+// The (above, below) stacking requested by a client through its _NET_WM_STATE atoms. The two
+// are mutually exclusive, with above taking priority if a client has somehow set both.
+pub(crate) fn stacking_from_wm_state(wm_state: &[String]) -> (bool, bool) {
+    let above = wm_state.iter().any(|a| a == Atom::NetWmStateAbove.as_ref());
+    let below = !above && wm_state.iter().any(|a| a == Atom::NetWmStateBelow.as_ref());
+
+    (above, below)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::xconnection::MockXConn;
+
+    fn client_with_types(types: &[Atom]) -> Client {
+        let conn = MockXConn::new(vec![], vec![], vec![]);
+        let mut c = Client::new(&conn, 1, 0, &[]);
+        c.wm_type = types.iter().map(|t| t.as_ref().to_string()).collect();
+        c
+    }
+
+    test_cases! {
+        window_type_predicates;
+        args: (types: &[Atom], expected: [bool; 4]);
+
+        case: normal => (&[Atom::NetWindowTypeNormal], [false, false, false, false]);
+        case: dialog => (&[Atom::NetWindowTypeDialog], [true, false, false, false]);
+        case: dock => (&[Atom::NetWindowTypeDock], [false, true, false, false]);
+        case: splash => (&[Atom::NetWindowTypeSplash], [false, false, true, false]);
+        case: utility => (&[Atom::NetWindowTypeUtility], [false, false, false, true]);
+        case: multiple => (&[Atom::NetWindowTypeSplash, Atom::NetWindowTypeDialog], [true, false, true, false]);
+        case: none => (&[], [false, false, false, false]);
+
+        body: {
+            let c = client_with_types(types);
+            let predicates = [c.is_dialog(), c.is_dock(), c.is_splash(), c.is_utility()];
+
+            assert_eq!(predicates, expected);
+        }
+    }
+
+    #[test]
+    fn unknown_window_types_are_kept() {
+        let mut c = client_with_types(&[Atom::NetWindowTypeDialog]);
+        c.wm_type
+            .push("_KDE_NET_WM_WINDOW_TYPE_OVERRIDE".to_string());
+
+        assert!(c.is_dialog());
+        assert_eq!(
+            c.wm_type(),
+            &[
+                "_NET_WM_WINDOW_TYPE_DIALOG",
+                "_KDE_NET_WM_WINDOW_TYPE_OVERRIDE"
+            ]
+        );
+    }
+
+    #[test]
+    fn sequence_numbers_increase_in_creation_order() {
+        let conn = MockXConn::new(vec![], vec![], vec![]);
+        let clients: Vec<Client> = (0..5).map(|id| Client::new(&conn, id, 0, &[])).collect();
+
+        assert!(clients
+            .windows(2)
+            .all(|w| w[0].sequence() < w[1].sequence()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sequence_numbers_follow_restored_clients() {
+        let conn = MockXConn::new(vec![], vec![], vec![]);
+        let restored = Client::new(&conn, 1, 0, &[]).sequence() + 1000;
+
+        ensure_sequence_after(restored);
+
+        assert!(Client::new(&conn, 2, 0, &[]).sequence() > restored);
+    }
+
+    #[test]
+    fn supports_protocol() {
+        let mut c = client_with_types(&[]);
+        c.wm_protocols = vec![Atom::WmDeleteWindow.as_ref().to_string()];
+
+        assert!(c.supports_protocol(Atom::WmDeleteWindow));
+        assert!(!c.supports_protocol(Atom::WmTakeFocus));
+    }
+}
@@ -1,10 +1,16 @@
 //! User facing configuration of the penrose [WindowManager][crate::core::manager::WindowManager].
 use crate::{
-    core::layout::{side_stack, Layout, LayoutConf},
+    core::{
+        bindings::LockModifier,
+        data_types::{ExistingClientPlacement, FocusPolicy, Gaps},
+        layout::{side_stack, Layout, LayoutConf},
+        screen::ScreenBar,
+        xconnection::UNMANAGED_WINDOW_TYPES,
+    },
     draw::{Color, DrawError},
 };
 
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto};
 
 __with_builder_and_getters! {
     /// The main user facing configuration details.
@@ -68,6 +74,11 @@ __with_builder_and_getters! {
     /// the window classes that will always be considered floating
     VecImplInto floating_classes: String; => vec!["dmenu", "dunst"];
 
+    /// the _NET_WM_WINDOW_TYPE values of windows that should be left unmanaged: they are mapped
+    /// where they request to be and never tiled or focused by penrose
+    VecImplInto unmanaged_window_types: String; =>
+        UNMANAGED_WINDOW_TYPES.iter().map(|a| a.as_ref());
+
     /// the [Layout] functions to be used by each [Workspace][crate::core::workspace::Workspace]
     ///
     /// # Constraints
@@ -84,8 +95,22 @@ __with_builder_and_getters! {
     ImplTry DrawError; unfocused_border: Color; => "#3c3836";
     /// the border width of each window in pixels
     Concrete border_px: u32; => 2;
-    /// the gap between tiled windows in pixels
+    /// the gap between tiled windows in pixels, used for all four sides of each window unless
+    /// `gaps` is set
     Concrete gap_px: u32; => 5;
+    /// independent top / bottom / left / right gaps around tiled windows in pixels. When set,
+    /// this takes precedence over `gap_px`
+    Concrete gaps: Option<Gaps>; => None;
+    /// the symbol of a [Layout] to switch a workspace to while one of its clients is fullscreen.
+    /// The previous layout is restored once the last fullscreen client on the workspace exits
+    /// fullscreen (None leaves the layout unchanged)
+    Concrete fullscreen_layout: Option<String>; => None;
+    /// whether or not gaps should be dropped when there is only a single tiled window visible
+    /// on a workspace
+    Concrete smart_gaps: bool; => false;
+    /// whether or not cycling between occupied workspaces should wrap at the first and last
+    /// workspace
+    Concrete cycle_occupied_wraps: bool; => true;
     /// the percentage of the screen to grow the main region by when incrementing
     Concrete main_ratio_step: f32; => 0.05;
     /// whether or not space should be reserved for a status bar
@@ -94,6 +119,49 @@ __with_builder_and_getters! {
     Concrete top_bar: bool; => true;
     /// the height of the space to be reserved for a status bar in pixels
     Concrete bar_height: u32; => 18;
+    /// per-screen overrides of `bar_height` and `top_bar` (by screen index). Screens without an
+    /// entry use the global values
+    Concrete screen_bars: Vec<Option<ScreenBar>>; => vec![];
+    /// whether or not tiled clients that set resize increments (e.g. terminals) should be sized
+    /// to a whole number of increments
+    Concrete respect_size_increments: bool; => false;
+    /// the index of the workspace to show on each screen at startup (by screen index). Screens
+    /// without an entry show the workspace they were assigned when the screens were detected
+    Concrete startup_workspaces: Vec<usize>; => vec![];
+    /// lock modifiers that are ignored when matching key and mouse bindings: bindings are grabbed
+    /// and triggered regardless of whether or not any of these are active. Defaults to only
+    /// NumLock (as in earlier releases) so bindings do not trigger while Caps Lock is on unless
+    /// `LockModifier::CapsLock` is added here
+    Concrete ignored_lock_modifiers: Vec<LockModifier>; => vec![LockModifier::NumLock];
+    /// the number of milliseconds following user input to the focused client (as reported via
+    /// _NET_WM_USER_TIME) during which newly mapped clients will not take focus and are instead
+    /// marked as urgent using _NET_WM_STATE_DEMANDS_ATTENTION until they are focused (0 disables
+    /// this). Triggering a key binding ends the grace period so
+    /// that clients spawned by the binding are focused as normal.
+    Concrete focus_steal_grace_ms: u64; => 0;
+    /// the minimum number of milliseconds between running client_property_changed hooks for the
+    /// same property on the same client: changes arriving faster than this are coalesced into a
+    /// single trailing call once the window has passed
+    Concrete property_change_throttle_ms: u64; => 50;
+    /// the number of milliseconds that a single hook can run for before a warning is logged (0
+    /// disables the warning). Hooks block the event loop while they run.
+    Concrete hook_warning_threshold_ms: u64; => 100;
+    /// whether or not tiled clients on a workspace that share a WM_CLASS are collapsed into a
+    /// single tab-like group, showing only one of them at a time in the layout
+    Concrete group_by_class: bool; => false;
+    /// the number of recently processed events to keep in the WindowManager event log for
+    /// debugging. Recording is off by default (0) as every event is copied into the log.
+    Concrete event_log_size: usize; => 0;
+    /// whether or not moving the pointer into a client window gives it focus
+    Concrete focus_follows_mouse: bool; => true;
+    /// the [FocusPolicy] to use for clients with a given _NET_WM_WINDOW_TYPE when
+    /// `focus_follows_mouse` is enabled. Clients without a matching entry use sloppy focus
+    Concrete window_type_focus_policies: HashMap<String, FocusPolicy>; => HashMap::new();
+    /// where to place clients that were already running when penrose started if they do not
+    /// specify a workspace using _NET_WM_DESKTOP
+    Concrete existing_client_placement: ExistingClientPlacement; => ExistingClientPlacement::ActiveWorkspace;
+    /// whether or not the pointer is moved to the centre of a client when it gains focus
+    Concrete mouse_follows_focus: bool; => false;
 }
 
 impl Config {
@@ -101,6 +169,12 @@ impl Config {
     pub fn ws_range(&self) -> std::ops::Range<usize> {
         1..(self.workspaces.len() + 1)
     }
+
+    /// The per-side gaps to apply around tiled windows: `gaps` if it has been set, otherwise
+    /// `gap_px` for all four sides.
+    pub fn effective_gaps(&self) -> Gaps {
+        self.gaps.unwrap_or_else(|| Gaps::uniform(self.gap_px))
+    }
 }
 
 impl ConfigBuilder {
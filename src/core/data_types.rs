@@ -60,6 +60,37 @@ impl Default for Point {
     }
 }
 
+/// Per-side gaps (in pixels) to leave around tiled windows
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Gaps {
+    /// The gap above each window
+    pub top: u32,
+    /// The gap below each window
+    pub bottom: u32,
+    /// The gap to the left of each window
+    pub left: u32,
+    /// The gap to the right of each window
+    pub right: u32,
+}
+
+impl Gaps {
+    /// Create a new set of Gaps with independent values for each side.
+    pub fn new(top: u32, bottom: u32, left: u32, right: u32) -> Self {
+        Self {
+            top,
+            bottom,
+            left,
+            right,
+        }
+    }
+
+    /// Create a new set of Gaps using the same value for all four sides.
+    pub fn uniform(px: u32) -> Self {
+        Self::new(px, px, px, px)
+    }
+}
+
 /* Argument enums */
 
 /// Increment / decrement a value
@@ -72,6 +103,99 @@ pub enum Change {
     Less,
 }
 
+/// The axis along which a [Region] should be divided
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// split left to right into columns
+    Horizontal,
+    /// split top to bottom into rows
+    Vertical,
+}
+
+/// Where the leftover pixels go when a [Region] can not be divided evenly
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Remainder {
+    /// one extra pixel for each of the first cells
+    #[default]
+    FirstCells,
+    /// one extra pixel for each of the last cells
+    LastCells,
+    /// one extra pixel for each of the cells in the middle
+    Centre,
+}
+
+/// How the pointer gives focus to a client when `focus_follows_mouse` is enabled
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FocusPolicy {
+    /// the client is focused when the pointer enters it
+    #[default]
+    Sloppy,
+    /// the client is not focused when the pointer enters it and keeps focus when the pointer
+    /// leaves: it must be focused explicitly, for example by clicking on it
+    Click,
+}
+
+/// Where to place clients that were already mapped when penrose started and that have no valid
+/// _NET_WM_DESKTOP property set
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ExistingClientPlacement {
+    /// place all of the clients on the active workspace
+    #[default]
+    ActiveWorkspace,
+    /// spread the clients across all workspaces in turn, starting from the active workspace
+    RoundRobin,
+}
+
+/// A default position for new floating clients, relative to the screen they are shown on
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FloatingGeometry {
+    /// A fixed size and offset (in pixels) from the top left corner of the screen
+    Absolute(Region),
+    /// Offset and size as fractions of the screen dimensions: (x, y, w, h)
+    Ratio(f32, f32, f32, f32),
+}
+
+impl FloatingGeometry {
+    /// The absolute [Region] described by this geometry when placed on `screen`.
+    ///
+    /// The result is clamped so that it does not extend past the edges of `screen`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use penrose::core::data_types::{FloatingGeometry, Region};
+    ///
+    /// let screen = Region::new(100, 0, 1000, 800);
+    ///
+    /// let abs = FloatingGeometry::Absolute(Region::new(10, 20, 300, 200));
+    /// assert_eq!(abs.region_in(&screen), Region::new(110, 20, 300, 200));
+    ///
+    /// let ratio = FloatingGeometry::Ratio(0.25, 0.25, 0.5, 0.5);
+    /// assert_eq!(ratio.region_in(&screen), Region::new(350, 200, 500, 400));
+    /// ```
+    pub fn region_in(&self, screen: &Region) -> Region {
+        let (sx, sy, sw, sh) = screen.values();
+        let (x, y, w, h) = match *self {
+            Self::Absolute(r) => r.values(),
+            Self::Ratio(x, y, w, h) => (
+                (sw as f32 * x) as u32,
+                (sh as f32 * y) as u32,
+                (sw as f32 * w) as u32,
+                (sh as f32 * h) as u32,
+            ),
+        };
+
+        let x = x.min(sw);
+        let y = y.min(sh);
+        Region::new(sx + x, sy + y, w.min(sw - x), h.min(sh - y))
+    }
+}
+
 /// X window border kind
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -239,6 +363,37 @@ impl Region {
         })
     }
 
+    /// Move this region so that it lies entirely within `bounds`, shrinking it only if it is
+    /// larger than `bounds` in either dimension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use penrose::core::data_types::Region;
+    ///
+    /// let screen = Region::new(0, 0, 1000, 800);
+    ///
+    /// // off the right edge: shifted left
+    /// let r = Region::new(900, 100, 300, 200);
+    /// assert_eq!(r.clamp_within(&screen), Region::new(700, 100, 300, 200));
+    ///
+    /// // larger than the screen: shrunk to fit
+    /// let r = Region::new(50, 50, 1200, 900);
+    /// assert_eq!(r.clamp_within(&screen), Region::new(0, 0, 1000, 800));
+    ///
+    /// // already inside: unchanged
+    /// let r = Region::new(10, 20, 30, 40);
+    /// assert_eq!(r.clamp_within(&screen), r);
+    /// ```
+    pub fn clamp_within(&self, bounds: &Region) -> Region {
+        let w = self.w.min(bounds.w);
+        let h = self.h.min(bounds.h);
+        let x = self.x.max(bounds.x).min(bounds.x + bounds.w - w);
+        let y = self.y.max(bounds.y).min(bounds.y + bounds.h - h);
+
+        Region::new(x, y, w, h)
+    }
+
     /// Split this `Region` into evenly sized rows.
     ///
     /// # Examples
@@ -289,6 +444,135 @@ impl Region {
             .collect()
     }
 
+    /// Split this `Region` into `n` pieces along the given [Axis] that exactly tile `self`.
+    ///
+    /// Unlike [as_rows][Region::as_rows] and [as_columns][Region::as_columns], pixels left over
+    /// from uneven division are not dropped: each is given to a different piece, with `remainder`
+    /// controlling which pieces receive them. Splitting into zero pieces results in an empty `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use penrose::core::data_types::{Axis, Region, Remainder};
+    ///
+    /// let r = Region::new(0, 0, 100, 50);
+    /// let widths = |rem| -> Vec<u32> {
+    ///     r.split_evenly(6, Axis::Horizontal, rem).iter().map(|r| r.w).collect()
+    /// };
+    ///
+    /// assert_eq!(widths(Remainder::FirstCells), vec![17, 17, 17, 17, 16, 16]);
+    /// assert_eq!(widths(Remainder::LastCells), vec![16, 16, 17, 17, 17, 17]);
+    /// assert_eq!(widths(Remainder::Centre), vec![16, 17, 17, 17, 17, 16]);
+    ///
+    /// // the pieces always tile the original region exactly
+    /// for rem in [Remainder::FirstCells, Remainder::LastCells, Remainder::Centre] {
+    ///     let regions = r.split_evenly(6, Axis::Horizontal, rem);
+    ///     assert_eq!(regions[0].x, 0);
+    ///     assert!(regions.windows(2).all(|w| w[0].x + w[0].w == w[1].x));
+    ///     assert_eq!(regions[5].x + regions[5].w, 100);
+    /// }
+    ///
+    /// let regions = r.split_evenly(3, Axis::Vertical, Remainder::Centre);
+    /// assert_eq!(
+    ///     regions,
+    ///     vec![
+    ///         Region::new(0, 0, 100, 17),
+    ///         Region::new(0, 17, 100, 17),
+    ///         Region::new(0, 34, 100, 16),
+    ///     ]
+    /// );
+    ///
+    /// assert!(r.split_evenly(0, Axis::Vertical, Remainder::default()).is_empty());
+    /// ```
+    pub fn split_evenly(&self, n: u32, direction: Axis, remainder: Remainder) -> Vec<Region> {
+        if n == 0 {
+            return vec![];
+        }
+
+        let extent = match direction {
+            Axis::Horizontal => self.w,
+            Axis::Vertical => self.h,
+        };
+        let (size, extra) = (extent / n, extent % n);
+        let first_extra = match remainder {
+            Remainder::FirstCells => 0,
+            Remainder::LastCells => n - extra,
+            Remainder::Centre => (n - extra) / 2,
+        };
+
+        let mut offset = 0;
+        (0..n)
+            .map(|i| {
+                let size = if (first_extra..first_extra + extra).contains(&i) {
+                    size + 1
+                } else {
+                    size
+                };
+                let r = match direction {
+                    Axis::Horizontal => Region::new(self.x + offset, self.y, size, self.h),
+                    Axis::Vertical => Region::new(self.x, self.y + offset, self.w, size),
+                };
+                offset += size;
+                r
+            })
+            .collect()
+    }
+
+    /// Split this `Region` into pieces along the given [Axis], sized proportionally to `weights`.
+    ///
+    /// Weights are normalised internally so only their relative sizes matter. Any pixels lost to
+    /// rounding are given to the final piece so that the returned regions exactly tile `self`. An
+    /// empty slice of weights (or one where all weights are zero) results in an empty `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use penrose::core::data_types::{Axis, Region};
+    ///
+    /// let r = Region::new(0, 0, 90, 100);
+    ///
+    /// let regions = r.split_weighted(&[2.0, 1.0], Axis::Horizontal);
+    /// assert_eq!(regions, vec![Region::new(0, 0, 60, 100), Region::new(60, 0, 30, 100)]);
+    ///
+    /// let regions = r.split_weighted(&[1.0, 1.0], Axis::Vertical);
+    /// assert_eq!(regions, r.as_rows(2));
+    ///
+    /// assert!(r.split_weighted(&[], Axis::Vertical).is_empty());
+    /// assert!(r.split_weighted(&[0.0, 0.0], Axis::Vertical).is_empty());
+    /// ```
+    pub fn split_weighted(&self, weights: &[f32], direction: Axis) -> Vec<Region> {
+        let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+        if total <= 0.0 {
+            return vec![];
+        }
+
+        let extent = match direction {
+            Axis::Horizontal => self.w,
+            Axis::Vertical => self.h,
+        };
+
+        let mut offset = 0;
+        let n = weights.len();
+
+        weights
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                let size = if i == n - 1 {
+                    extent - offset
+                } else {
+                    ((w.max(0.0) / total) * extent as f32).floor() as u32
+                };
+                let r = match direction {
+                    Axis::Horizontal => Region::new(self.x + offset, self.y, size, self.h),
+                    Axis::Vertical => Region::new(self.x, self.y + offset, self.w, size),
+                };
+                offset += size;
+                r
+            })
+            .collect()
+    }
+
     /// Divides this region into two columns where the first has the given width.
     ///
     /// # Errors
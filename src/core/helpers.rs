@@ -5,35 +5,45 @@ use crate::{
 };
 
 use std::{
-    io::Read,
+    fs,
+    io::{ErrorKind, Read},
+    path::Path,
     process::{Command, Stdio},
 };
 
 /// Run an external command
 ///
-/// This redirects the process stdout and stderr to /dev/null.
+/// The command is split on whitespace into a program and its arguments. This does not block
+/// waiting for the process to complete and redirects the process stdout and stderr to /dev/null.
+///
+/// # Errors
+/// Fails with [PenroseError::SpawnProc] if `cmd` is empty or the process could not be started.
 pub fn spawn<S: Into<String>>(cmd: S) -> Result<()> {
-    let s = cmd.into();
-    let parts: Vec<&str> = s.split_whitespace().collect();
-    let result = if parts.len() > 1 {
-        Command::new(parts[0])
-            .args(&parts[1..])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()
-    } else {
-        Command::new(parts[0])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()
-    };
-
-    match result {
+    let cmd = cmd.into();
+    match build_command(&cmd)?.spawn() {
         Ok(_) => Ok(()),
-        Err(e) => Err(e.into()),
+        Err(e) => Err(PenroseError::SpawnProc(format!("{}: {}", cmd, e))),
     }
 }
 
+// Split 'cmd' into a program and its arguments, ready to be run with stdout and stderr
+// redirected to /dev/null.
+pub(crate) fn build_command(cmd: &str) -> Result<Command> {
+    let parts: Vec<&str> = cmd.split_whitespace().collect();
+    let (prog, args) = match parts.split_first() {
+        Some(split) => split,
+        None => return Err(PenroseError::SpawnProc("empty command".into())),
+    };
+
+    let mut command = Command::new(prog);
+    command
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    Ok(command)
+}
+
 /// Run an external command with the specified command line arguments
 ///
 /// This redirects the process stdout and stderr to /dev/null.
@@ -72,7 +82,7 @@ pub fn spawn_for_output<S: Into<String>>(cmd: S) -> Result<String> {
     let mut buff = String::new();
     Ok(child
         .stdout
-        .ok_or(PenroseError::SpawnProc(cmd))?
+        .ok_or_else(|| PenroseError::SpawnProc(format!("unable to get stdout handle for {}", cmd)))?
         .read_to_string(&mut buff)
         .map(|_| buff)?)
 }
@@ -95,7 +105,7 @@ pub fn spawn_for_output_with_args<S: Into<String>>(cmd: S, args: &[&str]) -> Res
     let mut buff = String::new();
     Ok(child
         .stdout
-        .ok_or(PenroseError::SpawnProc(cmd))?
+        .ok_or_else(|| PenroseError::SpawnProc(format!("unable to get stdout handle for {}", cmd)))?
         .read_to_string(&mut buff)
         .map(|_| buff)?)
 }
@@ -133,6 +143,22 @@ pub fn keycodes_from_xmodmap() -> CodeMap {
     }
 }
 
+/// Read and remove the state file written by [WindowManager::restart][1].
+///
+/// Returns `Ok(None)` if there is no state file at `path`, which is the case for a normal startup.
+///
+/// [1]: crate::core::manager::WindowManager::restart
+pub fn take_restart_state<P: AsRef<Path>>(path: P) -> Result<Option<String>> {
+    let state = match fs::read_to_string(&path) {
+        Ok(state) => state,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    fs::remove_file(path)?;
+
+    Ok(Some(state))
+}
+
 /// Create a Vec of index selectors for the given input slice
 pub fn index_selectors<'a, T>(len: usize) -> Vec<Selector<'a, T>> {
     (0..len).map(Selector::Index).collect()
@@ -142,3 +168,25 @@ pub fn index_selectors<'a, T>(len: usize) -> Vec<Selector<'a, T>> {
 pub fn logging_error_handler() -> ErrorHandler {
     Box::new(|e: PenroseError| error!("{}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_command_splits_commands_into_program_and_args() {
+        let cmd = build_command("  dmenu_run -b  -p run ").unwrap();
+        let args: Vec<_> = cmd.get_args().collect();
+
+        assert_eq!(cmd.get_program(), "dmenu_run");
+        assert_eq!(args, vec!["-b", "-p", "run"]);
+    }
+
+    #[test]
+    fn build_command_rejects_empty_commands() {
+        assert!(matches!(
+            build_command("  "),
+            Err(PenroseError::SpawnProc(_))
+        ));
+    }
+}
@@ -112,15 +112,37 @@
 //! generate [XEvents][3] will only be processed once all hooks have run and control has returned to
 //! the manager itself.
 //!
+//! # Slow hooks
+//!
+//! Hooks are run synchronously on the same thread as the event loop, so a hook that blocks (for
+//! example, by waiting on the output of a slow subprocess) will freeze the entire window manager
+//! until it returns. As each hook is given a mutable reference to the `WindowManager` it is not
+//! possible for penrose to run it on a worker thread and abandon it after a timeout. Instead, any
+//! hook that takes longer than the `hook_warning_threshold_ms` set in your [Config][5] is logged
+//! as a warning, naming the trigger and the position of the hook in your hooks `Vec`, to help
+//! track down the source of any freezes. If your hook needs to do slow work, move that work to a
+//! thread or subprocess of its own (see [spawn][6]) that does not need access to the
+//! `WindowManager`.
+//!
+//! # Suspending hooks
+//!
+//! Bulk operations can trigger a large number of hooks in quick succession. Wrapping them in
+//! calls to [suspend_hooks][7] and [resume_hooks][8] prevents any hooks from running in the
+//! meantime, optionally running each suppressed trigger once when hooks are resumed.
+//!
 //! [1]: crate::core::workspace::Workspace
 //! [2]: crate::contrib::extensions::scratchpad::Scratchpad
 //! [3]: crate::core::xconnection::XEvent
 //! [4]: crate::core::client::Client
+//! [5]: crate::core::config::Config
+//! [6]: crate::core::helpers::spawn
+//! [7]: crate::core::manager::WindowManager::suspend_hooks
+//! [8]: crate::core::manager::WindowManager::resume_hooks
 use crate::{
     core::{
         data_types::Region,
         manager::WindowManager,
-        xconnection::{XConn, Xid},
+        xconnection::{XConn, XEvent, Xid},
     },
     Result,
 };
@@ -139,8 +161,10 @@ pub enum HookName {
     RemoveClient(Xid),
     ClientAddedToWorkspace(Xid, usize),
     ClientNameUpdated(Xid, String, bool),
+    ClientPropertyChanged(Xid, String),
     LayoutApplied(usize, usize),
     LayoutChange(usize),
+    LayoutParamsChanged(usize),
     WorkspaceChange(usize, usize),
     WorkspacesUpdated(Vec<String>, usize),
     ScreenChange,
@@ -148,6 +172,7 @@ pub enum HookName {
     RanderNotify,
     FocusChange(u32),
     EventHandled,
+    RawEvent(XEvent),
 }
 
 /// Utility type for defining hooks in your penrose configuration.
@@ -293,6 +318,31 @@ pub trait Hook<X: XConn> {
         Ok(())
     }
 
+    /// # Trigger Point
+    ///
+    /// Called whenever a property other than WM_NAME or _NET_WM_NAME is changed on a managed
+    /// [Client][1]. `atom` is the name of the property that changed. Repeated changes to the same
+    /// property of a client within `property_change_throttle_ms` of each other are coalesced:
+    /// the first change is reported straight away and, if there were further changes, this is
+    /// called once more after the throttle window has passed. That trailing call is made after
+    /// the next event is processed, so it may be delayed if the X server is idle.
+    ///
+    /// # Example Uses
+    ///
+    /// Reacting to custom properties set by external programs or less common properties that
+    /// penrose itself does not track.
+    ///
+    /// [1]: crate::core::client::Client
+    #[allow(unused_variables)]
+    fn client_property_changed(
+        &mut self,
+        wm: &mut WindowManager<X>,
+        id: Xid,
+        atom: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     /// # Trigger Point
     ///
     /// Called after a [Layout][1] is applied to the active Workspace.
@@ -354,6 +404,35 @@ pub trait Hook<X: XConn> {
         Ok(())
     }
 
+    /// # Trigger Point
+    ///
+    /// Called after the `max_main` or `main_ratio` of a workspace's active [Layout][1] has been
+    /// modified via [update_max_main][4] or [update_main_ratio][5]. Not called if the requested
+    /// change had no effect (e.g. the ratio was already at its limit).
+    ///
+    /// Arguments are indices into the WindowManager workspace and screen rings (internal data
+    /// structures that support indexing) which can be used to fetch references to the active [Workspace][2]
+    /// and [Screen][3].
+    ///
+    /// # Example Uses
+    ///
+    /// Updating a status bar widget that displays the current layout parameters.
+    ///
+    /// [1]: crate::core::layout::Layout
+    /// [2]: crate::core::workspace::Workspace
+    /// [3]: crate::core::screen::Screen
+    /// [4]: crate::core::manager::WindowManager::update_max_main
+    /// [5]: crate::core::manager::WindowManager::update_main_ratio
+    #[allow(unused_variables)]
+    fn layout_params_changed(
+        &mut self,
+        wm: &mut WindowManager<X>,
+        workspace_index: usize,
+        screen_index: usize,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     /// # Trigger Point
     ///
     /// Called after the active [Workspace][1] is changed on a [Screen][2].
@@ -485,4 +564,46 @@ pub trait Hook<X: XConn> {
     fn event_handled(&mut self, wm: &mut WindowManager<X>) -> Result<()> {
         Ok(())
     }
+
+    /// # Trigger Point
+    ///
+    /// Called at the top of the main [WindowManager] event loop for each [XEvent] received from
+    /// the X server, before it is processed by the `WindowManager`.
+    ///
+    /// The event is passed by reference: hooks can observe it but are unable to modify or
+    /// consume it.
+    ///
+    /// # Example Uses
+    ///
+    /// Logging or otherwise reacting to events that penrose does not handle itself.
+    #[allow(unused_variables)]
+    fn raw_event(&mut self, wm: &mut WindowManager<X>, event: &XEvent) -> Result<()> {
+        Ok(())
+    }
+
+    /// # Trigger Point
+    ///
+    /// Called by [kill_workspace_clients][1] before any of the clients on the target
+    /// [Workspace][2] are asked to close.
+    ///
+    /// Unlike other hooks, returning an error from this hook will prevent the clients from being
+    /// closed: the error is returned to the caller of `kill_workspace_clients` and no further
+    /// hooks are run.
+    ///
+    /// # Example Uses
+    ///
+    /// Logging which clients are being closed or vetoing the action if the workspace contains
+    /// clients that should be kept open.
+    ///
+    /// [1]: crate::core::manager::WindowManager::kill_workspace_clients
+    /// [2]: crate::core::workspace::Workspace
+    #[allow(unused_variables)]
+    fn kill_workspace_clients(
+        &mut self,
+        wm: &mut WindowManager<X>,
+        workspace_index: usize,
+        ids: &[Xid],
+    ) -> Result<()> {
+        Ok(())
+    }
 }
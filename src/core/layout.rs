@@ -60,7 +60,7 @@ use crate::core::{
     xconnection::Xid,
 };
 
-use std::{cmp, fmt};
+use std::{cmp, collections::HashMap, fmt};
 
 /// When and how a Layout should be applied.
 ///
@@ -77,6 +77,18 @@ pub struct LayoutConf {
     pub follow_focus: bool,
     /// Should cycling clients wrap at the first and last client?
     pub allow_wrapping: bool,
+    /// Border width in pixels to use in place of the global `border_px` while this layout is
+    /// active. This applies to both tiled and floating clients on the workspace. If `None` then
+    /// the global value from [Config][1] is used.
+    ///
+    /// [1]: crate::core::config::Config
+    pub border_width: Option<u32>,
+    /// Constrain the main client to this width:height aspect ratio (e.g. `(16, 9)`), centering it
+    /// within the area the layout assigns to it. If `None` the main client fills its area.
+    pub main_aspect_ratio: Option<(u32, u32)>,
+    /// Should this layout be skipped when cycling layouts? Excluded layouts can still be selected
+    /// directly by their symbol.
+    pub cycle_exclude: bool,
 }
 
 impl Default for LayoutConf {
@@ -86,6 +98,9 @@ impl Default for LayoutConf {
             gapless: false,
             follow_focus: false,
             allow_wrapping: true,
+            border_width: None,
+            main_aspect_ratio: None,
+            cycle_exclude: false,
         }
     }
 }
@@ -178,6 +193,9 @@ impl Layout {
                 gapless: false,
                 follow_focus: false,
                 allow_wrapping: true,
+                border_width: None,
+                main_aspect_ratio: None,
+                cycle_exclude: false,
             },
             f: Some(floating),
             max_main: 1,
@@ -199,11 +217,31 @@ impl Layout {
         focused: Option<Xid>,
         r: &Region,
     ) -> Vec<ResizeAction> {
-        (self.f.expect("missing layout function"))(clients, focused, r, self.max_main, self.ratio)
+        let mut actions = (self.f.expect("missing layout function"))(
+            clients,
+            focused,
+            r,
+            self.max_main,
+            self.ratio,
+        );
+
+        if let (Some((rw, rh)), Some(main)) = (self.conf.main_aspect_ratio, clients.first()) {
+            let main_id = main.id();
+            for (id, region) in actions.iter_mut() {
+                if *id == main_id {
+                    *region = region.map(|r| fit_aspect_ratio(&r, rw, rh));
+                }
+            }
+        }
+
+        actions
     }
 
     /// Increase/decrease the number of clients in the main area by 1
-    pub fn update_max_main(&mut self, change: Change) {
+    ///
+    /// Returns `true` if the number of clients in the main area was changed.
+    pub fn update_max_main(&mut self, change: Change) -> bool {
+        let prev = self.max_main;
         match change {
             Change::More => self.max_main += 1,
             Change::Less => {
@@ -212,11 +250,19 @@ impl Layout {
                 }
             }
         }
+
+        self.max_main != prev
     }
 
     /// Increase/decrease the size of the main area relative to secondary.
     /// (clamps at 1.0 and 0.0 respectively)
-    pub fn update_main_ratio(&mut self, change: Change, step: f32) {
+    ///
+    /// The axis that the ratio applies to is determined by the layout function itself: for
+    /// example [side_stack] splits the screen width while [bottom_stack] splits the height.
+    ///
+    /// Returns `true` if the ratio was changed.
+    pub fn update_main_ratio(&mut self, change: Change, step: f32) -> bool {
+        let prev = self.ratio;
         match change {
             Change::More => self.ratio += step,
             Change::Less => self.ratio -= step,
@@ -227,6 +273,8 @@ impl Layout {
         } else if self.ratio > 1.0 {
             self.ratio = 1.0;
         }
+
+        (self.ratio - prev).abs() > f32::EPSILON
     }
 }
 
@@ -234,6 +282,93 @@ impl Layout {
  * Utility functions for simplifying writing layouts
  */
 
+// The largest region with the given aspect ratio that fits inside of 'r', centered within it
+fn fit_aspect_ratio(r: &Region, ratio_w: u32, ratio_h: u32) -> Region {
+    if ratio_w == 0 || ratio_h == 0 {
+        return *r;
+    }
+
+    let (x, y, w, h) = r.values();
+    let (rw, rh) = (ratio_w as u64, ratio_h as u64);
+    let (new_w, new_h) = if w as u64 * rh > h as u64 * rw {
+        ((h as u64 * rw / rh) as u32, h) // too wide: limited by height
+    } else {
+        (w, (w as u64 * rh / rw) as u32) // too tall: limited by width
+    };
+
+    Region::new(x + (w - new_w) / 2, y + (h - new_h) / 2, new_w, new_h)
+}
+
+// Grow (or shrink) each client with a size delta by taking space from (or giving space to) an
+// adjacent client sharing the same column or row, preferring the client that follows it. Clients
+// without such a neighbour are left untouched so the total space used is always preserved.
+pub(crate) fn apply_size_deltas(actions: &mut [ResizeAction], deltas: &HashMap<Xid, i32>) {
+    let adjust = |v: u32, d: i64| (v as i64 + d) as u32;
+
+    for i in 0..actions.len() {
+        let (mut r, delta) = match actions[i] {
+            (id, Some(r)) => match deltas.get(&id) {
+                Some(&d) if d != 0 => (r, d as i64),
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        let neighbour = |pred: &dyn Fn(&Region) -> bool| {
+            actions.iter().enumerate().find_map(|(j, &(_, n))| match n {
+                Some(n) if j != i && pred(&n) => Some((j, n)),
+                _ => None,
+            })
+        };
+
+        let below = neighbour(&|n| n.x == r.x && n.w == r.w && n.y == r.y + r.h);
+        let above = neighbour(&|n| n.x == r.x && n.w == r.w && n.y + n.h == r.y);
+        let right = neighbour(&|n| n.y == r.y && n.h == r.h && n.x == r.x + r.w);
+        let left = neighbour(&|n| n.y == r.y && n.h == r.h && n.x + n.w == r.x);
+
+        let (j, n) = if let Some((j, mut n)) = below {
+            let d = clamp_delta(delta, r.h, n.h);
+            r.h = adjust(r.h, d);
+            n.y = adjust(n.y, d);
+            n.h = adjust(n.h, -d);
+            (j, n)
+        } else if let Some((j, mut n)) = above {
+            let d = clamp_delta(delta, r.h, n.h);
+            r.y = adjust(r.y, -d);
+            r.h = adjust(r.h, d);
+            n.h = adjust(n.h, -d);
+            (j, n)
+        } else if let Some((j, mut n)) = right {
+            let d = clamp_delta(delta, r.w, n.w);
+            r.w = adjust(r.w, d);
+            n.x = adjust(n.x, d);
+            n.w = adjust(n.w, -d);
+            (j, n)
+        } else if let Some((j, mut n)) = left {
+            let d = clamp_delta(delta, r.w, n.w);
+            r.x = adjust(r.x, -d);
+            r.w = adjust(r.w, d);
+            n.w = adjust(n.w, -d);
+            (j, n)
+        } else {
+            continue;
+        };
+
+        actions[i].1 = Some(r);
+        actions[j].1 = Some(n);
+    }
+}
+
+// Limit a size delta so that neither the resized client nor its neighbour drops below a single
+// pixel. Degenerate regions that are already too small to share space are left as they are.
+fn clamp_delta(delta: i64, own: u32, other: u32) -> i64 {
+    if own as u64 + (other as u64) < 2 {
+        return 0;
+    }
+
+    delta.clamp(1 - own as i64, other as i64 - 1)
+}
+
 /// number of clients for the main area vs secondary
 pub fn client_breakdown<T>(clients: &[T], n_main: u32) -> (u32, u32) {
     let n = clients.len() as u32;
@@ -362,3 +497,134 @@ pub fn monocle(
         Vec::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::xconnection::MockXConn;
+
+    test_cases! {
+        main_aspect_ratio;
+        args: (n_clients: u32, region: Region, ratio: (u32, u32));
+
+        case: single_wide_screen => (1, Region::new(0, 0, 2560, 1080), (16, 9));
+        case: single_tall_screen => (1, Region::new(0, 0, 1080, 1920), (16, 9));
+        case: with_stack => (3, Region::new(0, 0, 1920, 1080), (16, 9));
+        case: square => (2, Region::new(10, 20, 1366, 768), (1, 1));
+
+        body: {
+            let conn = MockXConn::new(vec![], vec![], vec![]);
+            let clients: Vec<Client> = (0..n_clients).map(|id| Client::new(&conn, id, 0, &[])).collect();
+            let client_refs: Vec<&Client> = clients.iter().collect();
+            let conf = LayoutConf {
+                main_aspect_ratio: Some(ratio),
+                ..Default::default()
+            };
+            let unconstrained = Layout::new("t", LayoutConf::default(), side_stack, 1, 0.6)
+                .arrange(&client_refs, None, &region);
+            let layout = Layout::new("t", conf, side_stack, 1, 0.6);
+
+            let actions = layout.arrange(&client_refs, None, &region);
+            let main = actions[0].1.unwrap();
+            let area = unconstrained[0].1.unwrap();
+            let (rw, rh) = ratio;
+
+            // within rounding tolerance of the requested ratio
+            let diff = (main.w as f64 / main.h as f64) - (rw as f64 / rh as f64);
+            assert!(diff.abs() < 0.01, "{:?} is not {}:{}", main, rw, rh);
+            assert!(area.contains(&main));
+            assert!(main.w == area.w || main.h == area.h);
+
+            // the stack is unaffected
+            assert_eq!(&actions[1..], &unconstrained[1..]);
+        }
+    }
+
+    test_cases! {
+        main_ratio_axis;
+        args: (f: LayoutFunc, vertical: bool);
+
+        case: side_stack_splits_width => (side_stack, false);
+        case: bottom_stack_splits_height => (bottom_stack, true);
+
+        body: {
+            let conn = MockXConn::new(vec![], vec![], vec![]);
+            let clients: Vec<Client> = (0..3).map(|id| Client::new(&conn, id, 0, &[])).collect();
+            let client_refs: Vec<&Client> = clients.iter().collect();
+            let region = Region::new(0, 0, 1000, 800);
+            let mut layout = Layout::new("t", LayoutConf::default(), f, 1, 0.5);
+
+            let before = layout.arrange(&client_refs, None, &region)[0].1.unwrap();
+            assert!(layout.update_main_ratio(Change::More, 0.25));
+            let after = layout.arrange(&client_refs, None, &region)[0].1.unwrap();
+
+            if vertical {
+                assert_eq!((before.w, before.h), (1000, 400));
+                assert_eq!((after.w, after.h), (1000, 600));
+            } else {
+                assert_eq!((before.w, before.h), (500, 800));
+                assert_eq!((after.w, after.h), (750, 800));
+            }
+        }
+    }
+
+    test_cases! {
+        size_deltas;
+        args: (actions: Vec<ResizeAction>, id: Xid, delta: i32, expected: Vec<ResizeAction>);
+
+        case: grow_takes_from_client_below => (
+            vec![(1, Some(Region::new(0, 0, 100, 50))), (2, Some(Region::new(0, 50, 100, 50)))],
+            1, 20,
+            vec![(1, Some(Region::new(0, 0, 100, 70))), (2, Some(Region::new(0, 70, 100, 30)))]
+        );
+        case: shrink_gives_to_client_below => (
+            vec![(1, Some(Region::new(0, 0, 100, 50))), (2, Some(Region::new(0, 50, 100, 50)))],
+            1, -20,
+            vec![(1, Some(Region::new(0, 0, 100, 30))), (2, Some(Region::new(0, 30, 100, 70)))]
+        );
+        case: last_in_column_takes_from_client_above => (
+            vec![(1, Some(Region::new(0, 0, 100, 50))), (2, Some(Region::new(0, 50, 100, 50)))],
+            2, 20,
+            vec![(1, Some(Region::new(0, 0, 100, 30))), (2, Some(Region::new(0, 30, 100, 70)))]
+        );
+        case: grow_takes_from_client_to_the_right => (
+            vec![(1, Some(Region::new(0, 0, 60, 50))), (2, Some(Region::new(60, 0, 40, 50)))],
+            1, 10,
+            vec![(1, Some(Region::new(0, 0, 70, 50))), (2, Some(Region::new(70, 0, 30, 50)))]
+        );
+        case: neighbour_is_never_removed_entirely => (
+            vec![(1, Some(Region::new(0, 0, 100, 50))), (2, Some(Region::new(0, 50, 100, 50)))],
+            1, 500,
+            vec![(1, Some(Region::new(0, 0, 100, 99))), (2, Some(Region::new(0, 99, 100, 1)))]
+        );
+        case: no_neighbour_is_a_noop => (
+            vec![(1, Some(Region::new(0, 0, 100, 50))), (2, Some(Region::new(0, 60, 50, 50)))],
+            1, 20,
+            vec![(1, Some(Region::new(0, 0, 100, 50))), (2, Some(Region::new(0, 60, 50, 50)))]
+        );
+        case: zero_height_neighbour_is_a_noop => (
+            vec![(1, Some(Region::new(0, 0, 100, 1))), (2, Some(Region::new(0, 1, 100, 0)))],
+            1, 20,
+            vec![(1, Some(Region::new(0, 0, 100, 1))), (2, Some(Region::new(0, 1, 100, 0)))]
+        );
+        case: zero_width_neighbour_is_a_noop => (
+            vec![(1, Some(Region::new(0, 0, 0, 50))), (2, Some(Region::new(0, 0, 1, 50)))],
+            2, -20,
+            vec![(1, Some(Region::new(0, 0, 0, 50))), (2, Some(Region::new(0, 0, 1, 50)))]
+        );
+
+        body: {
+            let mut actions = actions;
+            let deltas: HashMap<Xid, i32> = vec![(id, delta)].into_iter().collect();
+            let area = |a: &[ResizeAction]| -> u32 {
+                a.iter().map(|(_, r)| r.map(|r| r.w * r.h).unwrap_or(0)).sum()
+            };
+            let total = area(&actions);
+
+            apply_size_deltas(&mut actions, &deltas);
+
+            assert_eq!(actions, expected);
+            assert_eq!(area(&actions), total);
+        }
+    }
+}
@@ -2,21 +2,21 @@
 use crate::{
     core::{
         client::Client,
-        data_types::Region,
+        data_types::{Gaps, Region},
         hooks::HookName,
         layout::LayoutConf,
         manager::{event::EventAction, util::pad_region},
         ring::Selector,
         workspace::ArrangeActions,
         xconnection::{
-            Atom, ClientMessageKind, Prop, XClientConfig, XClientHandler, XClientProperties,
-            XEventHandler, XState, Xid,
+            self, Atom, ClientMessageKind, Prop, WindowState, XClientConfig, XClientHandler,
+            XClientProperties, XEventHandler, XState, Xid,
         },
     },
     draw::Color,
     Result,
 };
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap};
 use tracing::{trace, warn};
 
 #[derive(Debug)]
@@ -26,6 +26,70 @@ pub(super) struct Clients {
     focused_client_id: Option<Xid>,
     focused_border: Color,
     unfocused_border: Color,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    prop_cache: RefCell<PropCache>,
+}
+
+type PropCache = HashMap<Xid, HashMap<String, Prop>>;
+
+// A view of an X connection that serves client property reads from the Clients property cache,
+// allowing the XClientProperties helpers (and Client::new) to make use of it.
+pub(super) struct CachedProps<'a, X> {
+    cache: &'a RefCell<PropCache>,
+    conn: &'a X,
+}
+
+impl<'a, X> CachedProps<'a, X> {
+    fn invalidate(&self, id: Xid, name: &str) {
+        if let Some(props) = self.cache.borrow_mut().get_mut(&id) {
+            props.remove(name);
+        }
+    }
+}
+
+impl<'a, X> XClientProperties for CachedProps<'a, X>
+where
+    X: XClientProperties,
+{
+    fn list_props(&self, id: Xid) -> xconnection::Result<Vec<String>> {
+        self.conn.list_props(id)
+    }
+
+    fn get_prop(&self, id: Xid, name: &str) -> xconnection::Result<Prop> {
+        if let Some(prop) = self
+            .cache
+            .borrow()
+            .get(&id)
+            .and_then(|props| props.get(name))
+        {
+            trace!(id, name, "property cache hit");
+            return Ok(prop.clone());
+        }
+
+        let prop = self.conn.get_prop(id, name)?;
+        self.cache
+            .borrow_mut()
+            .entry(id)
+            .or_default()
+            .insert(name.to_string(), prop.clone());
+
+        Ok(prop)
+    }
+
+    fn delete_prop(&self, id: Xid, name: &str) -> xconnection::Result<()> {
+        self.invalidate(id, name);
+        self.conn.delete_prop(id, name)
+    }
+
+    fn change_prop(&self, id: Xid, name: &str, val: Prop) -> xconnection::Result<()> {
+        self.invalidate(id, name);
+        self.conn.change_prop(id, name, val)
+    }
+
+    fn set_client_state(&self, id: Xid, wm_state: WindowState) -> xconnection::Result<()> {
+        self.invalidate(id, Atom::WmState.as_ref());
+        self.conn.set_client_state(id, wm_state)
+    }
 }
 
 impl Clients {
@@ -35,6 +99,7 @@ impl Clients {
             focused_client_id: None,
             focused_border: focused_border.into(),
             unfocused_border: unfocused_border.into(),
+            prop_cache: RefCell::new(HashMap::new()),
         }
     }
 
@@ -129,9 +194,33 @@ impl Clients {
             self.focused_client_id = None;
         }
 
+        self.invalidate(id);
         self.inner.remove(&id)
     }
 
+    // Wrap the given connection so that client property reads go via the property cache
+    pub fn cached_props<'a, X>(&'a self, conn: &'a X) -> CachedProps<'a, X> {
+        CachedProps {
+            cache: &self.prop_cache,
+            conn,
+        }
+    }
+
+    // Fetch a property for the given client, hitting the X server only if we don't already have
+    // a cached value. Errors are not cached.
+    pub fn get_prop<X>(&self, id: Xid, name: &str, conn: &X) -> Result<Prop>
+    where
+        X: XClientProperties,
+    {
+        Ok(self.cached_props(conn).get_prop(id, name)?)
+    }
+
+    // Drop all cached properties for the given client so that the next read goes through to the
+    // X server. This needs to be called whenever we see a PropertyNotify for the client.
+    pub fn invalidate(&mut self, id: Xid) {
+        self.prop_cache.get_mut().remove(&id);
+    }
+
     pub fn get(&self, id: Xid) -> Option<&Client> {
         self.inner.get(&id)
     }
@@ -216,8 +305,8 @@ impl Clients {
         X: XState + XEventHandler + XClientConfig + XClientHandler + XClientProperties,
     {
         let accepts_focus = match self.inner.get(&id) {
-            Some(client) => client.accepts_focus,
-            None => conn.client_accepts_focus(id),
+            Some(client) => client.accepts_focus(),
+            None => self.cached_props(conn).client_accepts_focus(id),
         };
 
         self.set_x_focus(id, accepts_focus, conn)
@@ -251,7 +340,7 @@ impl Clients {
     where
         X: XClientProperties,
     {
-        let name = conn.client_name(id)?;
+        let name = self.cached_props(conn).client_name(id)?;
         if !is_root {
             if let Some(c) = self.inner.get_mut(&id) {
                 c.set_name(&name)
@@ -278,7 +367,8 @@ impl Clients {
         for (id, region) in actions.actions {
             trace!(id, ?region, "positioning client");
             if let Some(region) = region {
-                let reg = pad_region(&region, lc.gapless, gap_px, border_px);
+                let border_px = lc.border_width.unwrap_or(border_px);
+                let reg = pad_region(&region, lc.gapless, Gaps::uniform(gap_px), border_px);
                 conn.position_client(id, reg, border_px, false)?;
                 self.map_if_needed(id, conn)?;
             } else {
@@ -441,6 +531,7 @@ mod tests {
                 focused_client_id: None,
                 focused_border: 0xffffff.into(),
                 unfocused_border: 0x000000.into(),
+                prop_cache: RefCell::new(HashMap::new()),
             };
 
             let r = Region::new(0, 0, 1000, 800);
@@ -462,4 +553,67 @@ mod tests {
             assert_eq!(conn.unmaps.take(), expected_unmaps);
         }
     }
+
+    test_cases! {
+        mapping_state_transitions;
+        args: (initially_mapped: bool, map: bool, expected_maps: Vec<Xid>, expected_unmaps: Vec<Xid>);
+
+        case: map_already_mapped => (true, true, vec![], vec![]);
+        case: map_unmapped => (false, true, vec![1], vec![]);
+        case: unmap_mapped => (true, false, vec![], vec![1]);
+        case: unmap_already_unmapped => (false, false, vec![], vec![]);
+
+        body: {
+            let conn = RecordingXConn::init();
+            let mut clients = Clients::new(0xffffff, 0x000000);
+            let mut client = Client::new(&conn, 1, 0, &[]);
+            client.mapped = initially_mapped;
+            clients.insert(1, client);
+
+            // Repeated requests should only result in a single X call
+            for _ in 0..2 {
+                if map {
+                    clients.map_if_needed(1, &conn).unwrap();
+                } else {
+                    clients.unmap_if_needed(1, &conn).unwrap();
+                }
+            }
+
+            assert_eq!(clients.get(1).unwrap().mapped, map);
+            assert_eq!(conn.maps.take(), expected_maps);
+            assert_eq!(conn.unmaps.take(), expected_unmaps);
+        }
+    }
+
+    struct CountingXConn {
+        get_prop_calls: Cell<usize>,
+    }
+
+    impl StubXClientProperties for CountingXConn {
+        fn mock_get_prop(&self, _: Xid, _: &str) -> xconnection::Result<Prop> {
+            self.get_prop_calls.set(self.get_prop_calls.get() + 1);
+            Ok(Prop::UTF8String(vec!["mock name".into()]))
+        }
+    }
+
+    #[test]
+    fn cached_props_do_not_hit_the_x_server() {
+        let conn = CountingXConn {
+            get_prop_calls: Cell::new(0),
+        };
+        let mut clients = Clients::new(0xffffff, 0x000000);
+        let name = Atom::WmName.as_ref();
+
+        let first = clients.get_prop(1, name, &conn).unwrap();
+        let second = clients.get_prop(1, name, &conn).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(conn.get_prop_calls.get(), 1);
+
+        clients.get_prop(2, name, &conn).unwrap();
+        assert_eq!(conn.get_prop_calls.get(), 2);
+
+        clients.invalidate(1);
+        clients.get_prop(1, name, &conn).unwrap();
+        assert_eq!(conn.get_prop_calls.get(), 3);
+    }
 }
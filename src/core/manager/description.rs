@@ -0,0 +1,93 @@
+//! A read-only snapshot of the state of a [WindowManager][1] for debugging.
+//!
+//! [1]: crate::core::manager::WindowManager
+use crate::core::{client::Client, data_types::Region, xconnection::Xid};
+
+/// A point in time description of the screens, workspaces and clients known to a
+/// [WindowManager][1].
+///
+/// Unlike the serialized state used by [WindowManager::restart][2], this is a simplified view
+/// that is intended for inclusion in bug reports and comparison in tests: it can not be used to
+/// restore a `WindowManager`.
+///
+/// [1]: crate::core::manager::WindowManager
+/// [2]: crate::core::manager::WindowManager::restart
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateDescription {
+    /// The index of the focused screen
+    pub focused_screen: usize,
+    /// The ID of the focused client if there is one
+    pub focused_client: Option<Xid>,
+    /// The currently connected screens
+    pub screens: Vec<ScreenDescription>,
+    /// The workspaces, in index order
+    pub workspaces: Vec<WorkspaceDescription>,
+}
+
+/// A description of a single connected screen
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenDescription {
+    /// The index of the workspace being displayed
+    pub workspace: usize,
+    /// The full region of the screen
+    pub region: Region,
+    /// The region available to clients once space for a bar has been reserved
+    pub effective_region: Region,
+    /// Whether or not this is the primary screen
+    pub primary: bool,
+}
+
+/// A description of a single workspace and the clients it holds
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceDescription {
+    /// The name of the workspace
+    pub name: String,
+    /// The symbol of the active layout
+    pub layout: String,
+    /// Whether or not the active layout is locked
+    pub layout_locked: bool,
+    /// The ID of the focused client if there is one
+    pub focused_client: Option<Xid>,
+    /// The clients on this workspace, in layout order
+    pub clients: Vec<ClientDescription>,
+}
+
+/// A description of a single client and its state flags
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientDescription {
+    /// The X ID of the client window
+    pub id: Xid,
+    /// The WM_CLASS of the client
+    pub class: String,
+    /// The WM_NAME of the client
+    pub name: String,
+    /// The last region that the client was positioned in
+    pub region: Option<Region>,
+    /// Whether or not the client is floating
+    pub floating: bool,
+    /// Whether or not the client is fullscreen
+    pub fullscreen: bool,
+    /// Whether or not the client is mapped
+    pub mapped: bool,
+    /// Whether or not the client is urgent
+    pub urgent: bool,
+}
+
+impl From<&Client> for ClientDescription {
+    fn from(c: &Client) -> Self {
+        Self {
+            id: c.id,
+            class: c.wm_class().to_string(),
+            name: c.wm_name().to_string(),
+            region: c.last_region,
+            floating: c.floating,
+            fullscreen: c.fullscreen,
+            mapped: c.mapped,
+            urgent: c.urgent,
+        }
+    }
+}
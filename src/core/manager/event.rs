@@ -2,7 +2,7 @@
 /// messages to penrose actions is done.
 use crate::core::{
     bindings::{KeyCode, MouseEvent},
-    data_types::{Point, Region},
+    data_types::{FocusPolicy, Point, Region},
     hooks::HookName,
     manager::state::WmState,
     xconnection::{
@@ -45,7 +45,8 @@ pub enum EventAction {
     LayoutWorkspace(usize),
     /// A new X window needs to be mapped
     MapWindow(Xid),
-    /// A client is requesting to be moved: honoured if the client is floating
+    /// A client is requesting to be moved or resized: honoured if the client is floating,
+    /// otherwise tiled clients are returned to their position in the current layout
     MoveClientIfFloating(Xid, Region),
     /// The named hook should now be run
     RunHook(HookName),
@@ -59,6 +60,8 @@ pub enum EventAction {
     SetActiveWorkspace(usize),
     /// The active screen should be set based on point location
     SetScreenFromPoint(Option<Point>),
+    /// An X window should be kept above (first flag) or below (second flag) its peers
+    SetClientStacking(Xid, bool, bool),
     /// An X window should be set fullscreen
     ToggleClientFullScreen(Xid, bool),
     /// An unknown property was changed on an X window
@@ -77,10 +80,11 @@ where
         XEvent::Expose(_) => vec![], // FIXME: work out if this needs handling in the WindowManager
         XEvent::FocusIn(id) => vec![EventAction::FocusIn(id)],
         XEvent::KeyPress(code) => vec![EventAction::RunKeyBinding(code)],
-        XEvent::Leave(p) => vec![
+        XEvent::Leave(p) if focus_follows_mouse_for(state, p.id) => vec![
             EventAction::ClientFocusLost(p.id),
             EventAction::SetScreenFromPoint(Some(p.abs)),
         ],
+        XEvent::Leave(p) => vec![EventAction::SetScreenFromPoint(Some(p.abs))],
         XEvent::MouseEvent(evt) => vec![EventAction::RunMouseBinding(evt)],
         XEvent::RandrNotify => vec![EventAction::DetectScreens],
         XEvent::ScreenChange => vec![EventAction::SetScreenFromPoint(None)],
@@ -98,37 +102,80 @@ where
     }
 }
 
-fn process_client_message<X>(_state: &WmState, conn: &X, msg: ClientMessage) -> Vec<EventAction>
+fn process_client_message<X>(state: &WmState, conn: &X, msg: ClientMessage) -> Vec<EventAction>
 where
     X: XAtomQuerier,
 {
     let data = msg.data();
     trace!(id = msg.id, dtype = ?msg.dtype, ?data, "got client message");
 
-    let is_fullscreen = |data: &[u32]| {
-        data.iter()
-            .map(|&a| conn.atom_name(a))
-            .flatten()
-            .any(|s| s == Atom::NetWmStateFullscreen.as_ref())
-    };
-
     match Atom::from_str(&msg.dtype) {
         Ok(Atom::NetActiveWindow) => vec![EventAction::SetActiveClient(msg.id)],
         Ok(Atom::NetCurrentDesktop) => vec![EventAction::SetActiveWorkspace(data.as_usize()[0])],
         Ok(Atom::NetWmDesktop) => vec![EventAction::ClientToWorkspace(msg.id, data.as_usize()[0])],
-        Ok(Atom::NetWmState) if is_fullscreen(&data.as_u32()[1..3]) => {
-            // _NET_WM_STATE_ADD == 1, _NET_WM_STATE_TOGGLE == 2
-            let should_fullscreen = [1, 2].contains(&data.as_usize()[0]);
-            vec![EventAction::ToggleClientFullScreen(
-                msg.id,
-                should_fullscreen,
-            )]
+        Ok(Atom::NetWmState) => {
+            let action = data.as_usize()[0];
+            let atoms: Vec<String> = data.as_u32()[1..3]
+                .iter()
+                .filter_map(|&a| conn.atom_name(a).ok())
+                .collect();
+
+            let mut actions = vec![];
+            if atoms
+                .iter()
+                .any(|s| s == Atom::NetWmStateFullscreen.as_ref())
+            {
+                // _NET_WM_STATE_ADD == 1, _NET_WM_STATE_TOGGLE == 2
+                let should_fullscreen = [1, 2].contains(&action);
+                actions.push(EventAction::ToggleClientFullScreen(
+                    msg.id,
+                    should_fullscreen,
+                ));
+            }
+            actions.extend(process_stacking_request(state, msg.id, action, &atoms));
+
+            actions
         }
 
         _ => vec![],
     }
 }
 
+// Requests to remove (0), add (1) or toggle (2) _NET_WM_STATE_ABOVE / _NET_WM_STATE_BELOW for a
+// known client. Above and below are mutually exclusive so setting one clears the other.
+fn process_stacking_request(
+    state: &WmState,
+    id: Xid,
+    action: usize,
+    atoms: &[String],
+) -> Option<EventAction> {
+    let c = state.clients.get(id)?;
+    let current = (c.is_above(), c.is_below());
+    let update = |flag: bool| match action {
+        0 => false,
+        1 => true,
+        2 => !flag,
+        _ => flag,
+    };
+
+    let (mut above, mut below) = current;
+    for a in atoms {
+        if a == Atom::NetWmStateAbove.as_ref() {
+            above = update(above);
+            below = below && !above;
+        } else if a == Atom::NetWmStateBelow.as_ref() {
+            below = update(below);
+            above = above && !below;
+        }
+    }
+
+    if (above, below) == current {
+        None
+    } else {
+        Some(EventAction::SetClientStacking(id, above, below))
+    }
+}
+
 fn process_configure_notify(evt: ConfigureEvent) -> Vec<EventAction> {
     if evt.is_root {
         vec![EventAction::DetectScreens]
@@ -145,7 +192,28 @@ fn process_configure_request(evt: ConfigureEvent) -> Vec<EventAction> {
     }
 }
 
+// Whether or not pointer motion should change focus for the given window, taking into account
+// any focus policy set for its window type.
+fn focus_follows_mouse_for(state: &WmState, id: Xid) -> bool {
+    if !state.config.focus_follows_mouse {
+        return false;
+    }
+
+    let policies = &state.config.window_type_focus_policies;
+    match state.clients.get(id) {
+        Some(c) => !c
+            .wm_type()
+            .iter()
+            .any(|t| policies.get(t) == Some(&FocusPolicy::Click)),
+        None => true,
+    }
+}
+
 fn process_enter_notify(state: &WmState, p: PointerChange) -> Vec<EventAction> {
+    if !focus_follows_mouse_for(state, p.id) {
+        return vec![EventAction::SetScreenFromPoint(Some(p.abs))];
+    }
+
     let mut actions = vec![
         EventAction::ClientFocusGained(p.id),
         EventAction::SetScreenFromPoint(Some(p.abs)),
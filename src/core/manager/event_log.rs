@@ -0,0 +1,103 @@
+//! A bounded, in-memory history of the events processed by a [WindowManager][1].
+//!
+//! [1]: crate::core::manager::WindowManager
+use crate::core::xconnection::XEvent;
+use std::{collections::VecDeque, time::SystemTime};
+
+/// A single [XEvent] processed by the main event loop along with the actions it generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventLogEntry {
+    /// When the event was received
+    pub at: SystemTime,
+    /// The event itself
+    pub event: XEvent,
+    /// The actions that were generated in response to the event
+    pub actions: Vec<String>,
+}
+
+/// The most recent events processed by a [WindowManager][1], oldest first.
+///
+/// The number of entries held is controlled by the `event_log_size` [Config][2] field: once the
+/// log is full, recording a new entry drops the oldest one. The default `event_log_size` of 0
+/// disables recording entirely. The log is not persisted when serializing state for a restart.
+///
+/// [1]: crate::core::manager::WindowManager
+/// [2]: crate::core::config::Config
+#[derive(Debug, Default, Clone)]
+pub struct EventLog {
+    entries: VecDeque<EventLogEntry>,
+}
+
+impl EventLog {
+    // Record a new entry, dropping the oldest entries so that there are at most 'capacity'
+    pub(crate) fn record(&mut self, entry: EventLogEntry, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// The number of entries currently held in the log
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether or not there are currently any entries in the log
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the entries in the log, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &EventLogEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u32) -> EventLogEntry {
+        EventLogEntry {
+            at: SystemTime::now(),
+            event: XEvent::Destroy(id),
+            actions: vec![],
+        }
+    }
+
+    fn events(log: &EventLog) -> Vec<XEvent> {
+        log.iter().map(|e| e.event.clone()).collect()
+    }
+
+    #[test]
+    fn holds_the_most_recent_entries() {
+        let mut log = EventLog::default();
+        (1..=5).for_each(|id| log.record(entry(id), 3));
+
+        assert_eq!(log.len(), 3);
+        assert_eq!(
+            events(&log),
+            vec![XEvent::Destroy(3), XEvent::Destroy(4), XEvent::Destroy(5)]
+        );
+    }
+
+    #[test]
+    fn shrinking_the_capacity_drops_the_oldest_entries() {
+        let mut log = EventLog::default();
+        (1..=5).for_each(|id| log.record(entry(id), 5));
+        log.record(entry(6), 2);
+
+        assert_eq!(events(&log), vec![XEvent::Destroy(5), XEvent::Destroy(6)]);
+    }
+
+    #[test]
+    fn zero_capacity_records_nothing() {
+        let mut log = EventLog::default();
+        log.record(entry(1), 0);
+
+        assert!(log.is_empty());
+    }
+}
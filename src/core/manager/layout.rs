@@ -2,12 +2,18 @@ use crate::{
     core::{
         config::Config,
         hooks::HookName,
-        manager::{event::EventAction, state::WmState, util::pad_region},
-        xconnection::{XClientConfig, XClientHandler},
+        manager::{
+            event::EventAction,
+            state::WmState,
+            util::{pad_region, skip_if_transient},
+        },
+        xconnection::{XClientConfig, XClientHandler, Xid},
     },
     Result,
 };
 
+use std::time::Instant;
+
 #[tracing::instrument(level = "trace", err, skip(conn))]
 pub(super) fn layout_visible<X>(state: &mut WmState, conn: &X) -> Result<Vec<EventAction>>
 where
@@ -38,33 +44,91 @@ where
     let Config {
         show_bar,
         border_px,
-        gap_px,
+        smart_gaps,
+        respect_size_increments,
+        group_by_class,
         ..
     } = state.config;
 
+    let spanning = state.screens.spanning_workspace();
+    let region = match spanning {
+        // Hidden behind the workspace that is spanning all screens
+        Some(spanning_wix) if spanning_wix != wix => {
+            for id in state.workspaces[wix].client_ids() {
+                state.clients.unmap_if_needed(id, conn)?;
+            }
+            return Ok(None);
+        }
+        Some(_) => state
+            .screens
+            .combined_region(show_bar)
+            .unwrap_or(s.region(show_bar)),
+        None => s.region(show_bar),
+    };
+
+    let start = Instant::now();
     let (lc, aa) = state.workspaces.get_arrange_actions(
         wix,
-        s.region(show_bar),
+        region,
         &state
             .clients
             .clients_for_ids(&state.workspaces[wix].client_ids()),
+        group_by_class,
     )?;
+    state.metrics.layouts_applied += 1;
+    state.metrics.total_arrange_time += start.elapsed();
+
+    let border_px = lc.border_width.unwrap_or(border_px);
+    let gaps = state.config.effective_gaps();
+    let n_visible = aa.actions.iter().filter(|(_, r)| r.is_some()).count();
+    let gapless = lc.gapless || (smart_gaps && n_visible == 1);
 
     for (id, region) in aa.actions {
         trace!(id, ?region, "positioning client");
         if let Some(region) = region {
-            let reg = pad_region(&region, lc.gapless, gap_px, border_px);
-            conn.position_client(id, reg, border_px, false)?;
-            state.clients.map_if_needed(id, conn)?;
+            let mut reg = pad_region(&region, gapless, gaps, border_px);
+            if respect_size_increments {
+                if let Some(hints) = state
+                    .clients
+                    .get(id)
+                    .and_then(|c| c.wm_normal_hints.as_ref())
+                {
+                    reg = hints.apply_resize_increments(reg);
+                }
+            }
+            let res = conn.position_client(id, reg, border_px, false);
+            skip_if_transient(id, res.map_err(Into::into))?;
+            state.clients.modify(id, |c| c.last_region = Some(reg));
+            skip_if_transient(id, state.clients.map_if_needed(id, conn))?;
         } else {
-            state.clients.unmap_if_needed(id, conn)?;
+            skip_if_transient(id, state.clients.unmap_if_needed(id, conn))?;
         }
     }
 
-    for id in aa.floating {
+    let (above, below): (Vec<Xid>, Vec<Xid>) = {
+        let clients = state
+            .clients
+            .clients_for_ids(&state.workspaces[wix].client_ids());
+        (
+            clients.iter().filter(|c| c.above).map(|c| c.id).collect(),
+            clients.iter().filter(|c| c.below).map(|c| c.id).collect(),
+        )
+    };
+
+    for &id in below.iter() {
+        debug!(id, "stacking client below peers");
+        conn.lower_client(id)?;
+    }
+
+    for id in aa.floating.into_iter().filter(|id| !below.contains(id)) {
         debug!(id, "mapping floating client above tiled");
         conn.raise_client(id)?;
     }
 
+    for id in above {
+        debug!(id, "stacking client above peers");
+        conn.raise_client(id)?;
+    }
+
     Ok(Some(EventAction::RunHook(HookName::LayoutApplied(wix, i))))
 }
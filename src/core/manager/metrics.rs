@@ -0,0 +1,51 @@
+//! Lightweight counters for the work being done by a [WindowManager][1].
+//!
+//! [1]: crate::core::manager::WindowManager
+use std::time::Duration;
+
+/// A snapshot of the counters tracked by a [WindowManager][1] since it was created.
+///
+/// Counters are plain integers that are incremented in place as the `WindowManager` runs so
+/// tracking them adds no noticeable overhead. They are not persisted when serializing state for
+/// a restart.
+///
+/// [1]: crate::core::manager::WindowManager
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// The number of [XEvents][1] that have been processed by the main event loop
+    ///
+    /// [1]: crate::core::xconnection::XEvent
+    pub events_processed: u64,
+    /// The number of times that a layout has been applied to a workspace
+    pub layouts_applied: u64,
+    /// The total time spent running layout functions
+    pub total_arrange_time: Duration,
+    /// The number of individual hook methods that have been called
+    pub hook_invocations: u64,
+}
+
+impl Metrics {
+    /// The mean time spent running a layout function, if any layouts have been applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use penrose::core::manager::Metrics;
+    /// use std::time::Duration;
+    ///
+    /// let mut metrics = Metrics::default();
+    /// assert_eq!(metrics.average_arrange_time(), None);
+    ///
+    /// metrics.layouts_applied = 4;
+    /// metrics.total_arrange_time = Duration::from_millis(10);
+    /// assert_eq!(metrics.average_arrange_time(), Some(Duration::from_micros(2500)));
+    /// ```
+    pub fn average_arrange_time(&self) -> Option<Duration> {
+        if self.layouts_applied == 0 {
+            return None;
+        }
+
+        let nanos = self.total_arrange_time.as_nanos() / self.layouts_applied as u128;
+        Some(Duration::from_nanos(nanos as u64))
+    }
+}
@@ -1,40 +1,52 @@
 //! The main user API and control logic for Penrose.
 use crate::{
     core::{
-        bindings::{KeyBindings, KeyCode, MouseBindings, MouseEvent},
-        client::Client,
+        bindings::{
+            validate_bindings, KeyBindings, KeyCode, KeyCodeMask, MouseBindings, MouseEvent,
+        },
+        client::{stacking_from_wm_state, Client},
         config::Config,
-        data_types::{Change, Point, Region},
+        data_types::{Change, ExistingClientPlacement, Point, Region, RelativePosition},
         hooks::{HookName, Hooks},
         ring::{Direction, InsertPoint, Selector},
         screen::Screen,
         workspace::Workspace,
-        xconnection::{Atom, ClientMessageKind, WindowState, XConn, Xid},
+        xconnection::{Atom, ClientMessageKind, Prop, WindowState, XClientProperties, XConn, Xid},
     },
     ErrorHandler, PenroseError, Result,
 };
 use nix::sys::signal::{signal, SigHandler, Signal};
-use std::{cell::Cell, fmt};
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    fmt,
+    time::{Duration, Instant, SystemTime},
+};
 use tracing::Level;
 
 #[cfg(feature = "serde")]
 use crate::core::{helpers::logging_error_handler, layout::LayoutFunc};
 
-#[cfg(feature = "serde")]
-use std::collections::HashMap;
-
 mod clients;
+mod description;
 mod event;
+mod event_log;
 mod layout;
+mod metrics;
 mod screens;
 mod state;
 mod util;
 mod workspaces;
 
 use clients::Clients;
+pub use description::{
+    ClientDescription, ScreenDescription, StateDescription, WorkspaceDescription,
+};
 use event::process_next_event;
 use event::EventAction;
+pub use event_log::{EventLog, EventLogEntry};
 use layout::{apply_layout, layout_visible};
+pub use metrics::Metrics;
 use screens::Screens;
 use state::WmState;
 use workspaces::Workspaces;
@@ -68,12 +80,25 @@ pub struct WindowManager<X: XConn> {
     pub(super) state: WmState,
     #[cfg_attr(feature = "serde", serde(skip, default = "default_hooks"))]
     pub(super) hooks: Cell<Hooks<X>>,
-    pub(super) previous_workspace: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(super) previous_workspaces: HashMap<usize, usize>,
     pub(super) running: bool,
     #[cfg_attr(feature = "serde", serde(skip, default = "logging_error_handler"))]
     pub(super) error_handler: ErrorHandler,
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(super) hydrated: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) last_user_input: Option<Instant>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) last_property_changes: HashMap<(Xid, String), Instant>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) pending_property_changes: HashSet<(Xid, String)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) hooks_suspended: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) suspended_hooks: HashMap<HookName, usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) carried_client: Option<Xid>,
 }
 
 impl<X: XConn> fmt::Debug for WindowManager<X> {
@@ -82,7 +107,7 @@ impl<X: XConn> fmt::Debug for WindowManager<X> {
             .field("conn", &stringify!(self.conn))
             .field("state", &self.state)
             .field("hooks", &stringify!(self.hooks))
-            .field("previous_workspace", &self.previous_workspace)
+            .field("previous_workspaces", &self.previous_workspaces)
             .field("running", &self.running)
             .finish()
     }
@@ -104,7 +129,11 @@ impl<X: XConn> WindowManager<X> {
             config.main_ratio_step,
         );
 
-        let screens = Screens::new(config.bar_height, config.top_bar);
+        let screens = Screens::new(
+            config.bar_height,
+            config.top_bar,
+            config.screen_bars.clone(),
+        );
         let clients = Clients::new(config.focused_border, config.unfocused_border);
 
         let state = WmState {
@@ -112,16 +141,24 @@ impl<X: XConn> WindowManager<X> {
             clients,
             screens,
             workspaces,
+            metrics: Metrics::default(),
+            event_log: EventLog::default(),
         };
 
         Self {
             conn,
             state,
-            previous_workspace: 0,
+            previous_workspaces: HashMap::new(),
             hooks: Cell::new(hooks),
             running: false,
             hydrated: true,
             error_handler,
+            last_user_input: None,
+            last_property_changes: HashMap::new(),
+            pending_property_changes: HashSet::new(),
+            hooks_suspended: 0,
+            suspended_hooks: HashMap::new(),
+            carried_client: None,
         }
     }
 
@@ -185,6 +222,15 @@ impl<X: XConn> WindowManager<X> {
         self.error_handler = error_handler;
         self.workspaces.restore_layout_functions(&layout_funcs)?;
         util::validate_hydrated_wm_state(self)?;
+        if let Some(seq) = self
+            .clients
+            .all_known_ids()
+            .into_iter()
+            .filter_map(|id| self.clients.get(id).map(|c| c.sequence()))
+            .max()
+        {
+            crate::core::client::ensure_sequence_after(seq);
+        }
         self.hydrated = true;
         self.init()?;
         Ok(())
@@ -209,16 +255,41 @@ impl<X: XConn> WindowManager<X> {
         trace!("Setting EWMH properties");
         self.conn.set_wm_properties(&self.config.workspaces)?;
 
-        trace!("Forcing cursor to first screen");
-        Ok(self.conn.warp_cursor(None, &self.screens.inner[0])?)
+        if self.screens.n_screens() == 0 {
+            warn!("no screens detected: waiting for a RandR event before placing the cursor");
+            return Ok(());
+        }
+
+        // Start out on the primary output (if there is one) so that new clients are placed there
+        let ix = self.screens.primary_index().unwrap_or(0);
+        trace!(ix, "Forcing cursor to initial screen");
+        self.focus_screen_n(ix)
     }
 
     #[tracing::instrument(level = "debug", err, skip(self))]
     pub(crate) fn try_manage_existing_windows(&mut self) -> Result<()> {
         let classes = str_slice!(self.config.floating_classes);
-        for mut c in self.conn.active_managed_clients(classes)?.into_iter() {
+        let unmanaged_types = str_slice!(self.config.unmanaged_window_types);
+        let n = self.workspaces.len();
+        let mut next_wix = self.screens.active_ws_index();
+
+        let existing = self.conn.active_managed_clients(classes, unmanaged_types)?;
+        for (mut c, requested) in existing.into_iter() {
             let id = c.id();
-            self.add_client_to_workspace(c.workspace(), id)?;
+            let wix = match requested {
+                Some(wix) if wix < n => wix,
+                _ => match self.config.existing_client_placement {
+                    ExistingClientPlacement::ActiveWorkspace => self.screens.active_ws_index(),
+                    ExistingClientPlacement::RoundRobin => {
+                        let wix = next_wix;
+                        next_wix = (next_wix + 1) % n;
+                        wix
+                    }
+                },
+            };
+            c.set_workspace(wix);
+            c.origin_workspace = Some(wix);
+            self.add_client_to_workspace(wix, id, false)?;
             self.conn.unmap_client_if_needed(Some(&mut c))?;
             self.clients.insert(id, c);
             self.conn.mark_new_client(id)?;
@@ -237,13 +308,30 @@ impl<X: XConn> WindowManager<X> {
     fn run_hook(&mut self, hook_name: HookName) {
         use HookName::*;
 
+        if self.hooks_suspended > 0 {
+            trace!(target: "hooks", ?hook_name, "hooks are suspended");
+            // Per-event triggers are dropped rather than replayed: they are distinct for every
+            // event so coalescing them would just queue up the whole event stream.
+            if !matches!(hook_name, RawEvent(_) | EventHandled) {
+                let n = self.suspended_hooks.len();
+                self.suspended_hooks.entry(hook_name).or_insert(n);
+            }
+            return;
+        }
+
         // Relies on all hooks taking &mut WindowManager as the first arg.
         macro_rules! run_hooks {
             ($method:ident, $_self:expr, $($arg:expr),*) => {
                 {
                     debug!(target: "hooks", "Running {} hooks", stringify!($method));
                     let mut hooks = $_self.hooks.replace(vec![]);
-                    let res = hooks.iter_mut().try_for_each(|h| h.$method($_self, $($arg),*));
+                    let res = hooks.iter_mut().enumerate().try_for_each(|(i, h)| {
+                        $_self.metrics.hook_invocations += 1;
+                        let start = Instant::now();
+                        let res = h.$method($_self, $($arg),*);
+                        $_self.warn_if_slow_hook(stringify!($method), i, start.elapsed());
+                        res
+                    });
                     $_self.hooks.replace(hooks);
                     if let Err(e) = res {
                         ($_self.error_handler)(e);
@@ -260,11 +348,16 @@ impl<X: XConn> WindowManager<X> {
             ClientNameUpdated(id, name, is_root) => {
                 run_hooks!(client_name_updated, self, id, &name, is_root);
             }
+            ClientPropertyChanged(id, atom) => run_hooks!(client_property_changed, self, id, &atom),
             LayoutApplied(wix, i) => run_hooks!(layout_applied, self, wix, i),
             LayoutChange(wix) => {
                 let i = self.active_screen_index();
                 run_hooks!(layout_change, self, wix, i);
             }
+            LayoutParamsChanged(wix) => {
+                let i = self.active_screen_index();
+                run_hooks!(layout_params_changed, self, wix, i);
+            }
             WorkspaceChange(active, index) => run_hooks!(workspace_change, self, active, index),
             WorkspacesUpdated(names, wix) => {
                 run_hooks!(workspaces_updated, self, str_slice!(names), wix)
@@ -280,9 +373,30 @@ impl<X: XConn> WindowManager<X> {
             RanderNotify => run_hooks!(randr_notify, self,),
             FocusChange(root) => run_hooks!(focus_change, self, root),
             EventHandled => run_hooks!(event_handled, self,),
+            RawEvent(event) => run_hooks!(raw_event, self, &event),
+        }
+    }
+
+    // Hooks block the event loop while they run so we flag any that take long enough to be
+    // noticeable in order to help users track down the cause of apparent freezes.
+    fn warn_if_slow_hook(&self, method: &str, index: usize, elapsed: Duration) {
+        if self.hook_is_slow(elapsed) {
+            warn!(
+                target: "hooks",
+                method,
+                index,
+                ?elapsed,
+                threshold_ms = self.config.hook_warning_threshold_ms,
+                "hook exceeded the configured warning threshold"
+            );
         }
     }
 
+    fn hook_is_slow(&self, elapsed: Duration) -> bool {
+        let threshold = self.config.hook_warning_threshold_ms;
+        threshold > 0 && elapsed > Duration::from_millis(threshold)
+    }
+
     fn handle_event_actions(&mut self, actions: Vec<EventAction>) -> Result<()> {
         for a in actions {
             self.handle_event_action(a, None, None)?;
@@ -306,13 +420,14 @@ impl<X: XConn> WindowManager<X> {
             ClientFocusGained(id) => self.update_focus(id)?,
             ClientFocusLost(id) => self.state.clients.client_lost_focus(id, &self.conn),
             ClientNameChanged(id, is_root) => {
+                self.state.clients.invalidate(id);
                 let action = self
                     .state
                     .clients
                     .client_name_changed(id, is_root, &self.conn)?;
                 self.handle_event_action(action, None, None)?
             }
-            ClientToWorkspace(id, wix) => self.move_client_to_workspace(id, wix)?,
+            ClientToWorkspace(id, wix) => self.move_client_to_workspace(id, wix, false)?,
             DestroyClient(id) => self.remove_client(id)?,
             DetectScreens => {
                 self.run_hook(HookName::RanderNotify);
@@ -322,7 +437,7 @@ impl<X: XConn> WindowManager<X> {
             LayoutVisible => self.layout_visible()?,
             LayoutWorkspace(wix) => self.apply_layout(wix)?,
             MapWindow(id) => self.handle_map_request(id)?,
-            MoveClientIfFloating(id, r) => self.handle_move_if_floating(id, r)?,
+            MoveClientIfFloating(id, r) => self.handle_configure_request(id, r)?,
             RunHook(hook_name) => self.run_hook(hook_name),
             RunKeyBinding(e) => match key_bindings {
                 Some(kb) => self.run_key_binding(e, kb),
@@ -334,11 +449,16 @@ impl<X: XConn> WindowManager<X> {
             },
             SetActiveClient(id) => self.set_active_client(id)?,
             SetActiveWorkspace(wix) => self.focus_workspace(&Selector::Index(wix))?,
+            SetClientStacking(id, above, below) => {
+                self.conn.set_client_stacking_state(id, above, below)?;
+                self.set_client_stacking(id, above, below)?;
+            }
             SetScreenFromPoint(p) => self.set_screen_from_point(p)?,
             ToggleClientFullScreen(id, should_fullscreen) => {
                 self.set_fullscreen(id, should_fullscreen)?;
             }
             UnknownPropertyChange(id, atom, is_root) => {
+                self.state.clients.invalidate(id);
                 self.handle_prop_change(id, atom, is_root)?;
             }
             Unmap(id) => self.handle_unmap_notify(id)?,
@@ -357,9 +477,18 @@ impl<X: XConn> WindowManager<X> {
     /// the call to `grab_keys_and_run` and all internal state will still be accessible, though
     /// methods requiring the use of the [XConn] will fail.
     ///
+    /// # Errors
+    /// If the connection to the X server is lost then the loop stops and the error is returned
+    /// rather than being passed to the [ErrorHandler][4], as no further events can be received.
+    /// Errors returned by key and mouse bindings do not stop the loop: they are passed to the
+    /// [ErrorHandler][4]. This includes [PenroseError::EmptyWorkspace] from methods such as
+    /// [focus_master][5] when they are run against a [Workspace] with no clients.
+    ///
     /// [1]: crate::core::xconnection::XEventHandler::wait_for_event
     /// [2]: WindowManager::exit
     /// [3]: crate::core::xconnection::XConn::cleanup
+    /// [4]: crate::ErrorHandler
+    /// [5]: WindowManager::focus_master
     pub fn grab_keys_and_run(
         &mut self,
         mut key_bindings: KeyBindings<X>,
@@ -378,11 +507,20 @@ impl<X: XConn> WindowManager<X> {
             panic!("unable to set signal handler: {}", e);
         }
 
+        let locks = &self.config.ignored_lock_modifiers;
+        if let Err(e) = validate_bindings(&key_bindings, &mouse_bindings, locks) {
+            warn!("{}", e);
+        }
+
         trace!("grabbing key and mouse bindings");
-        self.conn.grab_keys(&key_bindings, &mouse_bindings)?;
+        self.conn.grab_keys(&key_bindings, &mouse_bindings, locks)?;
 
-        trace!("forcing focus to first workspace");
-        self.focus_workspace(&Selector::Index(0))?;
+        if self.screens.n_screens() == 0 {
+            warn!("no screens detected: skipping initial workspace focus");
+        } else {
+            trace!("focusing startup workspaces");
+            self.focus_startup_workspaces()?;
+        }
 
         self.run_hook(HookName::Startup);
         self.running = true;
@@ -395,25 +533,80 @@ impl<X: XConn> WindowManager<X> {
                     let _enter = span.enter();
                     trace!(details = ?event, "event details");
 
+                    self.run_hook(HookName::RawEvent(event.clone()));
+                    let capacity = self.config.event_log_size;
+                    let logged = if capacity > 0 {
+                        Some(event.clone())
+                    } else {
+                        None
+                    };
                     let actions = process_next_event(event, &self.state, &self.conn);
+                    if let Some(event) = logged {
+                        let entry = EventLogEntry {
+                            at: SystemTime::now(),
+                            event,
+                            actions: actions.iter().map(|a| format!("{:?}", a)).collect(),
+                        };
+                        self.event_log.record(entry, capacity);
+                    }
                     for action in actions {
                         if let Err(e) = self.handle_event_action(
                             action,
                             Some(&mut key_bindings),
                             Some(&mut mouse_bindings),
                         ) {
-                            (self.error_handler)(e);
+                            self.handle_error(e);
                         }
                     }
 
+                    self.flush_pending_property_changes();
                     self.run_hook(HookName::EventHandled);
                     self.conn.flush();
+                    self.metrics.events_processed += 1;
                 }
 
-                Err(e) => (self.error_handler)(PenroseError::X(e)),
+                Err(e) if e.is_connection_closed() => {
+                    error!(%e, "lost connection to the X server: exiting");
+                    self.running = false;
+                    return Err(PenroseError::X(e));
+                }
+
+                Err(e) => self.handle_error(PenroseError::X(e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Transient errors for windows that have already been destroyed are expected during normal
+    // operation so they are logged rather than being passed to the error handler.
+    fn handle_error(&mut self, e: PenroseError) {
+        if e.is_transient() {
+            debug!(%e, "ignoring transient X error");
+        } else {
+            (self.error_handler)(e);
+        }
+    }
+
+    // Show the configured startup workspace on each screen, leaving focus on the first screen.
+    fn focus_startup_workspaces(&mut self) -> Result<()> {
+        let startup = self.config.startup_workspaces.clone();
+        if startup.is_empty() {
+            return self.focus_workspace(&Selector::Index(0));
+        }
+
+        let n_screens = self.screens.n_screens();
+        let n_workspaces = self.workspaces.len();
+        for (screen_index, wix) in startup.into_iter().enumerate().take(n_screens) {
+            if wix >= n_workspaces {
+                warn!(screen_index, wix, "unknown startup workspace: skipping");
+                continue;
             }
+            self.focus_screen(&Selector::Index(screen_index));
+            self.focus_workspace(&Selector::Index(wix))?;
         }
 
+        self.focus_screen(&Selector::Index(0));
         Ok(())
     }
 
@@ -451,13 +644,19 @@ impl<X: XConn> WindowManager<X> {
             }
         };
 
+        if matches!(self.clients.get(target), Some(c) if c.urgent) {
+            self.clients.modify(target, |c| c.urgent = false);
+            self.conn.set_client_attention(target, false)?;
+        }
         let prev = self.state.clients.set_focused(target, &self.conn);
 
-        let (wix, accepts_focus) = {
+        let (wix, accepts_focus, mapped) = {
             // Safe to unwrap because we make sure this is a known client above
             let c = self.clients.get(target).unwrap();
-            (c.workspace(), c.accepts_focus)
+            (c.workspace(), c.accepts_focus(), c.mapped)
         };
+        // Focusing a client hidden behind another member of its group needs it to be shown
+        let regroup = self.config.group_by_class && !mapped;
 
         self.focus_screen(&Selector::Condition(&|s| s.wix == wix));
         self.clients
@@ -466,23 +665,47 @@ impl<X: XConn> WindowManager<X> {
         if let Some(ws) = self.workspaces.get_mut(wix) {
             ws.focus_client(target);
             let in_ws = prev.map_or(false, |prev_id| ws.client_ids().contains(&prev_id));
-            if ws.layout_conf().follow_focus && in_ws {
+            if (ws.layout_conf().follow_focus && in_ws) || regroup {
                 if let Err(e) = self.apply_layout(wix) {
                     error!("unable to apply layout on ws {}: {}", wix, e);
                 }
             }
         }
 
+        if self.config.mouse_follows_focus {
+            self.warp_pointer_to_focus(target)?;
+        }
+
         self.run_hook(HookName::FocusChange(target));
         Ok(())
     }
 
+    // Move the pointer to the newly focused client unless it is already over it. This is always
+    // the case when focus was driven by the pointer itself, so skipping the warp prevents pointer
+    // and focus events from chasing one another when focus_follows_mouse is also enabled.
+    fn warp_pointer_to_focus(&mut self, id: Xid) -> Result<()> {
+        let (_, under_pointer) = self.conn.query_pointer()?;
+        if under_pointer == Some(id) {
+            return Ok(());
+        }
+
+        Ok(self.conn.warp_cursor(Some(id), self.screens.focused())?)
+    }
+
     // The given window ID has been destroyed so remove our internal state referencing it.
     #[tracing::instrument(level = "trace", err, skip(self))]
     fn remove_client(&mut self, id: Xid) -> Result<()> {
         if let Some(client) = self.clients.remove(id) {
+            self.last_property_changes.retain(|(i, _), _| *i != id);
+            self.pending_property_changes.retain(|(i, _)| *i != id);
+            if self.carried_client == Some(id) {
+                self.carried_client = None;
+            }
             let wix = client.workspace();
             self.workspaces.remove_client(wix, id);
+            if client.fullscreen {
+                self.restore_pre_fullscreen_layout(wix);
+            }
 
             if self.screens.visible_workspaces().contains(&wix) {
                 self.apply_layout(wix)?;
@@ -498,7 +721,7 @@ impl<X: XConn> WindowManager<X> {
     }
 
     #[tracing::instrument(level = "trace", err, skip(self))]
-    fn move_client_to_workspace(&mut self, id: Xid, wix: usize) -> Result<()> {
+    fn move_client_to_workspace(&mut self, id: Xid, wix: usize, focus: bool) -> Result<()> {
         let current_wix = match self.clients.workspace_index_for_client(id) {
             Some(ix) => ix,
             None => return Err(PenroseError::UnknownClient(id)),
@@ -506,7 +729,7 @@ impl<X: XConn> WindowManager<X> {
 
         if current_wix != wix {
             self.workspaces.remove_client(current_wix, id);
-            self.add_client_to_workspace(wix, id)?;
+            self.add_client_to_workspace(wix, id, focus)?;
             self.clients.set_client_workspace(id, wix);
 
             if self.screens.visible_workspaces().contains(&wix) {
@@ -539,8 +762,20 @@ impl<X: XConn> WindowManager<X> {
     fn handle_map_request(&mut self, id: Xid) -> Result<()> {
         trace!(id, "handling map request");
         let classes = str_slice!(self.config.floating_classes);
-        let client = Client::new(&self.conn, id, self.screens.active_ws_index(), classes);
-        let is_managed_type = self.conn.is_managed_client(&client);
+        let props = self.state.clients.cached_props(&self.conn);
+        let mut client = Client::new(&props, id, self.screens.active_ws_index(), classes);
+        if let Ok(Prop::Cardinal(wix)) = props.get_prop(id, Atom::NetWmDesktop.as_ref()) {
+            // Clients may request a specific workspace: anything out of range (including the
+            // 0xFFFFFFFF "all desktops" value) is placed on the active workspace
+            if (wix as usize) < self.workspaces.len() {
+                client.set_workspace(wix as usize);
+            }
+        }
+        let unmanaged_types = str_slice!(self.config.unmanaged_window_types);
+        let is_managed_type = self.conn.is_managed_client(&client, unmanaged_types);
+        if !is_managed_type {
+            client.externally_managed();
+        }
         trace!(id, ?client.wm_name, ?client.wm_class, ?client.wm_type, "client details");
 
         // Run hooks to allow them to modify the client
@@ -570,23 +805,54 @@ impl<X: XConn> WindowManager<X> {
             return Ok(self.conn.map_client(id)?);
         }
 
+        let take_focus = self.should_focus_new_client();
         if wm_managed {
-            self.add_client_to_workspace(wix, id)?;
+            self.clients.modify(id, |c| c.origin_workspace = Some(wix));
+            self.add_client_to_workspace(wix, id, take_focus)?;
         }
 
         if floating {
             if let Some((_, s)) = self.screens.indexed_screen_for_workspace(wix) {
-                util::position_floating_client(
-                    &self.conn,
-                    id,
-                    s.region(self.config.show_bar),
-                    self.config.border_px,
-                )?
+                let screen_region = s.region(self.config.show_bar);
+                let has_saved_region = matches!(
+                    self.clients.get(id).map(|c| c.floating_region()),
+                    Some(Some(_))
+                );
+                let default = self
+                    .workspaces
+                    .get_workspace(wix)?
+                    .default_floating_geometry();
+
+                match default {
+                    Some(geometry) if !has_saved_region => {
+                        let r = geometry.region_in(&screen_region);
+                        self.clients.modify(id, |c| c.floating_region = Some(r));
+                        self.conn
+                            .position_client(id, r, self.border_px(wix), false)?;
+                    }
+                    _ => util::position_floating_client(
+                        &self.conn,
+                        id,
+                        screen_region,
+                        self.border_px(wix),
+                    )?,
+                }
             }
         }
 
         self.conn.mark_new_client(id)?;
-        self.update_focus(id)?;
+        if take_focus {
+            self.update_focus(id)?;
+        } else {
+            debug!(id, "not focusing new client while the user is typing");
+            self.clients.modify(id, |c| c.urgent = true);
+            self.conn.set_client_attention(id, true)?;
+            if let Some(focused) = self.clients.focused_client_id() {
+                if let Some(ws) = self.workspaces.get_mut(wix) {
+                    ws.focus_client(focused);
+                }
+            }
+        }
         self.update_known_x_clients()?;
 
         if wix == self.screens.active_ws_index() {
@@ -599,22 +865,117 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
-    fn handle_move_if_floating(&mut self, id: Xid, r: Region) -> Result<()> {
-        if let Some(client) = self.clients.get(id) {
-            if client.floating {
-                debug!(id, region = ?r, "repositioning floating window");
-                let bpx = self.config.border_px;
-                self.conn.position_client(id, r, bpx, true)?;
-            }
+    // New clients are focused unless the focused client has seen user input within the
+    // configured focus_steal_grace_ms, in which case we assume that the user is still typing.
+    // The border width for clients on the given workspace, taking into account any override set
+    // by the active layout.
+    fn border_px(&self, wix: usize) -> u32 {
+        self.workspaces
+            .get(wix)
+            .and_then(|ws| ws.layout_conf().border_width)
+            .unwrap_or(self.config.border_px)
+    }
+
+    fn should_focus_new_client(&self) -> bool {
+        let grace = Duration::from_millis(self.config.focus_steal_grace_ms);
+        !matches!(self.last_user_input, Some(t) if t.elapsed() < grace)
+    }
+
+    // Clients requesting a new size or position are only allowed to move themselves if they are
+    // floating. Tiled clients are put back where the current layout placed them and fullscreen
+    // clients are left alone.
+    fn handle_configure_request(&mut self, id: Xid, r: Region) -> Result<()> {
+        let (floating, fullscreen, last_region, wix) = match self.clients.get(id) {
+            Some(c) => (c.floating, c.fullscreen, c.last_region(), c.workspace()),
+            None => return Ok(()),
+        };
+
+        if fullscreen {
+            trace!(id, "ignoring configure request for fullscreen client");
+        } else if floating {
+            debug!(id, region = ?r, "repositioning floating window");
+            self.clients.modify(id, |c| c.floating_region = Some(r));
+            self.position_client(id, r, true)?;
+        } else if let Some(tiled) = last_region {
+            debug!(id, requested = ?r, ?tiled, "restoring tiled position");
+            self.conn
+                .position_client(id, tiled, self.border_px(wix), false)?;
         }
+
         Ok(())
     }
 
+    // Property changes for managed clients are passed on to hooks, dropping repeated changes to
+    // the same property within the configured throttle period.
     fn handle_prop_change(&mut self, id: Xid, atom: String, is_root: bool) -> Result<()> {
-        trace!(id, is_root, ?atom, "dropping prop change (unimplemented)");
+        if is_root || !self.clients.is_known(id) {
+            trace!(
+                id,
+                is_root,
+                ?atom,
+                "dropping prop change for unmanaged window"
+            );
+            return Ok(());
+        }
+
+        if atom == Atom::NetWmState.as_ref() {
+            let wm_state = match self.client_prop(id, &atom) {
+                Ok(Prop::Atom(atoms)) => atoms,
+                _ => vec![],
+            };
+            let (above, below) = stacking_from_wm_state(&wm_state);
+            self.set_client_stacking(id, above, below)?;
+        }
+
+        // Toolkits update _NET_WM_USER_TIME on each user interaction with a client, which is
+        // the only view we have of input that isn't bound to one of our own key bindings.
+        if atom == Atom::NetWmUserTime.as_ref() && self.focused_client_id() == Some(id) {
+            self.last_user_input = Some(Instant::now());
+        }
+
+        let throttle = Duration::from_millis(self.config.property_change_throttle_ms);
+        let now = Instant::now();
+        let key = (id, atom);
+        if let Some(last) = self.last_property_changes.get(&key) {
+            if now.duration_since(*last) < throttle {
+                trace!(id, atom = ?key.1, "throttling prop change");
+                self.pending_property_changes.insert(key);
+                return Ok(());
+            }
+        }
+
+        self.pending_property_changes.remove(&key);
+        self.last_property_changes.insert(key.clone(), now);
+        self.run_hook(HookName::ClientPropertyChanged(key.0, key.1));
         Ok(())
     }
 
+    // Throttled property changes are delivered once their throttle window has passed so that
+    // hooks always see the final change in a burst. There is no timer driving this so it is
+    // checked after each event is processed.
+    fn flush_pending_property_changes(&mut self) {
+        if self.pending_property_changes.is_empty() {
+            return;
+        }
+
+        let throttle = Duration::from_millis(self.config.property_change_throttle_ms);
+        let now = Instant::now();
+        let last_changes = &self.last_property_changes;
+        let mut ready: Vec<(Xid, String)> = self
+            .pending_property_changes
+            .iter()
+            .filter(|&key| matches!(last_changes.get(key), Some(t) if now.duration_since(*t) >= throttle))
+            .cloned()
+            .collect();
+        ready.sort();
+
+        for key in ready {
+            self.pending_property_changes.remove(&key);
+            self.last_property_changes.insert(key.clone(), now);
+            self.run_hook(HookName::ClientPropertyChanged(key.0, key.1));
+        }
+    }
+
     fn handle_unmap_notify(&mut self, id: Xid) -> Result<()> {
         Ok(self.conn.set_client_state(id, WindowState::Withdrawn)?)
     }
@@ -624,7 +985,16 @@ impl<X: XConn> WindowManager<X> {
     //       including mutable methods.
     #[tracing::instrument(level = "debug", skip(self, k, bindings), fields(k.code, k.mask))]
     fn run_key_binding(&mut self, k: KeyCode, bindings: &mut KeyBindings<X>) {
+        let k = self
+            .config
+            .ignored_lock_modifiers
+            .iter()
+            .fold(k, |k, &m| k.ignoring_modifier(m.into()));
+
         if let Some(action) = bindings.get_mut(&k) {
+            // Running a binding is a deliberate request to the window manager, so any clients
+            // it spawns should be free to take focus.
+            self.last_user_input = None;
             // ignoring Child handlers and SIGCHILD
             if let Err(e) = action(self) {
                 (self.error_handler)(e);
@@ -637,7 +1007,21 @@ impl<X: XConn> WindowManager<X> {
     //       including mutable methods.
     #[tracing::instrument(level = "debug", skip(self, e, bindings), fields(?e.state, ?e.kind))]
     fn run_mouse_binding(&mut self, e: MouseEvent, bindings: &mut MouseBindings<X>) {
-        if let Some(action) = bindings.get_mut(&(e.kind, e.state.clone())) {
+        let ignoring = self
+            .config
+            .ignored_lock_modifiers
+            .iter()
+            .fold(0, |mask, &m| mask | KeyCodeMask::from(m));
+
+        let action = match bindings.get_mut(&(e.kind, e.state.clone())) {
+            Some(action) => Some(action),
+            None => bindings
+                .iter_mut()
+                .find(|((kind, state), _)| *kind == e.kind && e.state.matches(state, ignoring))
+                .map(|(_, action)| action),
+        };
+
+        if let Some(action) = action {
             // ignoring Child handlers and SIGCHILD
             if let Err(e) = action(self, &e) {
                 (self.error_handler)(e);
@@ -663,6 +1047,26 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    // Keep the given client above or below its peers, restacking its workspace if it is visible
+    // and the requested stacking differs from what we currently have.
+    fn set_client_stacking(&mut self, id: Xid, above: bool, below: bool) -> Result<()> {
+        let wix = match self.clients.get(id) {
+            Some(c) if (c.above, c.below) != (above, below) => c.workspace(),
+            _ => return Ok(()),
+        };
+
+        self.clients.modify(id, |c| {
+            c.above = above;
+            c.below = below;
+        });
+
+        if self.screens.visible_workspaces().contains(&wix) {
+            self.apply_layout(wix)?;
+        }
+
+        Ok(())
+    }
+
     // Toggle the given client fullscreen. This has knock on effects for other windows and can
     // be triggered by user key bindings as well as applications requesting full screen as well.
     // TODO: should something going fullscreen also hide unmaged windows?
@@ -688,9 +1092,53 @@ impl<X: XConn> WindowManager<X> {
             .clients
             .toggle_fullscreen(id, wix, &client_ids, r, &self.conn)?;
 
+        if should_fullscreen {
+            self.enter_fullscreen_layout(wix);
+        } else {
+            self.restore_pre_fullscreen_layout(wix);
+        }
+
         self.handle_event_actions(actions)
     }
 
+    // Switch to the configured fullscreen layout (if there is one) for the given workspace
+    fn enter_fullscreen_layout(&mut self, wix: usize) {
+        let symbol = match self.config.fullscreen_layout.clone() {
+            Some(symbol) => symbol,
+            None => return,
+        };
+
+        let changed = match self.workspaces.workspace_mut(&Selector::Index(wix)) {
+            Some(ws) => ws.enter_fullscreen_layout(&symbol),
+            None => false,
+        };
+
+        if changed {
+            self.run_hook(HookName::LayoutChange(wix));
+        }
+    }
+
+    // Restore the layout used before the workspace entered fullscreen once there are no
+    // remaining fullscreen clients on it
+    fn restore_pre_fullscreen_layout(&mut self, wix: usize) {
+        let ids = self.workspaces.client_ids(wix).unwrap_or_default();
+        if ids
+            .iter()
+            .any(|&id| matches!(self.clients.get(id), Some(c) if c.fullscreen))
+        {
+            return;
+        }
+
+        let changed = match self.workspaces.workspace_mut(&Selector::Index(wix)) {
+            Some(ws) => ws.restore_pre_fullscreen_layout(),
+            None => false,
+        };
+
+        if changed {
+            self.run_hook(HookName::LayoutChange(wix));
+        }
+    }
+
     /*
      * Common mid level actions that make up larger event response handlers.
      */
@@ -727,6 +1175,12 @@ impl<X: XConn> WindowManager<X> {
 
     fn focus_screen(&mut self, sel: &Selector<'_, Screen>) -> &Screen {
         let prev_wix = self.screens.focused().wix;
+        if let Some(spanning) = self.screens.spanning_workspace() {
+            // every other screen is hidden behind the workspace that is spanning them
+            if matches!(self.screens.screen(sel), Some(s) if s.wix != spanning) {
+                return self.screens.focused();
+            }
+        }
 
         let actions = self.screens.focus_screen(sel);
         if let Err(e) = self.handle_event_actions(actions) {
@@ -745,9 +1199,9 @@ impl<X: XConn> WindowManager<X> {
     }
 
     #[tracing::instrument(level = "trace", err, skip(self))]
-    fn add_client_to_workspace(&mut self, wix: usize, id: Xid) -> Result<()> {
+    fn add_client_to_workspace(&mut self, wix: usize, id: Xid, focus: bool) -> Result<()> {
         self.clients.modify(id, |c| c.set_workspace(wix));
-        if let Some(action) = self.workspaces.add_client(wix, id)? {
+        if let Some(action) = self.workspaces.add_client(wix, id, focus)? {
             self.conn.set_client_workspace(id, wix)?;
             self.handle_event_action(action, None, None)?;
         }
@@ -784,6 +1238,70 @@ impl<X: XConn> WindowManager<X> {
         &mut self.conn
     }
 
+    /// Stop running hooks until a matching call to [resume_hooks][1] is made.
+    ///
+    /// This is useful when carrying out bulk operations (such as moving a large number of
+    /// clients) that would otherwise trigger a flood of hook calls. Calls may be nested: hooks
+    /// remain suspended until every call to `suspend_hooks` has been matched by a call to
+    /// `resume_hooks`.
+    ///
+    /// [1]: WindowManager::resume_hooks
+    pub fn suspend_hooks(&mut self) {
+        self.hooks_suspended += 1;
+    }
+
+    /// Undo a previous call to [suspend_hooks][1].
+    ///
+    /// Once the outermost suspension has been resumed, if `replay` is true then each distinct
+    /// hook trigger that was suppressed is run once, in the order that it was first triggered.
+    /// Otherwise the suppressed triggers are discarded. The per-event `raw_event` and
+    /// `event_handled` hooks are never replayed.
+    ///
+    /// [1]: WindowManager::suspend_hooks
+    pub fn resume_hooks(&mut self, replay: bool) {
+        self.hooks_suspended = self.hooks_suspended.saturating_sub(1);
+        if self.hooks_suspended > 0 {
+            return;
+        }
+
+        let suppressed = std::mem::take(&mut self.suspended_hooks);
+        if replay {
+            let mut ordered: Vec<_> = suppressed.into_iter().collect();
+            ordered.sort_by_key(|&(_, ix)| ix);
+            ordered.into_iter().for_each(|(h, _)| self.run_hook(h));
+        }
+    }
+
+    /// Whether or not hooks are currently suspended, see [suspend_hooks][1].
+    ///
+    /// [1]: WindowManager::suspend_hooks
+    pub fn hooks_suspended(&self) -> bool {
+        self.hooks_suspended > 0
+    }
+
+    /// A snapshot of the [Metrics] tracked by this [WindowManager] since it was created.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// The most recent events processed by this [WindowManager], oldest first.
+    ///
+    /// The number of events retained is set using the `event_log_size` [Config] field.
+    pub fn event_log(&self) -> &EventLog {
+        &self.event_log
+    }
+
+    /// Write the contents of the [EventLog] to the penrose log stream.
+    ///
+    /// This is intended to be bound to a key so that the events leading up to some unexpected
+    /// behaviour can be inspected after the fact.
+    pub fn dump_event_log(&self) {
+        info!(n_events = self.event_log.len(), "dumping recent events");
+        for entry in self.event_log.iter() {
+            info!(at = ?entry.at, event = ?entry.event, actions = ?entry.actions, "event");
+        }
+    }
+
     /// The currently focused client ID if there is one
     pub fn focused_client_id(&self) -> Option<Xid> {
         self.clients.focused_client_id()
@@ -791,6 +1309,10 @@ impl<X: XConn> WindowManager<X> {
 
     /// Cycle between known [screens][Screen]. Does not wrap from first to last
     pub fn cycle_screen(&mut self, direction: Direction) -> Result<()> {
+        if self.screens.spanning_workspace().is_some() {
+            return Ok(()); // every other screen is hidden behind the spanning workspace
+        }
+
         let old_wix = self.screens.focused().wix;
         let actions = self.state.screens.cycle_screen(direction, &self.conn)?;
 
@@ -802,6 +1324,23 @@ impl<X: XConn> WindowManager<X> {
         self.handle_event_actions(actions)
     }
 
+    /// Focus the [screen][Screen] at index `n`, warping the cursor to it and making the
+    /// [Workspace] it is displaying the active workspace.
+    ///
+    /// Like [WindowManager::focus_workspace_n], this returns an error if there is no screen at
+    /// the requested index so that it can be bound for each screen using a `map` block with `VAL`
+    /// arguments when calling [gen_keybindings][1].
+    ///
+    /// [1]: crate::gen_keybindings
+    pub fn focus_screen_n(&mut self, n: usize) -> Result<()> {
+        if n >= self.screens.n_screens() {
+            return Err(PenroseError::NoMatchingElement);
+        }
+
+        let screen = *self.focus_screen(&Selector::Index(n));
+        Ok(self.conn.warp_cursor(None, &screen)?)
+    }
+
     /// Cycle between [workspaces][1] on the current [screen][2].
     ///
     /// This method will pull workspaces to the active screen if they are currently displayed on
@@ -814,6 +1353,37 @@ impl<X: XConn> WindowManager<X> {
         self.focus_workspace(&Selector::Index(i))
     }
 
+    /// Cycle between non-empty [workspaces][1] on the current [screen][2], skipping any that do
+    /// not currently hold any clients.
+    ///
+    /// Whether or not this wraps at the first and last workspace is controlled by the
+    /// `cycle_occupied_wraps` config option. If there are no other occupied workspaces in the
+    /// given direction then this is a no-op.
+    ///
+    /// [1]: Workspace
+    /// [2]: Screen
+    pub fn cycle_occupied_workspace(&mut self, direction: Direction) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        let n = self.workspaces.len();
+        let wraps = self.config.cycle_occupied_wraps;
+
+        let next = (1..n)
+            .map(|i| match direction {
+                Direction::Forward => (wix + i) % n,
+                Direction::Backward => (wix + n - i) % n,
+            })
+            .filter(|&ix| match direction {
+                Direction::Forward => wraps || ix > wix,
+                Direction::Backward => wraps || ix < wix,
+            })
+            .find(|&ix| !self.workspaces[ix].is_empty());
+
+        match next {
+            Some(ix) => self.focus_workspace(&Selector::Index(ix)),
+            None => Ok(()),
+        }
+    }
+
     /// Move the currently focused [Workspace] to the next [Screen] in 'direction'
     pub fn drag_workspace(&mut self, direction: Direction) -> Result<()> {
         let wix = self.screens.active_ws_index();
@@ -821,11 +1391,25 @@ impl<X: XConn> WindowManager<X> {
         self.focus_workspace(&Selector::Index(wix)) // focus_workspace will pull it to the new screen
     }
 
+    // The index of the active workspace, or an error if there are no clients on it for
+    // operations that need at least one.
+    fn occupied_active_ws_index(&self) -> Result<usize> {
+        let wix = self.screens.active_ws_index();
+        if self.workspaces[wix].is_empty() {
+            return Err(PenroseError::EmptyWorkspace(wix));
+        }
+
+        Ok(wix)
+    }
+
     /// Cycle focus between [clients][1] for the active [Workspace]
     ///
+    /// # Errors
+    /// Fails with [PenroseError::EmptyWorkspace] if the active [Workspace] has no clients.
+    ///
     /// [1]: Client
     pub fn cycle_client(&mut self, direction: Direction) -> Result<()> {
-        let wix = self.screens.active_ws_index();
+        let wix = self.occupied_active_ws_index()?;
         let res = self.workspaces.cycle_client(wix, direction);
         if let Some((prev, new)) = res {
             self.state.clients.client_lost_focus(prev, &self.conn);
@@ -837,6 +1421,148 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Cycle focus between the [clients][Client] on the active [Workspace] that share a WM_CLASS
+    /// with the focused client.
+    ///
+    /// When [group_by_class][1] is enabled this swaps which member of the group is shown in the
+    /// layout. Floating clients and clients without a WM_CLASS are not part of any group.
+    ///
+    /// [1]: crate::core::config::Config::group_by_class
+    pub fn cycle_client_group(&mut self, direction: Direction) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        let (id, class) = match self.workspaces[wix]
+            .focused_client()
+            .and_then(|id| self.clients.get(id))
+            .and_then(|c| c.wm_class.first().map(|class| (c.id, class.clone())))
+        {
+            Some(focused) => focused,
+            None => return Ok(()),
+        };
+
+        let group: Vec<Xid> = self
+            .clients
+            .clients_for_ids(&self.workspaces[wix].client_ids())
+            .iter()
+            .filter(|c| c.wm_managed && !c.floating && c.wm_class.first() == Some(&class))
+            .map(|c| c.id)
+            .collect();
+
+        let n = group.len();
+        if n < 2 {
+            return Ok(());
+        }
+
+        let i = group.iter().position(|&g| g == id).unwrap_or(0);
+        let next = match direction {
+            Direction::Forward => group[(i + 1) % n],
+            Direction::Backward => group[(i + n - 1) % n],
+        };
+
+        self.state.clients.client_lost_focus(id, &self.conn);
+        self.update_focus(next)?;
+        let screen = self.screens.focused();
+        self.conn.warp_cursor(Some(next), screen)?;
+
+        Ok(())
+    }
+
+    /// Move focus directly to the master (first) [Client] on the active [Workspace]
+    ///
+    /// # Errors
+    /// Fails with [PenroseError::EmptyWorkspace] if the active [Workspace] has no clients.
+    pub fn focus_master(&mut self) -> Result<()> {
+        let wix = self.occupied_active_ws_index()?;
+        let prev = self.workspaces[wix].focused_client();
+
+        if let Some(new) = self.workspaces[wix].focus_master() {
+            if prev == Some(new) {
+                return Ok(());
+            }
+            if let Some(prev) = prev {
+                self.state.clients.client_lost_focus(prev, &self.conn);
+            }
+            self.update_focus(new)?;
+            let screen = self.screens.focused();
+            self.conn.warp_cursor(Some(new), screen)?;
+        }
+
+        Ok(())
+    }
+
+    /// Move focus back to the previously focused [Client] on the active [Workspace], allowing
+    /// focus to be toggled between the two most recently focused clients.
+    ///
+    /// # Errors
+    /// Fails with [PenroseError::EmptyWorkspace] if the active [Workspace] has no clients.
+    pub fn focus_last_client(&mut self) -> Result<()> {
+        let wix = self.occupied_active_ws_index()?;
+        let prev = self.workspaces[wix].focused_client();
+
+        if let Some(new) = self.workspaces[wix].focus_last() {
+            if let Some(prev) = prev {
+                self.state.clients.client_lost_focus(prev, &self.conn);
+            }
+            self.update_focus(new)?;
+            let screen = self.screens.focused();
+            self.conn.warp_cursor(Some(new), screen)?;
+        }
+
+        Ok(())
+    }
+
+    /// Cycle focus between the floating [clients][1] for the active [Workspace]
+    ///
+    /// Tiled clients are skipped over entirely. If the currently focused client is not floating
+    /// then focus moves to the first (or last) floating client on the workspace. This is a no-op
+    /// if there are fewer than two floating clients.
+    ///
+    /// [1]: Client
+    pub fn cycle_floating_client(&mut self, direction: Direction) -> Result<()> {
+        self.cycle_matching_client(direction, |c| c.floating)
+    }
+
+    /// Cycle focus between the tiled [clients][1] for the active [Workspace]
+    ///
+    /// Floating clients are skipped over entirely. If the currently focused client is floating
+    /// then focus moves to the first (or last) tiled client on the workspace. This is a no-op if
+    /// there are fewer than two tiled clients.
+    ///
+    /// [1]: Client
+    pub fn cycle_tiled_client(&mut self, direction: Direction) -> Result<()> {
+        self.cycle_matching_client(direction, |c| !c.floating)
+    }
+
+    fn cycle_matching_client(
+        &mut self,
+        direction: Direction,
+        pred: impl Fn(&Client) -> bool,
+    ) -> Result<()> {
+        let ws = self.active_workspace();
+        let matching: Vec<Xid> = ws
+            .client_ids()
+            .into_iter()
+            .filter(|&id| matches!(self.clients.get(id), Some(c) if pred(c)))
+            .collect();
+
+        if matching.len() < 2 {
+            return Ok(());
+        }
+
+        let n = matching.len();
+        let current = ws
+            .focused_client()
+            .and_then(|id| matching.iter().position(|&m| m == id));
+        let ix = match (current, direction) {
+            (Some(i), Direction::Forward) => (i + 1) % n,
+            (Some(i), Direction::Backward) => (i + n - 1) % n,
+            (None, Direction::Forward) => 0,
+            (None, Direction::Backward) => n - 1,
+        };
+
+        self.focus_client(&Selector::WinId(matching[ix]))?;
+        Ok(())
+    }
+
     /// Focus the [Client] matching the given [Selector]
     pub fn focus_client(&mut self, selector: &Selector<'_, Client>) -> Result<Xid> {
         let id = match self.client(selector) {
@@ -862,16 +1588,22 @@ impl<X: XConn> WindowManager<X> {
     ///
     /// This maintains the current window layout but permutes the positions of each window within
     /// that layout.
+    ///
+    /// # Errors
+    /// Fails with [PenroseError::EmptyWorkspace] if the active [Workspace] has no clients.
     pub fn rotate_clients(&mut self, direction: Direction) -> Result<()> {
-        let wix = self.screens.active_ws_index();
+        let wix = self.occupied_active_ws_index()?;
         self.workspaces.rotate_clients(wix, direction);
         self.apply_layout(wix)
     }
 
     /// Move the focused [Client] through the stack of clients on the active [Workspace].
+    ///
+    /// # Errors
+    /// Fails with [PenroseError::EmptyWorkspace] if the active [Workspace] has no clients.
     pub fn drag_client(&mut self, direction: Direction) -> Result<()> {
+        let wix = self.occupied_active_ws_index()?;
         if let Some(id) = self.clients.focused_client_id() {
-            let wix = self.screens.active_ws_index();
             self.workspaces.drag_client(wix, direction);
             self.apply_layout(wix)?;
             self.update_focus(id)?;
@@ -883,24 +1615,50 @@ impl<X: XConn> WindowManager<X> {
 
     /// Cycle between [layouts][1] for the active [Workspace]
     ///
+    /// This is a no-op if the layout for the active workspace is
+    /// [locked][WindowManager::toggle_layout_lock].
+    ///
     /// [1]: crate::core::layout::Layout
     pub fn cycle_layout(&mut self, direction: Direction) -> Result<()> {
         let wix = self.screens.active_ws_index();
-        self.workspaces.cycle_layout(wix, direction);
+        if !self.workspaces.cycle_layout(wix, direction) {
+            return Ok(());
+        }
         self.run_hook(HookName::LayoutChange(wix));
         self.apply_layout(wix)
     }
 
-    /// Increase or decrease the number of clients in the main area by 1.
+    /// Lock or unlock the active [layout][1] for the active [Workspace].
     ///
-    /// The change is applied to the active [layout][1] on the [Workspace] that currently holds
-    /// focus.
+    /// While locked, [cycle_layout][WindowManager::cycle_layout],
+    /// [set_workspace_layout][WindowManager::set_workspace_layout] and
+    /// [set_layout_all_workspaces][WindowManager::set_layout_all_workspaces] leave the layout of
+    /// the workspace unchanged.
     ///
     /// [1]: crate::core::layout::Layout
-    pub fn update_max_main(&mut self, change: Change) -> Result<()> {
+    pub fn toggle_layout_lock(&mut self) -> Result<()> {
         let wix = self.screens.active_ws_index();
-        self.workspaces.update_max_main(wix, change);
-        self.apply_layout(wix)
+        let ws = self
+            .workspaces
+            .workspace_mut(&Selector::Index(wix))
+            .ok_or(PenroseError::NoMatchingElement)?;
+        ws.set_layout_locked(!ws.layout_locked());
+
+        Ok(())
+    }
+
+    /// Increase or decrease the number of clients in the main area by 1.
+    ///
+    /// The change is applied to the active [layout][1] on the [Workspace] that currently holds
+    /// focus.
+    ///
+    /// [1]: crate::core::layout::Layout
+    pub fn update_max_main(&mut self, change: Change) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        if self.workspaces.update_max_main(wix, change) {
+            self.run_hook(HookName::LayoutParamsChanged(wix));
+        }
+        self.apply_layout(wix)
     }
 
     /// Increase or decrease the current [layout][1] main_ratio by `main_ratio_step`
@@ -911,7 +1669,36 @@ impl<X: XConn> WindowManager<X> {
     /// [1]: crate::core::layout::Layout
     pub fn update_main_ratio(&mut self, change: Change) -> Result<()> {
         let wix = self.screens.active_ws_index();
-        self.workspaces.update_main_ratio(wix, change);
+        if self.workspaces.update_main_ratio(wix, change) {
+            self.run_hook(HookName::LayoutParamsChanged(wix));
+        }
+        self.apply_layout(wix)
+    }
+
+    /// Grow or shrink the focused [Client] on the active [Workspace] by `amount` pixels.
+    ///
+    /// The space is taken from (or given back to) the neighbouring client that shares an edge
+    /// with the focused client in the same column or row, so the layout continues to use the same
+    /// total area. Adjustments are held per workspace until reset using
+    /// [WindowManager::reset_client_sizes].
+    pub fn resize_focused(&mut self, change: Change, amount: u32) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        if let Some(ws) = self.workspaces.workspace_mut(&Selector::Index(wix)) {
+            match ws.focused_client() {
+                Some(id) => ws.update_client_size(id, change, amount),
+                None => return Ok(()),
+            }
+        }
+        self.apply_layout(wix)
+    }
+
+    /// Drop any manual size adjustments made using [WindowManager::resize_focused] for the
+    /// active [Workspace], returning each client to the space allotted to it by the layout.
+    pub fn reset_client_sizes(&mut self) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        if let Some(ws) = self.workspaces.workspace_mut(&Selector::Index(wix)) {
+            ws.reset_client_sizes();
+        }
         self.apply_layout(wix)
     }
 
@@ -927,6 +1714,42 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Serialise the current [WindowManager] state using `serialize` (e.g. `serde_json::to_string`)
+    /// and write it to `path` so that it can be picked up again using [take_restart_state][1]
+    /// followed by [WindowManager::hydrate_and_init].
+    ///
+    /// [1]: crate::core::helpers::take_restart_state
+    #[cfg(feature = "serde")]
+    pub fn write_restart_state<P, F, E>(&self, path: P, serialize: F) -> Result<()>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnOnce(&Self) -> std::result::Result<String, E>,
+        E: fmt::Display,
+    {
+        let state = serialize(self).map_err(|e| perror!("unable to serialize state: {}", e))?;
+        Ok(std::fs::write(path, state)?)
+    }
+
+    /// Restart penrose in place, preserving the current state.
+    ///
+    /// The current state is written to `path` (see [WindowManager::write_restart_state]) before
+    /// cleaning up the X connection and re-executing the running binary with its original
+    /// arguments. Your startup logic is responsible for checking `path` and restoring the state
+    /// using [WindowManager::hydrate_and_init] if it is present.
+    ///
+    /// On success this method does not return.
+    #[cfg(feature = "serde")]
+    pub fn restart<P, F, E>(&mut self, path: P, serialize: F) -> Result<()>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnOnce(&Self) -> std::result::Result<String, E>,
+        E: fmt::Display,
+    {
+        self.write_restart_state(path, serialize)?;
+        self.exit()?;
+        util::exec_current_binary()
+    }
+
     /// The layout symbol for the [layout][1] currently being used on the
     /// active workspace
     ///
@@ -936,6 +1759,69 @@ impl<X: XConn> WindowManager<X> {
         self.workspaces.current_layout_symbol(wix)
     }
 
+    /// The layout symbol for the [layout][1] currently being used on the workspace at index `wix`
+    ///
+    /// [1]: crate::core::layout::Layout
+    pub fn workspace_layout_symbol(&self, wix: usize) -> Result<&str> {
+        Ok(self.workspaces.get_workspace(wix)?.layout_symbol())
+    }
+
+    /// Set the [layout][1] for the workspace at index `wix` to be the one with the given symbol.
+    ///
+    /// The workspace is re-laid out if it is currently visible. Fails if `wix` is not a known
+    /// workspace or if the workspace has no layout matching `symbol`. This is a no-op if the
+    /// layout for the workspace is [locked][WindowManager::toggle_layout_lock].
+    ///
+    /// [1]: crate::core::layout::Layout
+    pub fn set_workspace_layout(&mut self, wix: usize, symbol: &str) -> Result<()> {
+        let ws = self
+            .workspaces
+            .workspace_mut(&Selector::Index(wix))
+            .ok_or(PenroseError::NoMatchingElement)?;
+
+        if ws.layout_locked() {
+            return Ok(());
+        }
+
+        if ws.try_set_layout(symbol).is_none() {
+            return Err(perror!("unknown layout symbol: {}", symbol));
+        }
+
+        self.run_hook(HookName::LayoutChange(wix));
+        self.apply_layout(wix)
+    }
+
+    /// Set the [layout][1] for every workspace that has a layout with the given symbol.
+    ///
+    /// Any visible workspaces are re-laid out. Returns the indices of the workspaces that have no
+    /// layout matching `symbol`, which are left unchanged. Workspaces whose layout is
+    /// [locked][WindowManager::toggle_layout_lock] are also left unchanged.
+    ///
+    /// [1]: crate::core::layout::Layout
+    pub fn set_layout_all_workspaces(&mut self, symbol: &str) -> Result<Vec<usize>> {
+        let mut missing = vec![];
+
+        for wix in 0..self.workspaces.len() {
+            let ws = self
+                .workspaces
+                .workspace_mut(&Selector::Index(wix))
+                .ok_or(PenroseError::NoMatchingElement)?;
+
+            if ws.layout_symbol() == symbol || ws.layout_locked() {
+                continue;
+            }
+
+            if ws.try_set_layout(symbol).is_none() {
+                missing.push(wix);
+            } else {
+                self.run_hook(HookName::LayoutChange(wix));
+            }
+        }
+
+        self.layout_visible()?;
+        Ok(missing)
+    }
+
     /// Set the root X window name. Useful for exposing information to external programs
     pub fn set_root_window_name(&self, s: impl AsRef<str>) -> Result<()> {
         Ok(self.conn.set_root_window_name(s.as_ref())?)
@@ -948,6 +1834,21 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Set the displayed workspace for the focused screen to be the workspace at index `n`.
+    ///
+    /// Unlike [WindowManager::focus_workspace], this returns an error if there is no workspace at
+    /// the requested index. It can be bound for each workspace using a `map` block with `VAL`
+    /// arguments when calling [gen_keybindings][1], e.g. `"M-{}" => focus_workspace_n (VAL);`
+    /// mapped over `0..9`.
+    ///
+    /// [1]: crate::gen_keybindings
+    pub fn focus_workspace_n(&mut self, n: usize) -> Result<()> {
+        if n >= self.workspaces.len() {
+            return Err(PenroseError::NoMatchingElement);
+        }
+        self.focus_workspace(&Selector::Index(n))
+    }
+
     /// Set the displayed workspace for the focused screen to be `index` in the list of
     /// workspaces passed at `init`.
     ///
@@ -963,8 +1864,14 @@ impl<X: XConn> WindowManager<X> {
         }
 
         if let Some(index) = self.workspaces.index(selector) {
+            if let Some(id) = self.carried_client {
+                self.move_client_to_workspace(id, index, true)?;
+            }
+
             let active = self.screens.active_ws_index();
-            self.previous_workspace = active;
+            let was_spanning = self.screens.spanning_workspace() == Some(active);
+            self.previous_workspaces
+                .insert(self.screens.active_screen_index(), active);
 
             for i in 0..self.screens.n_screens() {
                 if self.screens.inner[i].wix == index {
@@ -973,6 +1880,7 @@ impl<X: XConn> WindowManager<X> {
                     // workspace we had on the screen where the target was
                     self.screens.inner[i].wix = self.screens.focused().wix;
                     self.screens.focused_mut().wix = index;
+                    self.previous_workspaces.insert(i, index);
 
                     // re-apply layouts as screen dimensions may differ
                     self.apply_layout(active)?;
@@ -1015,21 +1923,76 @@ impl<X: XConn> WindowManager<X> {
 
             self.workspaces.focus(&Selector::Index(index));
             self.run_hook(HookName::WorkspaceChange(active, index));
+
+            // the workspaces on other screens were hidden while 'active' was spanning them
+            if was_spanning {
+                self.screens.set_spanning_workspace(None);
+                self.layout_visible()?;
+            }
         }
 
         Ok(())
     }
 
-    /// Switch focus back to the last workspace that had focus.
+    /// Toggle the [Workspace] at index `wix` between being shown on a single screen and spanning
+    /// all connected screens.
+    ///
+    /// While spanning, the clients of the workspace are arranged across the combined region of
+    /// every screen and the workspaces shown on the other screens are hidden. If `wix` is not
+    /// currently visible it is first focused on the active screen and, while it is spanning,
+    /// focus will not move to any of the hidden screens. Toggling again (or replacing the
+    /// workspace on its screen) restores normal per-screen layouts.
+    pub fn span_workspace_all_screens(&mut self, wix: usize) -> Result<()> {
+        if wix >= self.workspaces.len() {
+            return Err(PenroseError::NoMatchingElement);
+        }
+
+        if self.screens.spanning_workspace() == Some(wix) {
+            self.screens.set_spanning_workspace(None);
+        } else {
+            if !self.screens.visible_workspaces().contains(&wix) {
+                self.focus_workspace(&Selector::Index(wix))?;
+            }
+            self.screens.set_spanning_workspace(Some(wix));
+        }
+
+        self.layout_visible()
+    }
+
+    /// Switch focus back to the last workspace that had focus on the focused screen.
+    ///
+    /// Workspace history is tracked per screen, so toggling on one screen is unaffected by
+    /// workspace changes made on any other screen.
     pub fn toggle_workspace(&mut self) -> Result<()> {
-        self.focus_workspace(&Selector::Index(self.previous_workspace))
+        let screen_index = self.screens.active_screen_index();
+        let previous = self
+            .previous_workspaces
+            .get(&screen_index)
+            .copied()
+            .unwrap_or(0);
+        self.focus_workspace(&Selector::Index(previous))
+    }
+
+    /// Switch focus back to the last [screen][Screen] that had focus, warping the cursor to it.
+    ///
+    /// This is a no-op if there is only a single screen or if focus has not yet moved between
+    /// screens.
+    pub fn toggle_screen(&mut self) -> Result<()> {
+        if self.screens.n_screens() < 2 {
+            return Ok(());
+        }
+
+        match self.screens.previous_index() {
+            Some(i) => self.focus_screen_n(i),
+            None => Ok(()),
+        }
     }
 
     /// Move the focused client to the workspace matching 'selector'.
     pub fn client_to_workspace(&mut self, selector: &Selector<'_, Workspace>) -> Result<()> {
         if let Some(id) = self.clients.focused_client_id() {
             if let Some(wix) = self.workspaces.index(selector) {
-                self.move_client_to_workspace(id, wix)?;
+                self.move_client_to_workspace(id, wix, false)?;
                 if let Some(now_focused) = self.active_workspace().focused_client() {
                     self.state.clients.set_focused(now_focused, &self.conn);
                 }
@@ -1039,6 +2002,52 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Start or stop carrying the focused [Client] between workspaces.
+    ///
+    /// While a client is being carried, it is moved to each workspace that is focused using
+    /// [focus_workspace][WindowManager::focus_workspace] (or any of the methods that call it,
+    /// such as [cycle_workspace][WindowManager::cycle_workspace]) and given focus there.
+    /// Toggling again drops the client on the workspace it is currently on. This is a no-op if
+    /// nothing is being carried and there is no focused client.
+    pub fn toggle_carry_focused(&mut self) -> Result<()> {
+        self.carried_client = match self.carried_client {
+            Some(_) => None,
+            None => self.clients.focused_client_id(),
+        };
+
+        Ok(())
+    }
+
+    /// The ID of the [Client] currently being carried between workspaces, if there is one.
+    ///
+    /// See [toggle_carry_focused][WindowManager::toggle_carry_focused].
+    pub fn carried_client(&self) -> Option<Xid> {
+        self.carried_client
+    }
+
+    /// Move the [Client] with the given ID back to the workspace it was first placed on when it
+    /// was mapped.
+    ///
+    /// This is useful for returning clients that have been temporarily pulled onto another
+    /// workspace. Clients that were never placed on a workspace (such as those that are
+    /// externally managed) are left where they are.
+    ///
+    /// # Errors
+    /// Fails with [PenroseError::UnknownClient] if `id` is not a known client.
+    pub fn return_client_home(&mut self, id: Xid) -> Result<()> {
+        let origin = match self.clients.get(id) {
+            Some(c) => c.origin_workspace(),
+            None => return Err(PenroseError::UnknownClient(id)),
+        };
+
+        match origin {
+            Some(wix) if wix < self.workspaces.len() => {
+                self.move_client_to_workspace(id, wix, false)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Move the focused client to the active workspace on the screen matching 'selector'.
     pub fn client_to_screen(&mut self, selector: &Selector<'_, Screen>) -> Result<()> {
         let i = match self.screen(selector) {
@@ -1048,6 +2057,138 @@ impl<X: XConn> WindowManager<X> {
         self.client_to_workspace(&Selector::Index(i))
     }
 
+    /// Move the focused client to the active workspace on the screen that is physically next to
+    /// the focused screen in the given direction.
+    ///
+    /// Adjacency is determined using the screen geometry reported by the [XConn], so this works
+    /// regardless of the order that screens are indexed in. If there is no screen in the requested
+    /// direction then this is a no-op.
+    pub fn move_focused_to_screen_in_direction(
+        &mut self,
+        position: RelativePosition,
+    ) -> Result<()> {
+        match self.screens.screen_in_direction(position) {
+            Some(i) => self.client_to_screen(&Selector::Index(i)),
+            None => Ok(()),
+        }
+    }
+
+    /// Move every client on the workspace at index `from` to the end of the stack of the
+    /// workspace at index `into`, leaving `from` empty.
+    pub fn merge_workspaces(&mut self, from: usize, into: usize) -> Result<()> {
+        let n_workspaces = self.workspaces.len();
+        if from >= n_workspaces || into >= n_workspaces {
+            return Err(PenroseError::NoMatchingElement);
+        }
+        if from == into {
+            return Ok(());
+        }
+
+        let existing = self.workspaces.client_ids(into)?;
+
+        for id in self.workspaces.client_ids(from)? {
+            if existing.contains(&id) {
+                // already tracked on the target workspace
+                self.workspaces.remove_client(from, id);
+                self.clients.set_client_workspace(id, into);
+                self.conn.set_client_workspace(id, into)?;
+            } else {
+                self.move_client_without_layout(id, from, into)?;
+            }
+        }
+
+        self.layout_visible()
+    }
+
+    // Move a client to the end of the stack of another workspace, unmapping it if that workspace
+    // is not visible. Callers are responsible for laying out the affected workspaces once they
+    // have finished moving clients.
+    fn move_client_without_layout(&mut self, id: Xid, from: usize, into: usize) -> Result<()> {
+        self.workspaces.remove_client(from, id);
+        self.workspaces[into].add_client(id, &InsertPoint::Last)?;
+        self.clients.set_client_workspace(id, into);
+        self.conn.set_client_workspace(id, into)?;
+        self.run_hook(HookName::ClientAddedToWorkspace(id, into));
+
+        if !self.screens.visible_workspaces().contains(&into) {
+            self.state.clients.unmap_if_needed(id, &self.conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Distribute the clients on the active [Workspace] round-robin across the empty workspaces
+    /// that follow it, leaving the first `keep` clients in place.
+    ///
+    /// Clients moved to workspaces that are not currently visible are unmapped and will be laid
+    /// out when their new workspace is next shown. This is a no-op if there are no empty
+    /// workspaces.
+    pub fn spread_clients(&mut self, keep: usize) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        let n_workspaces = self.workspaces.len();
+        let empty: Vec<usize> = (1..n_workspaces)
+            .map(|i| (wix + i) % n_workspaces)
+            .filter(|&ix| self.workspaces[ix].is_empty())
+            .collect();
+
+        if empty.is_empty() {
+            return Ok(());
+        }
+
+        let to_move = self.workspaces.client_ids(wix)?.into_iter().skip(keep);
+        for (id, &target) in to_move.zip(empty.iter().cycle()) {
+            self.move_client_without_layout(id, wix, target)?;
+        }
+
+        if let Some(now_focused) = self.active_workspace().focused_client() {
+            self.state.clients.set_focused(now_focused, &self.conn);
+        }
+
+        self.layout_visible()
+    }
+
+    /// Move clients between the workspaces currently visible on each screen so that they all hold
+    /// a roughly equal number of clients, then lay out each of them again.
+    ///
+    /// Clients are taken from the end of the stack of the fullest workspace and added to the end
+    /// of the stack of the emptiest until the number of clients on each visible workspace differs
+    /// by at most one. Workspaces that are not visible are left unchanged.
+    pub fn balance_visible_workspaces(&mut self) -> Result<()> {
+        let mut visible = self.screens.visible_workspaces();
+        visible.sort_unstable();
+        visible.dedup();
+
+        loop {
+            let counts: Vec<(usize, usize)> = visible
+                .iter()
+                .map(|&wix| (wix, self.workspaces[wix].len()))
+                .collect();
+            let (from, most) = match counts.iter().max_by_key(|(_, n)| *n) {
+                Some(&fullest) => fullest,
+                None => break,
+            };
+            let (into, least) = match counts.iter().min_by_key(|(_, n)| *n) {
+                Some(&emptiest) => emptiest,
+                None => break,
+            };
+            if most - least <= 1 {
+                break;
+            }
+
+            let id = match self.workspaces[from].iter_rev().next() {
+                Some(&id) => id,
+                None => break,
+            };
+            self.move_client_without_layout(id, from, into)?;
+        }
+
+        if let Some(now_focused) = self.active_workspace().focused_client() {
+            self.state.clients.set_focused(now_focused, &self.conn);
+        }
+
+        self.layout_visible()
+    }
+
     /// Toggle the fullscreen state of the [Client] matching the given [Selector]
     pub fn toggle_client_fullscreen(&mut self, selector: &Selector<'_, Client>) -> Result<()> {
         let (id, client_is_fullscreen) = match self.client(selector) {
@@ -1057,6 +2198,134 @@ impl<X: XConn> WindowManager<X> {
         self.set_fullscreen(id, !client_is_fullscreen)
     }
 
+    /// Force the [Client] with ID 'id' to be given (or not given) input focus directly, overriding
+    /// the input hint that it has set. Useful for applications that set their hints incorrectly.
+    pub fn set_client_focusable(&mut self, id: Xid, focusable: bool) -> Result<()> {
+        match self.clients.get_mut(id) {
+            Some(c) => c.set_focusable(Some(focusable)),
+            None => return Err(PenroseError::UnknownClient(id)),
+        }
+
+        if self.clients.focused_client_id() == Some(id) {
+            self.update_focus(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set whether or not the [Client] with ID 'id' should be managed by the active layout.
+    ///
+    /// Clients that are not managed remain on their workspace but are skipped when the layout is
+    /// applied, leaving them at their current position until they are managed again.
+    pub fn set_client_managed(&mut self, id: Xid, managed: bool) -> Result<()> {
+        let wix = match self.clients.get(id) {
+            Some(c) => c.workspace(),
+            None => return Err(PenroseError::UnknownClient(id)),
+        };
+
+        self.clients.modify(id, |c| c.wm_managed = managed);
+        self.apply_layout(wix)
+    }
+
+    /// Float the focused client and center it on the focused screen.
+    ///
+    /// The client is resized to `size_ratio` of the screen's effective width and height, which
+    /// must be in the range (0.0, 1.0]. The resulting region is stored as the client's floating
+    /// position.
+    pub fn float_focused_centered(&mut self, size_ratio: f32) -> Result<()> {
+        if !(size_ratio > 0.0 && size_ratio <= 1.0) {
+            return Err(perror!(
+                "size_ratio must be in (0.0, 1.0]: got {}",
+                size_ratio
+            ));
+        }
+
+        let (id, wix) = match self.clients.focused_client() {
+            Some(c) => (c.id(), c.workspace()),
+            None => return Ok(()),
+        };
+
+        let screen_region = self.screens.focused().region(self.config.show_bar);
+        let r = screen_region
+            .scale_w(size_ratio as f64)
+            .scale_h(size_ratio as f64)
+            .centered_in(&screen_region)?;
+
+        self.clients.modify(id, |c| {
+            c.floating = true;
+            c.floating_region = Some(r);
+            c.pre_maximize_region = None;
+        });
+        self.apply_layout(wix)?;
+        self.position_client(id, r, true)
+    }
+
+    /// Toggle the focused floating client between filling the effective region of the focused
+    /// screen and its previous floating position.
+    ///
+    /// Unlike fullscreen, the client keeps its border and space is still left for the bar. The
+    /// client remains floating in both states. This is a no-op if the focused client is tiled.
+    pub fn toggle_maximize_floating(&mut self) -> Result<()> {
+        let (id, saved) = match self.clients.focused_client() {
+            Some(c) if c.floating => (c.id(), c.pre_maximize_region),
+            _ => return Ok(()),
+        };
+
+        let r = match saved {
+            Some(r) => {
+                self.clients.modify(id, |c| c.pre_maximize_region = None);
+                r
+            }
+            None => {
+                let current = match self.client_geometry(id) {
+                    Some(r) => r,
+                    None => self.conn.client_geometry(id)?,
+                };
+                self.clients
+                    .modify(id, |c| c.pre_maximize_region = Some(current));
+                self.screens.focused().region(self.config.show_bar)
+            }
+        };
+
+        self.clients.modify(id, |c| c.floating_region = Some(r));
+        self.position_client(id, r, true)
+    }
+
+    /// Return every floating client on the active workspace to the tiled layout.
+    ///
+    /// Any saved floating positions are discarded and fullscreen clients are taken out of
+    /// fullscreen before the workspace is laid out again.
+    pub fn tile_all_floating(&mut self) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        let ids = self.workspaces.client_ids(wix)?;
+
+        for &id in ids.iter() {
+            if matches!(self.clients.get(id), Some(c) if c.fullscreen) {
+                self.set_fullscreen(id, false)?;
+            }
+        }
+
+        for id in ids {
+            self.clients.modify(id, |c| {
+                c.floating = false;
+                c.floating_region = None;
+                c.pre_maximize_region = None;
+            });
+        }
+
+        self.apply_layout(wix)
+    }
+
+    /// Run an external command, splitting `cmd` on whitespace into a program and its arguments.
+    ///
+    /// The process is spawned in the background so this will not block the event loop. See
+    /// [spawn][1] for details.
+    ///
+    /// [1]: crate::core::helpers::spawn
+    pub fn spawn(&self, cmd: &str) -> Result<()> {
+        crate::core::helpers::spawn(cmd)
+    }
+
     /// Kill the focused client window.
     #[tracing::instrument(level = "debug", err, skip(self))]
     pub fn kill_client(&mut self) -> Result<()> {
@@ -1069,6 +2338,51 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Ask every client on the workspace at index `wix` to close.
+    ///
+    /// Clients that support `WM_DELETE_WINDOW` are sent a delete request so that they are able to
+    /// shut down cleanly, all others are killed by the X server. The
+    /// [kill_workspace_clients][1] hooks are run before any clients are closed and are able to
+    /// veto the action by returning an error.
+    ///
+    /// [1]: crate::core::hooks::Hook::kill_workspace_clients
+    #[tracing::instrument(level = "debug", err, skip(self))]
+    pub fn kill_workspace_clients(&mut self, wix: usize) -> Result<()> {
+        let ids = self
+            .workspaces
+            .client_ids(wix)
+            .map_err(|_| PenroseError::NoMatchingElement)?;
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        // Run manually rather than via run_hook so that hooks are able to veto the kill
+        let mut hooks = self.hooks.replace(vec![]);
+        let res = hooks
+            .iter_mut()
+            .try_for_each(|h| h.kill_workspace_clients(self, wix, &ids));
+        self.hooks.replace(hooks);
+        res?;
+
+        for id in ids {
+            let polite = matches!(
+                self.clients.get(id),
+                Some(c) if c.supports_protocol(Atom::WmDeleteWindow)
+            );
+
+            if polite {
+                let msg = ClientMessageKind::DeleteWindow(id).as_message(&self.conn)?;
+                self.conn.send_client_event(msg)?;
+            } else {
+                self.conn.kill_client(id)?;
+            }
+        }
+        self.conn.flush();
+
+        Ok(())
+    }
+
     /// Get a reference to the first Screen satisfying 'selector'. Xid selectors will return
     /// the screen containing that Client if the client is known.
     /// NOTE: It is not possible to get a mutable reference to a Screen.
@@ -1170,6 +2484,17 @@ impl<X: XConn> WindowManager<X> {
         self.update_x_workspace_details()
     }
 
+    /// Set the name of the active Workspace.
+    ///
+    /// This can be combined with [spawn_for_output][1] to rename workspaces interactively, e.g.
+    /// using the output of `dmenu`.
+    ///
+    /// [1]: crate::core::helpers::spawn_for_output
+    pub fn rename_current_workspace(&mut self, name: impl Into<String>) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        self.set_workspace_name(name, &Selector::Index(wix))
+    }
+
     /// Take a reference to the first Client found matching 'selector'
     pub fn client(&self, selector: &Selector<'_, Client>) -> Option<&Client> {
         match selector {
@@ -1250,10 +2575,29 @@ impl<X: XConn> WindowManager<X> {
         self.screens.screen_size(index, self.config.show_bar)
     }
 
+    /// The index of the [Screen] for the primary RandR output, if the [XConn] reports one.
+    ///
+    /// This screen is focused when the [WindowManager] is initialised.
+    pub fn primary_screen_index(&self) -> Option<usize> {
+        self.screens.primary_index()
+    }
+
+    /// Toggle whether or not space is reserved for a status bar and re-apply layouts.
+    ///
+    /// This is a global setting applied to all screens. Only the space reserved for the bar is
+    /// affected: showing or hiding the bar window itself is left to the bar implementation.
+    pub fn toggle_bar(&mut self) -> Result<()> {
+        self.state.config.show_bar = !self.config.show_bar;
+        self.layout_visible()
+    }
+
     /// Position an individual client on the display. (x,y) coordinates are absolute (i.e. relative
     /// to the root window not any individual screen).
     pub fn position_client(&self, id: Xid, region: Region, stack_above: bool) -> Result<()> {
-        let bpx = self.config.border_px;
+        let bpx = match self.clients.get(id) {
+            Some(c) => self.border_px(c.workspace()),
+            None => self.config.border_px,
+        };
         self.conn
             .position_client(id, region, bpx, stack_above)
             .map_err(|e| e.into())
@@ -1268,6 +2612,33 @@ impl<X: XConn> WindowManager<X> {
             .map_err(|e| e.into())
     }
 
+    /// Fetch a property for the Client with ID 'id', using a cached value if one is available.
+    ///
+    /// Cached properties are dropped whenever the X server notifies us that a property on the
+    /// client has changed.
+    pub fn client_prop(&self, id: Xid, name: &str) -> Result<Prop> {
+        self.state.clients.get_prop(id, name, &self.conn)
+    }
+
+    /// The current geometry of the Client with ID 'id', for use by external tools.
+    ///
+    /// Floating clients report their saved floating position if they have one, otherwise this is
+    /// the region they were last given by the layout. Returns `None` if the client is unknown or
+    /// not currently mapped.
+    pub fn client_geometry(&self, id: Xid) -> Option<Region> {
+        let c = self.clients.get(id).filter(|c| c.mapped)?;
+        if c.floating {
+            c.floating_region().or_else(|| c.last_region())
+        } else {
+            c.last_region()
+        }
+    }
+
+    /// Drop any cached properties for the Client with ID 'id'.
+    pub fn invalidate_client_props(&mut self, id: Xid) {
+        self.state.clients.invalidate(id)
+    }
+
     /// Hide the Client with ID 'id'.
     pub fn hide_client(&mut self, id: Xid) -> Result<()> {
         self.state.clients.unmap_if_needed(id, &self.conn)
@@ -1282,6 +2653,19 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Re-apply the layout for the workspace at index `wix`, or for all visible workspaces if
+    /// `wix` is `None`.
+    ///
+    /// This is useful after moving clients around programmatically. Workspaces that are not
+    /// currently displayed on a screen are left untouched.
+    pub fn layout_workspace(&mut self, wix: Option<usize>) -> Result<()> {
+        match wix {
+            Some(wix) if wix >= self.workspaces.len() => Err(PenroseError::NoMatchingElement),
+            Some(wix) => self.apply_layout(wix),
+            None => self.layout_visible(),
+        }
+    }
+
     /// An index into the WindowManager known screens for the screen that is currently focused
     pub fn active_screen_index(&self) -> usize {
         self.screens.active_screen_index()
@@ -1293,21 +2677,30 @@ mod tests {
     use super::*;
     use crate::{
         __test_helpers::{
-            n_clients, test_key_bindings, test_mouse_bindings, test_windowmanager, RecordedCall,
-            RecordingXConn,
+            n_clients, test_key_bindings, test_layouts, test_mouse_bindings, test_windowmanager,
+            RecordedCall, RecordingXConn, TestXConn, EXIT_CODE, LAYOUT_CHANGE_CODE,
         },
         core::{
+            bindings::{LockModifier, ModifierKey, MouseButton, MouseEventKind, MouseState},
             data_types::*,
             helpers::logging_error_handler,
             layout::*,
             ring::Direction::*,
             screen::*,
-            xconnection::{MockXConn, Prop, XEvent},
+            xconnection::{
+                ClientEventMask, ClientMessage, ClientMessageData, MockXConn, PointerChange, Prop,
+                PropertyEvent, WmNormalHints, XEvent,
+            },
         },
         draw::Color,
     };
 
-    use std::{cell::Cell, collections::HashMap, convert::TryFrom};
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        convert::TryFrom,
+        rc::Rc,
+    };
 
     fn wm_with_mock_conn(events: Vec<XEvent>, unmanaged_ids: Vec<Xid>) -> WindowManager<MockXConn> {
         let conn = MockXConn::new(test_screens(), events, unmanaged_ids);
@@ -1545,8 +2938,151 @@ mod tests {
         assert!(wm.workspaces[0].len() == 1);
     }
 
-    struct ScreenChangingXConn {
-        num_screens: Cell<usize>,
+    #[test]
+    fn above_and_below_clients_are_restacked_around_peers() {
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(
+            conf,
+            RecordingXConn::init(),
+            vec![],
+            logging_error_handler(),
+        );
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 4, 0);
+        wm.clients.modify(10, |c| c.above = true);
+        wm.clients.modify(20, |c| c.floating = true);
+        wm.clients.modify(30, |c| c.below = true);
+        wm.conn().clear();
+
+        wm.layout_screen(0).unwrap();
+
+        let stacking: Vec<RecordedCall> = wm
+            .conn()
+            .calls()
+            .into_iter()
+            .filter(|(m, _)| m == "raise_client" || m == "lower_client")
+            .collect();
+
+        assert_eq!(
+            stacking,
+            vec![
+                ("lower_client".into(), strings!(30)),
+                ("raise_client".into(), strings!(20)),
+                ("raise_client".into(), strings!(10)),
+            ]
+        );
+    }
+
+    // Atoms 1 and 2 are _NET_WM_STATE_ABOVE and _NET_WM_STATE_BELOW: anything else is sticky
+    struct StackingXConn {
+        wm_state: RefCell<Vec<String>>,
+    }
+
+    __impl_stub_xcon! {
+        for StackingXConn;
+
+        atom_queries: {
+            fn mock_atom_name(&self, atom: Xid) -> XResult<String> {
+                Ok(match atom {
+                    1 => Atom::NetWmStateAbove.as_ref().to_string(),
+                    2 => Atom::NetWmStateBelow.as_ref().to_string(),
+                    _ => "_NET_WM_STATE_STICKY".to_string(),
+                })
+            }
+        }
+        client_properties: {
+            fn mock_get_prop(&self, id: Xid, name: &str) -> XResult<Prop> {
+                if name == Atom::NetWmState.as_ref() {
+                    Ok(Prop::Atom(self.wm_state.borrow().clone()))
+                } else {
+                    Err(crate::core::xconnection::XError::MissingProperty(name.into(), id))
+                }
+            }
+
+            fn mock_change_prop(&self, _: Xid, name: &str, val: Prop) -> XResult<()> {
+                if let (true, Prop::Atom(atoms)) = (name == Atom::NetWmState.as_ref(), val) {
+                    *self.wm_state.borrow_mut() = atoms;
+                }
+                Ok(())
+            }
+        }
+        client_handler: {}
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> XResult<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
+            }
+        }
+        conn: {}
+    }
+
+    fn stacking_test_wm(initial: &[Atom]) -> WindowManager<StackingXConn> {
+        let conn = StackingXConn {
+            wm_state: RefCell::new(initial.iter().map(|a| a.as_ref().to_string()).collect()),
+        };
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.handle_map_request(10).unwrap();
+
+        wm
+    }
+
+    fn stacking(wm: &WindowManager<StackingXConn>) -> (bool, bool) {
+        let c = wm.clients.get(10).unwrap();
+        (c.is_above(), c.is_below())
+    }
+
+    test_cases! {
+        net_wm_state_stacking_messages;
+        args: (initial: &[Atom], action: u32, atoms: [u32; 2], expected: (bool, bool));
+
+        case: add_above => (&[], 1, [1, 0], (true, false));
+        case: add_below_clears_above => (&[Atom::NetWmStateAbove], 1, [2, 0], (false, true));
+        case: remove_above => (&[Atom::NetWmStateAbove], 0, [1, 0], (false, false));
+        case: toggle_below => (&[Atom::NetWmStateBelow], 2, [2, 0], (false, false));
+        case: unrelated_state => (&[], 1, [3, 0], (false, false));
+
+        body: {
+            let mut wm = stacking_test_wm(initial);
+
+            let data = ClientMessageData::from([action, atoms[0], atoms[1], 0, 0]);
+            let mask = ClientEventMask::NoEventMask;
+            let msg = ClientMessage::new(10, mask, Atom::NetWmState.as_ref(), data);
+            let actions = process_next_event(XEvent::ClientMessage(msg), &wm.state, &wm.conn);
+            wm.handle_event_actions(actions).unwrap();
+
+            assert_eq!(stacking(&wm), expected);
+            assert_eq!(stacking_from_wm_state(&wm.conn().wm_state.borrow()), expected);
+        }
+    }
+
+    #[test]
+    fn net_wm_state_property_changes_update_stacking() {
+        let mut wm = stacking_test_wm(&[Atom::NetWmStateAbove]);
+        assert_eq!(stacking(&wm), (true, false));
+
+        *wm.conn().wm_state.borrow_mut() = vec![Atom::NetWmStateBelow.as_ref().to_string()];
+        let event = XEvent::PropertyNotify(PropertyEvent {
+            id: 10,
+            atom: Atom::NetWmState.as_ref().to_string(),
+            is_root: false,
+        });
+        let actions = process_next_event(event, &wm.state, &wm.conn);
+        wm.handle_event_actions(actions).unwrap();
+
+        assert_eq!(stacking(&wm), (false, true));
+    }
+
+    struct ScreenChangingXConn {
+        num_screens: Cell<usize>,
     }
 
     impl ScreenChangingXConn {
@@ -1574,410 +3110,2897 @@ mod tests {
         conn: {}
     }
 
-    impl WindowManager<ScreenChangingXConn> {
-        fn set_num_screens(&mut self, n: usize) {
-            self.conn_mut().set_num_screens(n);
+    struct WindowTypeXConn {
+        window_type: Atom,
+        maps: Cell<Vec<Xid>>,
+    }
+
+    __impl_stub_xcon! {
+        for WindowTypeXConn;
+
+        atom_queries: {}
+        client_properties: {
+            fn mock_get_prop(&self, id: Xid, name: &str) -> crate::core::xconnection::Result<Prop> {
+                if name == Atom::NetWmWindowType.as_ref() {
+                    Ok(Prop::Atom(vec![self.window_type.as_ref().to_string()]))
+                } else {
+                    Err(crate::core::xconnection::XError::MissingProperty(name.into(), id))
+                }
+            }
+        }
+        client_handler: {
+            fn mock_map_client(&self, id: Xid) -> crate::core::xconnection::Result<()> {
+                let mut maps = self.maps.take();
+                maps.push(id);
+                self.maps.set(maps);
+                Ok(())
+            }
+        }
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> crate::core::xconnection::Result<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
+            }
+
+            fn mock_active_clients(&self) -> crate::core::xconnection::Result<Vec<Xid>> {
+                Ok(vec![10])
+            }
         }
+        conn: {}
     }
 
-    // TODO: rewrite and move out to screens.rs
-    #[test]
-    fn updating_screens_retains_focused_workspaces() {
-        let conn = ScreenChangingXConn {
-            num_screens: Cell::new(1),
-        };
-        let conf = Config::default();
-        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
-        wm.init().unwrap();
+    test_cases! {
+        existing_windows_use_configured_unmanaged_types;
+        args: (window_type: Atom, managed: bool);
 
-        // detect_screens is called on init so should have one screen
-        assert_eq!(wm.screens.n_screens(), 1);
-        assert_eq!(wm.screens.focused().wix, 0);
+        case: configured_type => (Atom::NetWindowTypeToolbar, false);
+        case: default_type_not_configured => (Atom::NetWindowTypeDock, true);
 
-        // Focus workspace 1 the redetect screens: should have 1 and 0
-        wm.focus_workspace(&Selector::Index(1)).unwrap();
-        assert_eq!(wm.screens.focused().wix, 1);
-        wm.set_num_screens(2);
-        wm.detect_screens().unwrap();
-        assert_eq!(wm.screens.n_screens(), 2);
-        assert_eq!(wm.screens.get(0).unwrap().wix, 1);
-        assert_eq!(wm.screens.get(1).unwrap().wix, 0);
+        body: {
+            let conn = WindowTypeXConn {
+                window_type,
+                maps: Cell::new(vec![]),
+            };
+            let conf = Config {
+                unmanaged_window_types: vec![Atom::NetWindowTypeToolbar.as_ref().to_string()],
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
 
-        // Adding another screen should now have WS 2 as 1 is taken
-        wm.set_num_screens(3);
-        wm.detect_screens().unwrap();
-        assert_eq!(wm.screens.n_screens(), 3);
-        assert_eq!(wm.screens.get(0).unwrap().wix, 1);
-        assert_eq!(wm.screens.get(1).unwrap().wix, 0);
-        assert_eq!(wm.screens.get(2).unwrap().wix, 2);
+            wm.try_manage_existing_windows().unwrap();
 
-        // Focus WS 3 on screen 1, drop down to 1 screen: it should still have WS 3
-        wm.focus_workspace(&Selector::Index(3)).unwrap();
-        wm.set_num_screens(1);
-        wm.detect_screens().unwrap();
-        assert_eq!(wm.screens.n_screens(), 1);
-        assert_eq!(wm.screens.get(0).unwrap().wix, 3);
+            assert_eq!(wm.clients.is_known(10), managed);
+        }
     }
 
-    // Check that workspace layout is triggered correctly from public methods
+    test_cases! {
+        unmanaged_window_types;
+        args: (window_type: Atom, managed: bool);
 
-    macro_rules! layout_trigger_test {
-        { $method:ident; $should_layout:expr; $($arg:expr),* } => {
-            paste::paste! {
-                #[test]
-                fn [<layout_trigger_test _ $method>]() {
-                    let conn = RecordingXConn::init();
-                    let conf = Config {
-                        layouts: focus_test_layouts(false),
-                        ..Default::default()
-                    };
-                    let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
-                    wm.init().unwrap();
-                    add_n_clients(&mut wm, 3, 0);
-                    wm.focus_workspace(&Selector::Index(1)).unwrap();
-                    add_n_clients(&mut wm, 3, 30);
-                    wm.focus_workspace(&Selector::Index(0)).unwrap();
-                    wm.conn.clear();
-                    wm.$method($($arg),*).unwrap();
+        case: dock => (Atom::NetWindowTypeDock, false);
+        case: desktop => (Atom::NetWindowTypeDesktop, false);
+        case: tooltip => (Atom::NetWindowTypeTooltip, false);
+        case: notification => (Atom::NetWindowTypeNotification, false);
+        case: normal => (Atom::NetWindowTypeNormal, true);
 
-                    // Defining "we applied layout" as "position_client" was called
-                    // at least once. Tests around layout application itself being
-                    // correct are handled separately
-                    let did_layout = wm
-                        .conn
-                        .calls()
-                        .iter()
-                        .any(|c| c.0 == *"position_client");
+        body: {
+            let conn = WindowTypeXConn {
+                window_type,
+                maps: Cell::new(vec![]),
+            };
+            let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+            wm.init().unwrap();
 
-                    if $should_layout {
-                        assert!(did_layout);
-                    } else {
-                        assert!(!did_layout);
-                    }
-                }
+            wm.handle_map_request(10).unwrap();
+
+            assert_eq!(wm.clients.get(10).unwrap().wm_managed, managed);
+            assert_eq!(wm.workspaces[0].client_ids().contains(&10), managed);
+            if !managed {
+                assert_eq!(wm.conn().maps.take(), vec![10]);
             }
         }
     }
 
-    layout_trigger_test!(cycle_workspace; true; Forward);
-    layout_trigger_test!(drag_workspace; true; Forward);
-    layout_trigger_test!(cycle_client; false; Forward);
-    layout_trigger_test!(focus_client; false; &Selector::Any);
-    layout_trigger_test!(rotate_clients; true; Forward);
-    layout_trigger_test!(drag_client; true; Forward);
-    layout_trigger_test!(cycle_layout; true; Forward);
-    layout_trigger_test!(update_max_main; true; Change::More);
-    layout_trigger_test!(update_main_ratio; true; Change::More);
-    layout_trigger_test!(exit; false;);
-    layout_trigger_test!(set_root_window_name; false; "test");
-    layout_trigger_test!(set_client_insert_point; false; InsertPoint::First);
-    layout_trigger_test!(focus_workspace; true; &Selector::Index(1));
-    layout_trigger_test!(toggle_workspace; true;);
-    layout_trigger_test!(client_to_workspace; true; &Selector::Index(1));
-    layout_trigger_test!(client_to_screen; true; &Selector::Index(1));
-    layout_trigger_test!(toggle_client_fullscreen; true; &Selector::WinId(10));
-    layout_trigger_test!(kill_client; false;);
-    layout_trigger_test!(remove_workspace; true; &Selector::Index(0));
-    layout_trigger_test!(position_client; true; 10, Region::default(), true);
-    layout_trigger_test!(layout_screen; true; 0);
+    struct DesktopXConn {
+        requested: Option<u32>,
+        desktops: Cell<Vec<(Xid, Prop)>>,
+    }
 
-    /*
-     * Helpers for specifying expected events with RecordingXConn
-     */
+    __impl_stub_xcon! {
+        for DesktopXConn;
 
-    fn _focus(id: Xid) -> RecordedCall {
-        ("focus_client".into(), strings!(id))
+        atom_queries: {}
+        client_properties: {
+            fn mock_get_prop(&self, id: Xid, name: &str) -> crate::core::xconnection::Result<Prop> {
+                match self.requested {
+                    Some(wix) if name == Atom::NetWmDesktop.as_ref() => Ok(Prop::Cardinal(wix)),
+                    _ => Err(crate::core::xconnection::XError::MissingProperty(name.into(), id)),
+                }
+            }
+
+            fn mock_change_prop(&self, id: Xid, name: &str, val: Prop) -> crate::core::xconnection::Result<()> {
+                if name == Atom::NetWmDesktop.as_ref() {
+                    let mut desktops = self.desktops.take();
+                    desktops.push((id, val));
+                    self.desktops.set(desktops);
+                }
+                Ok(())
+            }
+        }
+        client_handler: {}
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> crate::core::xconnection::Result<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
+            }
+        }
+        conn: {}
     }
 
-    fn _take_focus(id: Xid) -> RecordedCall {
-        let conn = RecordingXConn::init();
-        let evt = ClientMessageKind::TakeFocus(id).as_message(&conn).unwrap();
-        ("send_client_event".into(), strings!(evt))
+    type XResult<T> = crate::core::xconnection::Result<T>;
+
+    struct PropReadCountingXConn {
+        reads: Cell<Vec<(Xid, String)>>,
     }
 
-    fn _id(a: Atom) -> RecordedCall {
-        ("atom_id".into(), strings!(a.as_ref()))
+    __impl_stub_xcon! {
+        for PropReadCountingXConn;
+
+        atom_queries: {}
+        client_properties: {
+            fn mock_get_prop(&self, id: Xid, name: &str) -> XResult<Prop> {
+                let mut reads = self.reads.take();
+                reads.push((id, name.to_string()));
+                self.reads.set(reads);
+                Ok(Prop::UTF8String(vec!["mock".into()]))
+            }
+        }
+        client_handler: {}
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> XResult<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
+            }
+        }
+        conn: {}
     }
 
-    fn _border(id: Xid, focused: bool) -> RecordedCall {
-        let color = if focused {
-            Color::try_from("#00ff00").unwrap()
-        } else {
-            Color::try_from("#ff0000").unwrap()
+    #[test]
+    fn client_property_reads_use_the_cache_until_a_property_notify() {
+        let conn = PropReadCountingXConn {
+            reads: Cell::new(vec![]),
         };
-        ("set_client_border_color".into(), strings!(id, color))
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.handle_map_request(10).unwrap();
+
+        let class = Atom::WmClass.as_ref();
+        let read_class = |wm: &WindowManager<PropReadCountingXConn>| {
+            let reads = wm.conn().reads.take();
+            reads
+                .iter()
+                .filter(|(id, name)| *id == 10 && name == class)
+                .count()
+        };
+
+        // WM_CLASS is read once when managing the client and is then served from the cache
+        assert_eq!(read_class(&wm), 1);
+        wm.client_prop(10, class).unwrap();
+        assert_eq!(read_class(&wm), 0);
+
+        let event = XEvent::PropertyNotify(PropertyEvent {
+            id: 10,
+            atom: class.to_string(),
+            is_root: false,
+        });
+        let actions = process_next_event(event, &wm.state, &wm.conn);
+        wm.handle_event_actions(actions).unwrap();
+
+        wm.client_prop(10, class).unwrap();
+        assert_eq!(read_class(&wm), 1);
     }
 
-    fn _active(id: Xid) -> RecordedCall {
-        let args = strings!(42, "_NET_ACTIVE_WINDOW", Prop::Window(vec![id]));
-        ("change_prop".into(), args)
+    // A connection where the window for `closed` has already been destroyed
+    struct ClosedWindowXConn {
+        closed: Xid,
+        positioned: Cell<Vec<Xid>>,
+        events: Cell<Vec<XResult<XEvent>>>,
     }
 
-    fn _remove_active() -> RecordedCall {
-        ("delete_prop".into(), strings!(42, "_NET_ACTIVE_WINDOW"))
+    impl ClosedWindowXConn {
+        fn new(closed: Xid, events: Vec<XResult<XEvent>>) -> Self {
+            Self {
+                closed,
+                positioned: Cell::new(vec![]),
+                events: Cell::new(events),
+            }
+        }
     }
 
-    test_cases! {
-        update_focus;
-        args: (
-            target: Xid,
-            accepts_focus: bool,
-            current: Option<Xid>,
-            n_clients: usize,
-            follow_focus: bool,
-            expected_focus: Option<Xid>,
-            expected_calls: Vec<RecordedCall>
-        );
+    __impl_stub_xcon! {
+        for ClosedWindowXConn;
 
-        // We should still run focusing logic when the requested target is our current focus
-        case: client_is_current_focus => (
-            10, true, Some(10), 3, false,
-            Some(10), vec![_focus(10), _active(10), _border(10, true)]
-        );
+        atom_queries: {}
+        client_properties: {}
+        client_handler: {}
+        client_config: {
+            fn mock_position_client(&self, id: Xid, _: Region, _: u32, _: bool) -> XResult<()> {
+                if id == self.closed {
+                    return Err(crate::core::xconnection::XError::WindowGone(id));
+                }
+                let mut positioned = self.positioned.take();
+                positioned.push(id);
+                self.positioned.set(positioned);
+                Ok(())
+            }
+        }
+        event_handler: {
+            fn mock_wait_for_event(&self) -> XResult<XEvent> {
+                let mut remaining = self.events.take();
+                if remaining.is_empty() {
+                    return Ok(XEvent::KeyPress(EXIT_CODE));
+                }
+                let next = remaining.remove(0);
+                self.events.set(remaining);
+                next
+            }
+        }
+        state: {
+            fn mock_current_screens(&self) -> XResult<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
+            }
+        }
+        conn: {}
+    }
 
-        // We should remove the focused border from the current client first
-        case: client_is_not_current_focus => (
-            20, true, Some(10), 3, false,
-            Some(20), vec![_border(10, false), _focus(20), _active(20), _border(20, true)]
-        );
+    #[test]
+    fn layout_continues_past_closed_windows() {
+        let conn = ClosedWindowXConn::new(20, vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 3, 0);
+        wm.conn().positioned.take();
 
-        // Focus should default to the focused client on the active workspace if the given client
-        // is not in the client_map
-        case: client_is_unknown_workspace_populated => (
-            999, true, Some(10), 3, false,
-            Some(30), vec![_border(10, false), _focus(30), _active(30), _border(30, true)]
-        );
+        assert!(wm.layout_screen(0).is_ok());
+        assert_eq!(wm.conn().positioned.take(), vec![30, 10]);
+    }
 
-        // If the client is unknown and the workspace is empty, focus should revert to root
-        case: client_is_unknown_workspace_empty => (
-            999, true, None, 0, false,
-            None, vec![_focus(42), _remove_active()]
+    #[test]
+    fn event_loop_exits_when_the_connection_is_closed() {
+        let errors = Rc::new(Cell::new(0));
+        let handled = Rc::clone(&errors);
+        // MockXConn reports a closed connection once its events are exhausted
+        let conn = MockXConn::new(test_screens(), vec![XEvent::MapRequest(10, false)], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let handler: ErrorHandler = Box::new(move |_| handled.set(handled.get() + 1));
+        let mut wm = WindowManager::new(conf, conn, vec![], handler);
+        wm.init().unwrap();
+
+        let res = wm.grab_keys_and_run(HashMap::new(), HashMap::new());
+
+        assert!(matches!(
+            res,
+            Err(PenroseError::X(
+                crate::core::xconnection::XError::ConnectionClosed
+            ))
+        ));
+        assert!(!wm.running);
+        assert_eq!(errors.get(), 0);
+        assert_eq!(wm.clients.all_known_ids(), vec![10]);
+    }
+
+    struct NoopHook;
+    impl<X: XConn> crate::core::hooks::Hook<X> for NoopHook {}
+
+    #[test]
+    fn metrics_are_updated_as_events_are_processed() {
+        let events = vec![XEvent::MapRequest(10, false), XEvent::MapRequest(20, false)];
+        let conn = MockXConn::new(test_screens(), events, vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let hooks: Hooks<MockXConn> = vec![Box::new(NoopHook), Box::new(NoopHook)];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+        let before = wm.metrics();
+
+        // exits once the mock events are exhausted
+        assert!(wm
+            .grab_keys_and_run(HashMap::new(), HashMap::new())
+            .is_err());
+        let after = wm.metrics();
+
+        assert_eq!(before.events_processed, 0);
+        assert_eq!(after.events_processed, 2);
+        assert!(after.layouts_applied >= before.layouts_applied + 2);
+        assert!(after.total_arrange_time >= before.total_arrange_time);
+        assert!(after.average_arrange_time().is_some());
+        // every hook point is run for both hooks
+        assert!(after.hook_invocations > before.hook_invocations);
+        assert_eq!(after.hook_invocations % 2, 0);
+    }
+
+    #[test]
+    fn describe_reflects_the_current_state() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        wm.focus_workspace(&Selector::Index(1)).unwrap();
+        add_n_clients(&mut wm, 1, 2);
+        wm.toggle_client_fullscreen(&Selector::Focused).unwrap();
+        wm.toggle_layout_lock().unwrap();
+
+        let desc = wm.describe();
+        let ids = |wix: usize| -> Vec<Xid> {
+            desc.workspaces[wix].clients.iter().map(|c| c.id).collect()
+        };
+
+        // workspace 1 is swapped onto the focused screen
+        assert_eq!(desc.focused_screen, 0);
+        assert_eq!(desc.focused_client, Some(30));
+        assert_eq!(
+            desc.screens.iter().map(|s| s.workspace).collect::<Vec<_>>(),
+            vec![1, 0]
         );
+        assert_eq!(desc.screens[1].region, Region::new(1366, 0, 1366, 768));
+        assert_eq!(desc.workspaces.len(), wm.workspaces.len());
+
+        assert_eq!(ids(0), vec![20, 10]);
+        assert_eq!(desc.workspaces[0].focused_client, Some(20));
+        assert!(!desc.workspaces[0].layout_locked);
+        assert!(desc.workspaces[0].clients.iter().all(|c| !c.fullscreen));
+
+        assert_eq!(ids(1), vec![30]);
+        assert!(desc.workspaces[1].layout_locked);
+        assert!(desc.workspaces[1].clients[0].fullscreen);
+        assert!(desc.workspaces[1].clients[0].mapped);
+        assert!(ids(2).is_empty());
+    }
 
-        // If the client doesn't accept focus then we should still mark it as focused in the
-        // internal state, but a TakeFocus client message should be sent instead of forcing
-        // focus.
-        case: client_does_not_accept_focus_different => (
-            20, false, Some(10), 3, false,
-            Some(20), vec![
-                _border(10, false), _id(Atom::WmTakeFocus), _take_focus(20)
+    #[test]
+    fn event_log_keeps_the_most_recent_events() {
+        let events = vec![
+            XEvent::MapRequest(10, false),
+            XEvent::MapRequest(20, false),
+            XEvent::MapRequest(30, false),
+        ];
+        let conn = MockXConn::new(test_screens(), events, vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            event_log_size: 2,
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        // exits once the mock events are exhausted
+        assert!(wm
+            .grab_keys_and_run(HashMap::new(), HashMap::new())
+            .is_err());
+
+        let logged: Vec<(XEvent, Vec<String>)> = wm
+            .event_log()
+            .iter()
+            .map(|e| (e.event.clone(), e.actions.clone()))
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                (
+                    XEvent::MapRequest(20, false),
+                    strings!(EventAction::MapWindow(20))
+                ),
+                (
+                    XEvent::MapRequest(30, false),
+                    strings!(EventAction::MapWindow(30))
+                ),
             ]
         );
+    }
 
-        // If the client doesn't accept focus, and it is the current focus then we should just
-        // set the border and send the TakeFocus event
-        case: client_does_not_accept_focus_same => (
-            20, false, Some(20), 3, false,
-            Some(20), vec![_id(Atom::WmTakeFocus), _take_focus(20)]
+    #[test]
+    fn transient_errors_are_not_passed_to_the_error_handler() {
+        let errors = Rc::new(RefCell::new(vec![]));
+        let handler_errors = Rc::clone(&errors);
+        let error_handler: ErrorHandler =
+            Box::new(move |e: PenroseError| handler_errors.borrow_mut().push(e.to_string()));
+
+        let events = vec![
+            Err(crate::core::xconnection::XError::WindowGone(42)),
+            Err(crate::core::xconnection::XError::Raw("boom".into())),
+        ];
+        let conn = ClosedWindowXConn::new(42, events);
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], error_handler);
+        wm.init().unwrap();
+
+        let mut bindings: KeyBindings<ClosedWindowXConn> = HashMap::new();
+        bindings.insert(
+            EXIT_CODE,
+            Box::new(|wm: &mut WindowManager<ClosedWindowXConn>| wm.exit()),
         );
+        wm.grab_keys_and_run(bindings, HashMap::new()).unwrap();
 
-        // TODO: add test cases for follow_focus layout triggering
+        assert_eq!(*errors.borrow(), vec!["Unhandled error: boom".to_string()]);
+    }
 
-        body: {
-            let conn = RecordingXConn::init();
-            let conf = Config {
-                layouts: focus_test_layouts(follow_focus),
-                focused_border: Color::try_from("#00ff00").unwrap(),
-                unfocused_border: Color::try_from("#ff0000").unwrap(),
-                ..Default::default()
-            };
-            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
-            wm.init().unwrap();
-            add_n_clients(&mut wm, n_clients, 0);
-            if let Some(id) = current {
-                wm.state.clients.set_focused(id, &wm.conn);
+    // Tracks the window under the pointer, which is moved by calls to warp_cursor
+    struct PointerXConn {
+        under_pointer: Cell<Option<Xid>>,
+        warps: Cell<Vec<Xid>>,
+    }
+
+    __impl_stub_xcon! {
+        for PointerXConn;
+
+        atom_queries: {}
+        client_properties: {}
+        client_handler: {}
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> XResult<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
             }
-            wm.clients.modify(target, |c| c.accepts_focus = accepts_focus);
-            wm.conn().clear();
 
-            wm.update_focus(target).unwrap();
+            fn mock_query_pointer(&self) -> XResult<(Point, Option<Xid>)> {
+                Ok((Point::default(), self.under_pointer.get()))
+            }
 
-            assert_eq!(wm.clients.focused_client_id(), expected_focus);
-            assert_eq!(wm.conn().calls(), expected_calls);
+            fn mock_warp_cursor(&self, win_id: Option<Xid>, _: &Screen) -> XResult<()> {
+                if let Some(id) = win_id {
+                    self.under_pointer.set(Some(id));
+                    let mut warps = self.warps.take();
+                    warps.push(id);
+                    self.warps.set(warps);
+                }
+                Ok(())
+            }
         }
+        conn: {}
     }
 
-    macro_rules! changing_screen_focus_test {
-        ($method:ident, $start_screen:expr, $test:expr, $expected:expr) => {
-            #[test]
-            fn $method() {
-                let conn = RecordingXConn::init();
-                let conf = Default::default();
-                let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
-                wm.init().unwrap();
+    fn pointer_test_wm(
+        focus_follows_mouse: bool,
+        mouse_follows_focus: bool,
+    ) -> WindowManager<PointerXConn> {
+        let conn = PointerXConn {
+            under_pointer: Cell::new(None),
+            warps: Cell::new(vec![]),
+        };
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            focus_follows_mouse,
+            mouse_follows_focus,
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 3, 0);
+        wm.conn().under_pointer.set(None);
+        wm.conn().warps.take();
 
-                wm.focus_screen(&Selector::Index($start_screen));
+        wm
+    }
 
-                let expected = $expected
-                    .into_iter()
-                    .map(|id| {
-                        (
-                            "change_prop".into(),
-                            strings!(42, "_NET_CURRENT_DESKTOP", Prop::Cardinal(id)),
-                        )
-                    })
-                    .collect::<Vec<RecordedCall>>();
-                wm.conn().clear();
+    fn enter_window(id: Xid) -> XEvent {
+        XEvent::Enter(PointerChange {
+            id,
+            abs: Point::new(1, 1),
+            relative: Point::new(1, 1),
+        })
+    }
 
-                // cycle the screen
-                $test(&mut wm);
+    test_cases! {
+        mouse_follows_focus;
+        args: (enabled: bool, expected: Vec<Xid>);
 
-                // check that the recorded calls are the expected values
-                assert_eq!(wm.conn().calls(), expected);
-            }
-        };
-    }
+        case: disabled => (false, vec![]);
+        case: enabled => (true, vec![20]);
 
-    changing_screen_focus_test!(
-        cycle_screen_backwards_on_first_screen,
-        0,
-        |wm: &mut WindowManager<_>| { wm.cycle_screen(Backward).unwrap() },
-        vec![]
-    );
-    changing_screen_focus_test!(
-        cycle_screen_backwards_on_second_screen,
-        1,
-        |wm: &mut WindowManager<_>| { wm.cycle_screen(Backward).unwrap() },
-        vec![0]
-    );
-    changing_screen_focus_test!(
-        cycle_screen_forwards_on_first_screen,
-        0,
-        |wm: &mut WindowManager<_>| { wm.cycle_screen(Forward).unwrap() },
-        vec![1]
-    );
-    changing_screen_focus_test!(
-        cycle_screen_forwards_on_last_screen,
-        1, // the mock setup apparently only has two screens
-        |wm: &mut WindowManager<_>| { wm.cycle_screen(Forward).unwrap() },
-        vec![]
-    );
+        body: {
+            let mut wm = pointer_test_wm(true, enabled);
 
-    changing_screen_focus_test!(
-        focus_screen_same,
-        0, // the mock setup apparently only has two screens
-        |wm: &mut WindowManager<_>| {
-            wm.focus_screen(&Selector::Index(0));
-        },
-        vec![]
-    );
-    changing_screen_focus_test!(
-        focus_screen_different,
-        0, // the mock setup apparently only has two screens
-        |wm: &mut WindowManager<_>| {
-            wm.focus_screen(&Selector::Index(1));
-        },
-        vec![1]
-    );
+            wm.update_focus(20).unwrap();
+
+            assert_eq!(wm.focused_client_id(), Some(20));
+            assert_eq!(wm.conn().warps.take(), expected);
+        }
+    }
 
     #[test]
-    fn cycle_screen_updates_active() {
-        let mut wm = test_windowmanager(2, vec![]);
+    fn mouse_follows_focus_does_not_warp_for_pointer_driven_focus() {
+        let mut wm = pointer_test_wm(true, true);
+
+        // The pointer is moved into 20 by the user, generating an EnterNotify
+        wm.conn().under_pointer.set(Some(20));
+        for _ in 0..3 {
+            let actions = process_next_event(enter_window(20), &wm.state, &wm.conn);
+            wm.handle_event_actions(actions).unwrap();
+        }
 
-        assert_eq!(wm.active_screen_index(), 0);
-        wm.cycle_screen(Forward).unwrap();
-        assert_eq!(wm.active_screen_index(), 1);
-        wm.cycle_screen(Forward).unwrap();
-        assert_eq!(wm.active_screen_index(), 1);
+        assert_eq!(wm.focused_client_id(), Some(20));
+        assert_eq!(wm.conn().warps.take(), vec![]);
     }
 
-    #[test]
-    fn cycle_workspace_updates_focused() {
-        let mut wm = test_windowmanager(1, vec![]);
+    test_cases! {
+        focus_follows_mouse;
+        args: (enabled: bool, expected: Xid);
 
-        assert_eq!(wm.focused_workspaces(), vec![0]);
-        wm.cycle_workspace(Forward).unwrap();
-        assert_eq!(wm.focused_workspaces(), vec![1]);
-        wm.cycle_workspace(Backward).unwrap();
-        wm.cycle_workspace(Backward).unwrap();
-        assert_eq!(wm.focused_workspaces(), vec![8]);
+        case: enabled => (true, 20);
+        case: disabled => (false, 30);
+
+        body: {
+            let mut wm = pointer_test_wm(enabled, false);
+
+            let actions = process_next_event(enter_window(20), &wm.state, &wm.conn);
+            wm.handle_event_actions(actions).unwrap();
+
+            assert_eq!(wm.focused_client_id(), Some(expected));
+        }
+    }
+
+    fn set_window_type(wm: &mut WindowManager<PointerXConn>, id: Xid, window_type: Atom) {
+        wm.clients
+            .modify(id, |c| c.wm_type = vec![window_type.as_ref().to_string()]);
     }
 
-    #[test]
-    fn drag_workspace_move_focused_workspaces_between_screens() {
-        let mut wm = test_windowmanager(2, vec![]);
+    test_cases! {
+        window_type_focus_policy;
+        args: (window_type: Atom, expected: Xid);
+
+        case: dialog_requires_click => (Atom::NetWindowTypeDialog, 30);
+        case: normal_is_sloppy => (Atom::NetWindowTypeNormal, 20);
+
+        body: {
+            let mut wm = pointer_test_wm(true, false);
+            wm.config.window_type_focus_policies =
+                vec![(Atom::NetWindowTypeDialog.as_ref().to_string(), FocusPolicy::Click)]
+                    .into_iter()
+                    .collect();
+            set_window_type(&mut wm, 20, window_type);
+
+            let actions = process_next_event(enter_window(20), &wm.state, &wm.conn);
+            wm.handle_event_actions(actions).unwrap();
+
+            assert_eq!(wm.focused_client_id(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn click_to_focus_clients_keep_focus_when_the_pointer_leaves() {
+        let mut wm = pointer_test_wm(true, false);
+        wm.config.window_type_focus_policies = vec![(
+            Atom::NetWindowTypeDialog.as_ref().to_string(),
+            FocusPolicy::Click,
+        )]
+        .into_iter()
+        .collect();
+        set_window_type(&mut wm, 30, Atom::NetWindowTypeDialog);
+
+        let leave = XEvent::Leave(PointerChange {
+            id: 30,
+            abs: Point::new(1, 1),
+            relative: Point::new(1, 1),
+        });
+        let actions = process_next_event(leave, &wm.state, &wm.conn);
+
+        assert!(!actions.contains(&EventAction::ClientFocusLost(30)));
+    }
+
+    test_cases! {
+        requested_net_wm_desktop;
+        args: (requested: Option<u32>, expected: usize);
+
+        case: no_request => (None, 0);
+        case: valid_request => (Some(2), 2);
+        case: out_of_range_request => (Some(42), 0);
+        case: all_desktops_request => (Some(0xFFFFFFFF), 0);
+
+        body: {
+            let conn = DesktopXConn {
+                requested,
+                desktops: Cell::new(vec![]),
+            };
+            let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+
+            wm.handle_map_request(10).unwrap();
+
+            assert_eq!(wm.clients.get(10).unwrap().workspace(), expected);
+            assert_eq!(wm.workspaces[expected].client_ids(), vec![10]);
+            assert_eq!(wm.conn().desktops.take(), vec![(10, Prop::Cardinal(expected as u32))]);
+        }
+    }
+
+    // A connection with existing clients that may have a _NET_WM_DESKTOP property set
+    struct ExistingClientsXConn {
+        desktops: Vec<(Xid, u32)>,
+    }
+
+    __impl_stub_xcon! {
+        for ExistingClientsXConn;
+
+        atom_queries: {}
+        client_properties: {
+            fn mock_get_prop(&self, id: Xid, name: &str) -> XResult<Prop> {
+                match self.desktops.iter().find(|&&(i, _)| i == id) {
+                    Some(&(_, wix)) if name == Atom::NetWmDesktop.as_ref() => Ok(Prop::Cardinal(wix)),
+                    _ => Err(crate::core::xconnection::XError::MissingProperty(name.into(), id)),
+                }
+            }
+        }
+        client_handler: {}
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> XResult<Vec<Screen>> {
+                Ok(vec![Screen::new(Region::new(0, 0, 1366, 768), 0)])
+            }
+
+            fn mock_active_clients(&self) -> XResult<Vec<Xid>> {
+                Ok(vec![10, 20, 30, 40])
+            }
+        }
+        conn: {}
+    }
+
+    test_cases! {
+        existing_client_placement;
+        args: (placement: ExistingClientPlacement, expected: Vec<usize>);
+
+        case: active_workspace => (ExistingClientPlacement::ActiveWorkspace, vec![2, 0, 0, 0]);
+        case: round_robin => (ExistingClientPlacement::RoundRobin, vec![2, 0, 1, 2]);
+
+        body: {
+            // 10 requests a valid workspace, 30 requests all workspaces and the rest have no hint
+            let conn = ExistingClientsXConn {
+                desktops: vec![(10, 2), (30, 0xFFFFFFFF)],
+            };
+            let conf = Config {
+                workspaces: vec!["1".into(), "2".into(), "3".into()],
+                layouts: focus_test_layouts(false),
+                existing_client_placement: placement,
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+
+            wm.try_manage_existing_windows().unwrap();
+
+            let placed: Vec<usize> = [10, 20, 30, 40]
+                .iter()
+                .map(|&id| wm.clients.get(id).unwrap().workspace())
+                .collect();
+            assert_eq!(placed, expected);
+            for (&id, &wix) in [10, 20, 30, 40].iter().zip(expected.iter()) {
+                assert!(wm.workspaces[wix].client_ids().contains(&id));
+            }
+        }
+    }
+
+    #[test]
+    fn existing_clients_can_be_returned_home() {
+        let conn = ExistingClientsXConn {
+            desktops: vec![(10, 2)],
+        };
+        let conf = Config {
+            workspaces: vec!["1".into(), "2".into(), "3".into()],
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.try_manage_existing_windows().unwrap();
+        assert_eq!(wm.clients.get(10).unwrap().origin_workspace(), Some(2));
+
+        wm.move_client_to_workspace(10, 1, false).unwrap();
+        wm.return_client_home(10).unwrap();
+
+        assert_eq!(wm.clients.get(10).unwrap().workspace(), 2);
+        assert!(wm.workspaces[2].client_ids().contains(&10));
+    }
+
+    #[test]
+    fn net_wm_desktop_is_updated_when_moving_clients() {
+        let conn = DesktopXConn {
+            requested: None,
+            desktops: Cell::new(vec![]),
+        };
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.handle_map_request(10).unwrap();
+        wm.conn().desktops.take();
+
+        wm.client_to_workspace(&Selector::Index(3)).unwrap();
+
+        assert_eq!(wm.conn().desktops.take(), vec![(10, Prop::Cardinal(3))]);
+    }
+
+    test_cases! {
+        focus_steal_grace_period;
+        args: (grace_ms: u64, since_input: Option<u64>, steals_focus: bool);
+
+        case: disabled => (0, Some(0), true);
+        case: no_input => (5000, None, true);
+        case: within_grace_period => (5000, Some(0), false);
+        case: outside_grace_period => (5000, Some(10), true);
+
+        body: {
+            let conn = MockXConn::new(test_screens(), vec![], vec![]);
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                focus_steal_grace_ms: grace_ms,
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            wm.handle_map_request(10).unwrap();
+            wm.last_user_input = since_input
+                .and_then(|secs| Instant::now().checked_sub(Duration::from_secs(secs)));
+
+            wm.handle_map_request(20).unwrap();
+
+            let expected = if steals_focus { 20 } else { 10 };
+            assert_eq!(wm.focused_client_id(), Some(expected));
+            assert_eq!(wm.workspaces[0].focused_client(), Some(expected));
+            assert_eq!(wm.clients.get(20).unwrap().is_urgent(), !steals_focus);
+        }
+    }
+
+    fn focus_steal_test_wm() -> WindowManager<MockXConn> {
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            focus_steal_grace_ms: 5000,
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.handle_map_request(10).unwrap();
+
+        wm
+    }
+
+    test_cases! {
+        user_time_changes_start_focus_steal_grace_period;
+        args: (id: Xid, atom: &str, steals_focus: bool);
+
+        case: focused_client_user_time => (10, "_NET_WM_USER_TIME", false);
+        case: other_property => (10, "WM_NAME", true);
+        case: unfocused_client_user_time => (20, "_NET_WM_USER_TIME", true);
+
+        body: {
+            let mut wm = focus_steal_test_wm();
+            wm.handle_map_request(20).unwrap();
+            wm.focus_client(&Selector::WinId(10)).unwrap();
+
+            wm.handle_prop_change(id, atom.to_string(), false).unwrap();
+            wm.handle_map_request(30).unwrap();
+
+            let expected = if steals_focus { 30 } else { 10 };
+            assert_eq!(wm.focused_client_id(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn clients_spawned_by_a_key_binding_take_focus() {
+        let mut wm = focus_steal_test_wm();
+        wm.handle_prop_change(10, "_NET_WM_USER_TIME".into(), false)
+            .unwrap();
+
+        let code = KeyCode { mask: 0, code: 42 };
+        let mut bindings: KeyBindings<MockXConn> = HashMap::new();
+        bindings.insert(code, Box::new(|_: &mut WindowManager<MockXConn>| Ok(())));
+        wm.run_key_binding(code, &mut bindings);
+        wm.handle_map_request(20).unwrap();
+
+        assert_eq!(wm.focused_client_id(), Some(20));
+        assert!(!wm.clients.get(20).unwrap().is_urgent());
+    }
+
+    #[test]
+    fn clients_denied_focus_demand_attention_until_focused() {
+        let conn = StackingXConn {
+            wm_state: RefCell::new(vec![]),
+        };
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            focus_steal_grace_ms: 5000,
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.handle_map_request(10).unwrap();
+        wm.handle_prop_change(10, "_NET_WM_USER_TIME".into(), false)
+            .unwrap();
+
+        let attention = Atom::NetWmStateDemandsAttention.as_ref().to_string();
+        wm.handle_map_request(20).unwrap();
+        assert!(wm.clients.get(20).unwrap().is_urgent());
+        assert!(wm.conn().wm_state.borrow().contains(&attention));
+
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+        assert!(!wm.clients.get(20).unwrap().is_urgent());
+        assert!(!wm.conn().wm_state.borrow().contains(&attention));
+    }
+
+    #[test]
+    fn override_redirect_windows_are_not_managed() {
+        let wm = wm_with_mock_conn(vec![], vec![]);
+        let actions = process_next_event(XEvent::MapRequest(10, true), &wm.state, &wm.conn);
+
+        assert!(actions.is_empty());
+    }
+
+    impl WindowManager<ScreenChangingXConn> {
+        fn set_num_screens(&mut self, n: usize) {
+            self.conn_mut().set_num_screens(n);
+        }
+    }
+
+    // TODO: rewrite and move out to screens.rs
+    #[test]
+    fn updating_screens_retains_focused_workspaces() {
+        let conn = ScreenChangingXConn {
+            num_screens: Cell::new(1),
+        };
+        let conf = Config::default();
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        // detect_screens is called on init so should have one screen
+        assert_eq!(wm.screens.n_screens(), 1);
+        assert_eq!(wm.screens.focused().wix, 0);
+
+        // Focus workspace 1 the redetect screens: should have 1 and 0
+        wm.focus_workspace(&Selector::Index(1)).unwrap();
+        assert_eq!(wm.screens.focused().wix, 1);
+        wm.set_num_screens(2);
+        wm.detect_screens().unwrap();
+        assert_eq!(wm.screens.n_screens(), 2);
+        assert_eq!(wm.screens.get(0).unwrap().wix, 1);
+        assert_eq!(wm.screens.get(1).unwrap().wix, 0);
+
+        // Adding another screen should now have WS 2 as 1 is taken
+        wm.set_num_screens(3);
+        wm.detect_screens().unwrap();
+        assert_eq!(wm.screens.n_screens(), 3);
+        assert_eq!(wm.screens.get(0).unwrap().wix, 1);
+        assert_eq!(wm.screens.get(1).unwrap().wix, 0);
+        assert_eq!(wm.screens.get(2).unwrap().wix, 2);
+
+        // Focus WS 3 on screen 1, drop down to 1 screen: it should still have WS 3
+        wm.focus_workspace(&Selector::Index(3)).unwrap();
+        wm.set_num_screens(1);
+        wm.detect_screens().unwrap();
+        assert_eq!(wm.screens.n_screens(), 1);
+        assert_eq!(wm.screens.get(0).unwrap().wix, 3);
+    }
+
+    #[test]
+    fn detect_screens_action_picks_up_new_screens() {
+        let conn = ScreenChangingXConn {
+            num_screens: Cell::new(1),
+        };
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        assert_eq!(wm.screens.n_screens(), 1);
+
+        wm.set_num_screens(2);
+        let mut action = crate::contrib::actions::detect_screens();
+        action(&mut wm).unwrap();
+
+        assert_eq!(wm.screens.n_screens(), 2);
+        assert_eq!(wm.screens.get(1).unwrap().wix, 1);
+    }
+
+    test_cases! {
+        layout_border_width;
+        args: (border_width: Option<u32>, expected: u32);
+
+        case: global_border_px_by_default => (None, 2);
+        case: layout_override_wins => (Some(0), 0);
+
+        body: {
+            let conf = Config {
+                layouts: vec![Layout::new(
+                    "t",
+                    LayoutConf { border_width, ..Default::default() },
+                    mock_layout,
+                    1,
+                    0.6,
+                )],
+                border_px: 2,
+                ..Default::default()
+            };
+            let mut wm =
+                WindowManager::new(conf, RecordingXConn::init(), vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 2, 0);
+            wm.conn().clear();
+
+            wm.layout_screen(0).unwrap();
+
+            let borders: Vec<String> = wm
+                .conn()
+                .calls()
+                .into_iter()
+                .filter(|(m, _)| m == "position_client")
+                .map(|(_, args)| args[2].clone())
+                .collect();
+
+            assert_eq!(borders, vec![expected.to_string(); 2]);
+
+            // Floating clients positioned directly use the same border width
+            wm.conn().clear();
+            wm.position_client(10, Region::new(0, 0, 100, 100), true).unwrap();
+            let calls = wm.conn().calls();
+            let (_, args) = calls.iter().find(|(m, _)| m == "position_client").unwrap();
+            assert_eq!(args[2], expected.to_string());
+        }
+    }
+
+    // Check that workspace layout is triggered correctly from public methods
+
+    macro_rules! layout_trigger_test {
+        { $method:ident; $should_layout:expr; $($arg:expr),* } => {
+            paste::paste! {
+                #[test]
+                fn [<layout_trigger_test _ $method>]() {
+                    let conn = RecordingXConn::init();
+                    let conf = Config {
+                        layouts: focus_test_layouts(false),
+                        ..Default::default()
+                    };
+                    let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+                    wm.init().unwrap();
+                    add_n_clients(&mut wm, 3, 0);
+                    wm.focus_workspace(&Selector::Index(1)).unwrap();
+                    add_n_clients(&mut wm, 3, 30);
+                    wm.focus_workspace(&Selector::Index(0)).unwrap();
+                    wm.conn.clear();
+                    wm.$method($($arg),*).unwrap();
+
+                    // Defining "we applied layout" as "position_client" was called
+                    // at least once. Tests around layout application itself being
+                    // correct are handled separately
+                    let did_layout = wm
+                        .conn
+                        .calls()
+                        .iter()
+                        .any(|c| c.0 == *"position_client");
+
+                    if $should_layout {
+                        assert!(did_layout);
+                    } else {
+                        assert!(!did_layout);
+                    }
+                }
+            }
+        }
+    }
+
+    layout_trigger_test!(cycle_workspace; true; Forward);
+    layout_trigger_test!(drag_workspace; true; Forward);
+    layout_trigger_test!(cycle_occupied_workspace; true; Forward);
+    layout_trigger_test!(cycle_client; false; Forward);
+    layout_trigger_test!(focus_master; false;);
+    layout_trigger_test!(focus_last_client; false;);
+    layout_trigger_test!(focus_client; false; &Selector::Any);
+    layout_trigger_test!(rotate_clients; true; Forward);
+    layout_trigger_test!(drag_client; true; Forward);
+    layout_trigger_test!(cycle_layout; true; Forward);
+    layout_trigger_test!(set_workspace_layout; true; 0, "t");
+    layout_trigger_test!(update_max_main; true; Change::More);
+    layout_trigger_test!(update_main_ratio; true; Change::More);
+    layout_trigger_test!(resize_focused; true; Change::More, 10);
+    layout_trigger_test!(reset_client_sizes; true;);
+    layout_trigger_test!(exit; false;);
+    layout_trigger_test!(set_root_window_name; false; "test");
+    layout_trigger_test!(set_client_insert_point; false; InsertPoint::First);
+    layout_trigger_test!(focus_workspace; true; &Selector::Index(1));
+    layout_trigger_test!(focus_workspace_n; true; 1);
+    layout_trigger_test!(toggle_workspace; true;);
+    layout_trigger_test!(client_to_workspace; true; &Selector::Index(1));
+    layout_trigger_test!(client_to_screen; true; &Selector::Index(1));
+    layout_trigger_test!(merge_workspaces; true; 0, 1);
+    layout_trigger_test!(spread_clients; true; 1);
+    layout_trigger_test!(balance_visible_workspaces; true;);
+    layout_trigger_test!(toggle_client_fullscreen; true; &Selector::WinId(10));
+    layout_trigger_test!(kill_client; false;);
+    layout_trigger_test!(remove_workspace; true; &Selector::Index(0));
+    layout_trigger_test!(position_client; true; 10, Region::default(), true);
+    layout_trigger_test!(layout_screen; true; 0);
+    layout_trigger_test!(layout_workspace; true; None);
+    layout_trigger_test!(toggle_bar; true;);
+    layout_trigger_test!(tile_all_floating; true;);
+    layout_trigger_test!(set_client_managed; true; 10, false);
+
+    test_cases! {
+        empty_workspace_error;
+        args: (method: fn(&mut WindowManager<MockXConn>) -> Result<()>);
+
+        case: cycle_client => (|wm| wm.cycle_client(Direction::Forward));
+        case: focus_master => (|wm| wm.focus_master());
+        case: focus_last_client => (|wm| wm.focus_last_client());
+        case: rotate_clients => (|wm| wm.rotate_clients(Direction::Forward));
+        case: drag_client => (|wm| wm.drag_client(Direction::Forward));
+
+        body: {
+            let mut wm = wm_with_mock_conn(vec![], vec![]);
+            add_n_clients(&mut wm, 2, 0);
+            assert!(method(&mut wm).is_ok());
+
+            wm.focus_workspace(&Selector::Index(3)).unwrap();
+            match method(&mut wm) {
+                Err(PenroseError::EmptyWorkspace(3)) => (),
+                other => panic!("expected EmptyWorkspace(3), got {:?}", other),
+            }
+        }
+    }
+
+    test_cases! {
+        resize_increments;
+        args: (respect: bool, inc: (u32, u32), expected: Region);
+
+        // mock_layout gives (0, 18, 1366, 750) which is padded to (5, 23, 1352, 736)
+        case: ignored_when_not_configured => (false, (7, 10), Region::new(5, 23, 1352, 736));
+        case: snapped_and_centered => (true, (7, 10), Region::new(7, 27, 1348, 728));
+        case: unit_increments_are_noop => (true, (1, 1), Region::new(5, 23, 1352, 736));
+
+        body: {
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                respect_size_increments: respect,
+                ..Default::default()
+            };
+            let mut wm =
+                WindowManager::new(conf, RecordingXConn::init(), vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 1, 0);
+
+            // base size 4x8 so the final size must be 4 + n*inc.0 x 8 + m*inc.1 (e.g. 7 and 10)
+            let base = Region::new(0, 0, 4, 8);
+            let hints = WmNormalHints::new(Default::default(), Some(base), None, None, None)
+                .with_resize_increments(inc.0, inc.1);
+            wm.clients.modify(10, |c| c.wm_normal_hints = Some(hints.clone()));
+            wm.conn().clear();
+
+            wm.layout_screen(0).unwrap();
+
+            let calls = wm.conn().calls();
+            let (_, args) = calls.iter().find(|(m, _)| m == "position_client").unwrap();
+            assert_eq!(args[1], format!("{:?}", expected));
+            if respect {
+                assert_eq!((expected.w - 4) % inc.0, 0);
+                assert_eq!((expected.h - 8) % inc.1, 0);
+            }
+        }
+    }
+
+    test_cases! {
+        smart_gaps;
+        args: (smart_gaps: bool, n_clients: usize, expected: Region);
+
+        // mock_layout gives (0, 18, 1366, 750) for the first client: gaps are 5px, borders 2px
+        case: disabled_single_client => (false, 1, Region::new(5, 23, 1352, 736));
+        case: single_client => (true, 1, Region::new(0, 18, 1362, 746));
+        case: multiple_clients => (true, 2, Region::new(5, 23, 1352, 736));
+
+        body: {
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                smart_gaps,
+                ..Default::default()
+            };
+            let mut wm =
+                WindowManager::new(conf, RecordingXConn::init(), vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, n_clients, 0);
+            wm.conn().clear();
+
+            wm.layout_screen(0).unwrap();
+
+            let calls = wm.conn().calls();
+            let (_, args) = calls.iter().find(|(m, _)| m == "position_client").unwrap();
+            assert_eq!(args[1], format!("{:?}", expected));
+        }
+    }
+
+    test_cases! {
+        per_side_gaps;
+        args: (gaps: Option<Gaps>, expected: Region);
+
+        // mock_layout gives (0, 18, 1366, 750) for the first client: borders are 2px
+        case: gap_px_only => (None, Region::new(5, 23, 1352, 736));
+        case: uniform => (Some(Gaps::uniform(10)), Region::new(10, 28, 1342, 726));
+        case: asymmetric => (Some(Gaps::new(1, 2, 3, 4)), Region::new(3, 19, 1355, 743));
+
+        body: {
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                gaps,
+                ..Default::default()
+            };
+            let mut wm =
+                WindowManager::new(conf, RecordingXConn::init(), vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 1, 0);
+            wm.conn().clear();
+
+            wm.layout_screen(0).unwrap();
+
+            let calls = wm.conn().calls();
+            let (_, args) = calls.iter().find(|(m, _)| m == "position_client").unwrap();
+            assert_eq!(args[1], format!("{:?}", expected));
+        }
+    }
+
+    test_cases! {
+        layout_workspace;
+        args: (wix: Option<usize>, expected: Vec<Xid>);
+
+        case: single_workspace => (Some(1), vec![40, 50, 60]);
+        case: hidden_workspace => (Some(2), vec![]);
+        case: all_visible => (None, vec![10, 20, 30, 40, 50, 60]);
+
+        body: {
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                ..Default::default()
+            };
+            let mut wm =
+                WindowManager::new(conf, RecordingXConn::init(), vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 3, 0);
+            wm.focus_screen(&Selector::Index(1));
+            add_n_clients(&mut wm, 3, 3);
+            wm.conn().clear();
+
+            wm.layout_workspace(wix).unwrap();
+
+            let mut positioned: Vec<Xid> = wm
+                .conn()
+                .calls()
+                .into_iter()
+                .filter(|(m, _)| m == "position_client")
+                .map(|(_, args)| args[0].parse().unwrap())
+                .collect();
+            positioned.sort_unstable();
+
+            assert_eq!(positioned, expected);
+        }
+    }
+
+    #[test]
+    fn layout_workspace_out_of_bounds_is_an_error() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+
+        assert!(wm.layout_workspace(Some(42)).is_err());
+    }
+
+    /*
+     * Helpers for specifying expected events with RecordingXConn
+     */
+
+    fn _focus(id: Xid) -> RecordedCall {
+        ("focus_client".into(), strings!(id))
+    }
+
+    fn _take_focus(id: Xid) -> RecordedCall {
+        let conn = RecordingXConn::init();
+        let evt = ClientMessageKind::TakeFocus(id).as_message(&conn).unwrap();
+        ("send_client_event".into(), strings!(evt))
+    }
+
+    fn _id(a: Atom) -> RecordedCall {
+        ("atom_id".into(), strings!(a.as_ref()))
+    }
+
+    fn _border(id: Xid, focused: bool) -> RecordedCall {
+        let color = if focused {
+            Color::try_from("#00ff00").unwrap()
+        } else {
+            Color::try_from("#ff0000").unwrap()
+        };
+        ("set_client_border_color".into(), strings!(id, color))
+    }
+
+    fn _active(id: Xid) -> RecordedCall {
+        let args = strings!(42, "_NET_ACTIVE_WINDOW", Prop::Window(vec![id]));
+        ("change_prop".into(), args)
+    }
+
+    fn _remove_active() -> RecordedCall {
+        ("delete_prop".into(), strings!(42, "_NET_ACTIVE_WINDOW"))
+    }
+
+    test_cases! {
+        update_focus;
+        args: (
+            target: Xid,
+            accepts_focus: bool,
+            current: Option<Xid>,
+            n_clients: usize,
+            follow_focus: bool,
+            expected_focus: Option<Xid>,
+            expected_calls: Vec<RecordedCall>
+        );
+
+        // We should still run focusing logic when the requested target is our current focus
+        case: client_is_current_focus => (
+            10, true, Some(10), 3, false,
+            Some(10), vec![_focus(10), _active(10), _border(10, true)]
+        );
+
+        // We should remove the focused border from the current client first
+        case: client_is_not_current_focus => (
+            20, true, Some(10), 3, false,
+            Some(20), vec![_border(10, false), _focus(20), _active(20), _border(20, true)]
+        );
+
+        // Focus should default to the focused client on the active workspace if the given client
+        // is not in the client_map
+        case: client_is_unknown_workspace_populated => (
+            999, true, Some(10), 3, false,
+            Some(30), vec![_border(10, false), _focus(30), _active(30), _border(30, true)]
+        );
+
+        // If the client is unknown and the workspace is empty, focus should revert to root
+        case: client_is_unknown_workspace_empty => (
+            999, true, None, 0, false,
+            None, vec![_focus(42), _remove_active()]
+        );
+
+        // If the client doesn't accept focus then we should still mark it as focused in the
+        // internal state, but a TakeFocus client message should be sent instead of forcing
+        // focus.
+        case: client_does_not_accept_focus_different => (
+            20, false, Some(10), 3, false,
+            Some(20), vec![
+                _border(10, false), _id(Atom::WmTakeFocus), _take_focus(20)
+            ]
+        );
+
+        // If the client doesn't accept focus, and it is the current focus then we should just
+        // set the border and send the TakeFocus event
+        case: client_does_not_accept_focus_same => (
+            20, false, Some(20), 3, false,
+            Some(20), vec![_id(Atom::WmTakeFocus), _take_focus(20)]
+        );
+
+        // TODO: add test cases for follow_focus layout triggering
+
+        body: {
+            let conn = RecordingXConn::init();
+            let conf = Config {
+                layouts: focus_test_layouts(follow_focus),
+                focused_border: Color::try_from("#00ff00").unwrap(),
+                unfocused_border: Color::try_from("#ff0000").unwrap(),
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, n_clients, 0);
+            if let Some(id) = current {
+                wm.state.clients.set_focused(id, &wm.conn);
+            }
+            wm.clients.modify(target, |c| c.accepts_focus = accepts_focus);
+            wm.conn().clear();
+
+            wm.update_focus(target).unwrap();
+
+            assert_eq!(wm.clients.focused_client_id(), expected_focus);
+            assert_eq!(wm.conn().calls(), expected_calls);
+        }
+    }
+
+    test_cases! {
+        set_client_focusable;
+        args: (parsed: bool, focusable: bool);
+
+        case: override_enables_focus => (false, true);
+        case: override_disables_focus => (true, false);
+
+        body: {
+            let conn = RecordingXConn::init();
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                focused_border: Color::try_from("#00ff00").unwrap(),
+                unfocused_border: Color::try_from("#ff0000").unwrap(),
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 3, 0); // [30, 20, 10]
+            wm.clients.modify(20, |c| c.accepts_focus = parsed);
+
+            wm.set_client_focusable(20, focusable).unwrap();
+            wm.conn().clear();
+            wm.cycle_client(Forward).unwrap();
+
+            let calls = wm.conn().calls();
+            assert_eq!(wm.clients.focused_client_id(), Some(20));
+            assert_eq!(calls.contains(&_focus(20)), focusable);
+            assert_eq!(calls.contains(&_take_focus(20)), !focusable);
+        }
+    }
+
+    #[test]
+    fn set_client_focusable_unknown_client() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        assert!(wm.set_client_focusable(42, true).is_err());
+    }
+
+    macro_rules! changing_screen_focus_test {
+        ($method:ident, $start_screen:expr, $test:expr, $expected:expr) => {
+            #[test]
+            fn $method() {
+                let conn = RecordingXConn::init();
+                let conf = Default::default();
+                let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+                wm.init().unwrap();
+
+                wm.focus_screen(&Selector::Index($start_screen));
+
+                let expected = $expected
+                    .into_iter()
+                    .map(|id| {
+                        (
+                            "change_prop".into(),
+                            strings!(42, "_NET_CURRENT_DESKTOP", Prop::Cardinal(id)),
+                        )
+                    })
+                    .collect::<Vec<RecordedCall>>();
+                wm.conn().clear();
+
+                // cycle the screen
+                $test(&mut wm);
+
+                // check that the recorded calls are the expected values
+                assert_eq!(wm.conn().calls(), expected);
+            }
+        };
+    }
+
+    changing_screen_focus_test!(
+        cycle_screen_backwards_on_first_screen,
+        0,
+        |wm: &mut WindowManager<_>| { wm.cycle_screen(Backward).unwrap() },
+        vec![]
+    );
+    changing_screen_focus_test!(
+        cycle_screen_backwards_on_second_screen,
+        1,
+        |wm: &mut WindowManager<_>| { wm.cycle_screen(Backward).unwrap() },
+        vec![0]
+    );
+    changing_screen_focus_test!(
+        cycle_screen_forwards_on_first_screen,
+        0,
+        |wm: &mut WindowManager<_>| { wm.cycle_screen(Forward).unwrap() },
+        vec![1]
+    );
+    changing_screen_focus_test!(
+        cycle_screen_forwards_on_last_screen,
+        1, // the mock setup apparently only has two screens
+        |wm: &mut WindowManager<_>| { wm.cycle_screen(Forward).unwrap() },
+        vec![]
+    );
+
+    changing_screen_focus_test!(
+        focus_screen_same,
+        0, // the mock setup apparently only has two screens
+        |wm: &mut WindowManager<_>| {
+            wm.focus_screen(&Selector::Index(0));
+        },
+        vec![]
+    );
+    changing_screen_focus_test!(
+        focus_screen_different,
+        0, // the mock setup apparently only has two screens
+        |wm: &mut WindowManager<_>| {
+            wm.focus_screen(&Selector::Index(1));
+        },
+        vec![1]
+    );
+
+    #[test]
+    fn zero_screen_startup_does_not_panic() {
+        let mut wm = test_windowmanager(0, vec![]);
+        assert_eq!(wm.screens.n_screens(), 0);
+
+        wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn cycle_screen_updates_active() {
+        let mut wm = test_windowmanager(2, vec![]);
+
+        assert_eq!(wm.active_screen_index(), 0);
+        wm.cycle_screen(Forward).unwrap();
+        assert_eq!(wm.active_screen_index(), 1);
+        wm.cycle_screen(Forward).unwrap();
+        assert_eq!(wm.active_screen_index(), 1);
+    }
+
+    // Records the regions of the screens that the cursor is warped to
+    #[derive(Default)]
+    struct ScreenWarpXConn {
+        warps: Cell<Vec<Region>>,
+    }
+
+    __impl_stub_xcon! {
+        for ScreenWarpXConn;
+
+        atom_queries: {}
+        client_properties: {}
+        client_handler: {}
+        client_config: {}
+        event_handler: {}
+        state: {
+            fn mock_current_screens(&self) -> XResult<Vec<Screen>> {
+                Ok(test_screens())
+            }
+
+            fn mock_warp_cursor(&self, win_id: Option<Xid>, screen: &Screen) -> XResult<()> {
+                if win_id.is_none() {
+                    let mut warps = self.warps.take();
+                    warps.push(screen.region(false));
+                    self.warps.set(warps);
+                }
+                Ok(())
+            }
+        }
+        conn: {}
+    }
+
+    test_cases! {
+        focus_screen_n;
+        args: (n: usize, expected_screen: usize, expected_warps: Vec<Region>);
+
+        case: other_screen => (1, 1, vec![Region::new(1366, 0, 1366, 768)]);
+        case: current_screen => (0, 0, vec![Region::new(0, 0, 1366, 768)]);
+        case: out_of_range => (2, 0, vec![]);
+
+        body: {
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                ..Default::default()
+            };
+            let conn = ScreenWarpXConn::default();
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            wm.conn().warps.take();
+
+            let res = wm.focus_screen_n(n);
+            assert_eq!(res.is_ok(), n < 2);
+            assert_eq!(wm.active_screen_index(), expected_screen);
+            assert_eq!(wm.screens.active_ws_index(), expected_screen);
+            assert_eq!(wm.conn().warps.take(), expected_warps);
+        }
+    }
+
+    test_cases! {
+        move_focused_to_screen_in_direction;
+        args: (screen: usize, position: RelativePosition, expected_workspace: usize);
+
+        case: first_to_right => (0, RelativePosition::Right, 1);
+        case: second_to_left => (1, RelativePosition::Left, 0);
+        case: first_to_left_is_noop => (0, RelativePosition::Left, 0);
+        case: second_to_right_is_noop => (1, RelativePosition::Right, 1);
+        case: first_above_is_noop => (0, RelativePosition::Above, 0);
+
+        body: {
+            let mut wm = wm_with_mock_conn(vec![], vec![]);
+            wm.focus_screen_n(screen).unwrap();
+            add_n_clients(&mut wm, 2, 0);
+
+            wm.move_focused_to_screen_in_direction(position).unwrap();
+
+            assert_eq!(wm.clients.get(20).unwrap().workspace(), expected_workspace);
+            assert!(wm.workspaces[expected_workspace].client_ids().contains(&20));
+            assert!(wm.workspaces[screen].client_ids().contains(&10));
+        }
+    }
+
+    #[test]
+    fn toggle_screen_alternates_between_screens() {
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let conn = ScreenWarpXConn::default();
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        wm.conn().warps.take();
+
+        // nothing to toggle back to yet
+        wm.toggle_screen().unwrap();
+        assert_eq!(wm.active_screen_index(), 0);
+
+        wm.focus_screen_n(1).unwrap();
+        wm.focus_workspace(&Selector::Index(3)).unwrap();
+        wm.conn().warps.take();
+
+        wm.toggle_screen().unwrap();
+        assert_eq!(wm.active_screen_index(), 0);
+        assert_eq!(wm.active_workspace().name(), "1");
+
+        wm.toggle_screen().unwrap();
+        assert_eq!(wm.active_screen_index(), 1);
+        assert_eq!(wm.active_workspace().name(), "4");
+
+        assert_eq!(
+            wm.conn().warps.take(),
+            vec![
+                Region::new(0, 0, 1366, 768),
+                Region::new(1366, 0, 1366, 768)
+            ]
+        );
+    }
+
+    #[test]
+    fn toggle_screen_with_a_single_screen_is_a_noop() {
+        let mut wm = test_windowmanager(1, vec![]);
+
+        wm.toggle_screen().unwrap();
+
+        assert_eq!(wm.active_screen_index(), 0);
+        assert_eq!(wm.focused_workspaces(), vec![0]);
+    }
+
+    #[test]
+    fn return_client_home_moves_clients_back_to_their_origin() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        add_n_clients(&mut wm, 2, 0);
+        assert_eq!(wm.clients.get(20).unwrap().origin_workspace(), Some(2));
+
+        wm.client_to_workspace(&Selector::Index(4)).unwrap();
+        wm.focus_workspace(&Selector::Index(4)).unwrap();
+        wm.client_to_workspace(&Selector::Index(0)).unwrap();
+        assert_eq!(wm.clients.get(20).unwrap().workspace(), 0);
+
+        wm.return_client_home(20).unwrap();
+
+        let c = wm.clients.get(20).unwrap();
+        assert_eq!(c.workspace(), 2);
+        assert_eq!(c.origin_workspace(), Some(2));
+        assert!(wm.workspaces[2].client_ids().contains(&20));
+        assert!(!wm.workspaces[0].client_ids().contains(&20));
+    }
+
+    #[test]
+    fn carried_clients_follow_workspace_changes_until_dropped() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0);
+        wm.toggle_carry_focused().unwrap();
+        assert_eq!(wm.carried_client(), Some(30));
+
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        wm.focus_workspace(&Selector::Index(3)).unwrap();
+
+        assert_eq!(wm.workspaces[3].client_ids(), vec![30]);
+        assert!(wm.workspaces[2].client_ids().is_empty());
+        assert_eq!(wm.focused_client_id(), Some(30));
+
+        wm.toggle_carry_focused().unwrap();
+        wm.focus_workspace(&Selector::Index(0)).unwrap();
+
+        assert_eq!(wm.carried_client(), None);
+        assert_eq!(wm.clients.get(30).unwrap().workspace(), 3);
+        assert_eq!(wm.workspaces[3].client_ids(), vec![30]);
+        assert_eq!(wm.workspaces[0].client_ids(), vec![20, 10]);
+    }
+
+    #[test]
+    fn removing_a_carried_client_drops_it() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        wm.toggle_carry_focused().unwrap();
+
+        wm.remove_client(20).unwrap();
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+
+        assert_eq!(wm.carried_client(), None);
+        assert_eq!(wm.workspaces[0].client_ids(), vec![10]);
+    }
+
+    #[test]
+    fn return_client_home_for_a_client_at_home_is_a_noop() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+
+        wm.return_client_home(10).unwrap();
+
+        assert_eq!(wm.workspaces[0].client_ids(), vec![20, 10]);
+    }
+
+    #[test]
+    fn return_client_home_for_an_unknown_client_is_an_error() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+
+        assert!(wm.return_client_home(42).is_err());
+    }
+
+    #[test]
+    fn cycle_workspace_updates_focused() {
+        let mut wm = test_windowmanager(1, vec![]);
+
+        assert_eq!(wm.focused_workspaces(), vec![0]);
+        wm.cycle_workspace(Forward).unwrap();
+        assert_eq!(wm.focused_workspaces(), vec![1]);
+        wm.cycle_workspace(Backward).unwrap();
+        wm.cycle_workspace(Backward).unwrap();
+        assert_eq!(wm.focused_workspaces(), vec![8]);
+    }
+
+    // (workspace index, client ids, X calls made before the hook ran)
+    type KillRecord = (usize, Vec<Xid>, usize);
+    struct KillWorkspaceClientsHook {
+        veto: bool,
+        seen: Rc<RefCell<Vec<KillRecord>>>,
+    }
+
+    impl crate::core::hooks::Hook<RecordingXConn> for KillWorkspaceClientsHook {
+        fn kill_workspace_clients(
+            &mut self,
+            wm: &mut WindowManager<RecordingXConn>,
+            wix: usize,
+            ids: &[Xid],
+        ) -> Result<()> {
+            // record how many X calls had been made at the point the hook ran
+            let n_calls = wm.conn().calls().len();
+            self.seen.borrow_mut().push((wix, ids.to_vec(), n_calls));
+            if self.veto {
+                Err(perror!("vetoed"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    test_cases! {
+        kill_workspace_clients;
+        args: (veto: bool);
+
+        case: allowed => (false);
+        case: vetoed => (true);
+
+        body: {
+            let seen = Rc::new(RefCell::new(vec![]));
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                ..Default::default()
+            };
+            let hooks: Hooks<RecordingXConn> = vec![Box::new(KillWorkspaceClientsHook {
+                veto,
+                seen: Rc::clone(&seen),
+            })];
+            let mut wm =
+                WindowManager::new(conf, RecordingXConn::init(), hooks, logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 3, 0); // [30, 20, 10]
+            let delete = vec![Atom::WmDeleteWindow.as_ref().to_string()];
+            wm.clients.modify(10, |c| c.wm_protocols = delete.clone());
+            wm.clients.modify(30, |c| c.wm_protocols = delete.clone());
+            wm.conn().clear();
+
+            let res = wm.kill_workspace_clients(0);
+            let calls: Vec<RecordedCall> = wm
+                .conn()
+                .calls()
+                .into_iter()
+                .filter(|(m, _)| m == "send_client_event" || m == "kill_client")
+                .collect();
+
+            assert_eq!(*seen.borrow(), vec![(0, vec![30, 20, 10], 0)]);
+            if veto {
+                assert!(res.is_err());
+                assert!(calls.is_empty());
+            } else {
+                res.unwrap();
+                let conn = RecordingXConn::init();
+                let delete_msg = |id| {
+                    let msg = ClientMessageKind::DeleteWindow(id).as_message(&conn).unwrap();
+                    ("send_client_event".to_string(), strings!(msg))
+                };
+                assert_eq!(
+                    calls,
+                    vec![
+                        delete_msg(30),
+                        ("kill_client".to_string(), strings!(20)),
+                        delete_msg(10),
+                    ]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn kill_workspace_clients_unknown_workspace() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        assert!(wm.kill_workspace_clients(42).is_err());
+    }
+
+    type WorkspacesUpdate = (Vec<String>, usize);
+    struct RecordWorkspaceNames(Rc<RefCell<Vec<WorkspacesUpdate>>>);
+    impl<X: XConn> crate::core::hooks::Hook<X> for RecordWorkspaceNames {
+        fn workspaces_updated(
+            &mut self,
+            _: &mut WindowManager<X>,
+            names: &[&str],
+            active: usize,
+        ) -> Result<()> {
+            let names = names.iter().map(|s| s.to_string()).collect();
+            self.0.borrow_mut().push((names, active));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rename_current_workspace() {
+        let updates = Rc::new(RefCell::new(vec![]));
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            workspaces: vec!["1".into(), "2".into(), "3".into()],
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let hooks: Hooks<MockXConn> = vec![Box::new(RecordWorkspaceNames(Rc::clone(&updates)))];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        updates.borrow_mut().clear();
+
+        wm.rename_current_workspace("web").unwrap();
+
+        assert_eq!(wm.workspaces[2].name(), "web");
+        assert_eq!(
+            *updates.borrow(),
+            vec![(vec!["1".to_string(), "2".to_string(), "web".to_string()], 2)]
+        );
+    }
+
+    struct RecordLayoutParamsChanged(Rc<RefCell<Vec<usize>>>);
+    impl<X: XConn> crate::core::hooks::Hook<X> for RecordLayoutParamsChanged {
+        fn layout_params_changed(
+            &mut self,
+            _: &mut WindowManager<X>,
+            workspace_index: usize,
+            _: usize,
+        ) -> Result<()> {
+            self.0.borrow_mut().push(workspace_index);
+            Ok(())
+        }
+    }
+
+    test_cases! {
+        layout_params_changed_hook;
+        args: (ratio_changes: Vec<Change>, max_main_changes: Vec<Change>, expected: usize);
+
+        case: ratio_increased => (vec![Change::More], vec![], 1);
+        case: ratio_decreased => (vec![Change::Less], vec![], 1);
+        case: ratio_already_at_max => (vec![Change::More, Change::More], vec![], 1);
+        case: max_main_increased => (vec![], vec![Change::More], 1);
+        case: max_main_already_zero => (vec![], vec![Change::Less, Change::Less], 1);
+        case: both => (vec![Change::More], vec![Change::More], 2);
+
+        body: {
+            let fired = Rc::new(RefCell::new(vec![]));
+            let conn = MockXConn::new(test_screens(), vec![], vec![]);
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                main_ratio_step: 0.5,
+                ..Default::default()
+            };
+            let hooks: Hooks<MockXConn> =
+                vec![Box::new(RecordLayoutParamsChanged(Rc::clone(&fired)))];
+            let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+            wm.init().unwrap();
+            wm.focus_workspace(&Selector::Index(1)).unwrap();
+
+            for change in ratio_changes {
+                wm.update_main_ratio(change).unwrap();
+            }
+            for change in max_main_changes {
+                wm.update_max_main(change).unwrap();
+            }
+
+            assert_eq!(*fired.borrow(), vec![1; expected]);
+        }
+    }
+
+    struct RecordLayoutChanges(Rc<RefCell<Vec<usize>>>);
+    impl<X: XConn> crate::core::hooks::Hook<X> for RecordLayoutChanges {
+        fn layout_change(&mut self, _: &mut WindowManager<X>, wix: usize, _: usize) -> Result<()> {
+            self.0.borrow_mut().push(wix);
+            Ok(())
+        }
+    }
+
+    fn suspended_hooks_wm(changes: &Rc<RefCell<Vec<usize>>>) -> WindowManager<MockXConn> {
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: test_layouts(),
+            ..Default::default()
+        };
+        let hooks: Hooks<MockXConn> = vec![Box::new(RecordLayoutChanges(Rc::clone(changes)))];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+
+        wm
+    }
+
+    test_cases! {
+        suspended_hooks;
+        args: (replay: bool, expected: Vec<usize>);
+
+        case: replayed_once_on_resume => (true, vec![0]);
+        case: dropped_on_resume => (false, vec![]);
+
+        body: {
+            let changes = Rc::new(RefCell::new(vec![]));
+            let mut wm = suspended_hooks_wm(&changes);
+
+            wm.suspend_hooks();
+            assert!(wm.hooks_suspended());
+            (0..3).for_each(|_| wm.cycle_layout(Direction::Forward).unwrap());
+            assert!(changes.borrow().is_empty());
+
+            wm.resume_hooks(replay);
+            assert!(!wm.hooks_suspended());
+            assert_eq!(*changes.borrow(), expected);
+
+            wm.cycle_layout(Direction::Forward).unwrap();
+            assert_eq!(changes.borrow().len(), expected.len() + 1);
+        }
+    }
+
+    #[test]
+    fn nested_hook_suspension_resumes_on_the_outermost_call() {
+        let changes = Rc::new(RefCell::new(vec![]));
+        let mut wm = suspended_hooks_wm(&changes);
+
+        wm.suspend_hooks();
+        wm.suspend_hooks();
+        wm.cycle_layout(Direction::Forward).unwrap();
+
+        wm.resume_hooks(true);
+        assert!(wm.hooks_suspended());
+        assert!(changes.borrow().is_empty());
+
+        wm.resume_hooks(true);
+        assert!(!wm.hooks_suspended());
+        assert_eq!(*changes.borrow(), vec![0]);
+    }
+
+    type PropertyChange = (Xid, String);
+    struct RecordPropertyChanges(Rc<RefCell<Vec<PropertyChange>>>);
+    impl<X: XConn> crate::core::hooks::Hook<X> for RecordPropertyChanges {
+        fn client_property_changed(
+            &mut self,
+            _: &mut WindowManager<X>,
+            id: Xid,
+            atom: &str,
+        ) -> Result<()> {
+            self.0.borrow_mut().push((id, atom.to_string()));
+            Ok(())
+        }
+    }
+
+    test_cases! {
+        client_property_changed_hook;
+        args: (throttle_ms: u64, changes: Vec<(Xid, &str)>, expected: Vec<(Xid, &str)>);
+
+        case: managed_client => (0, vec![(10, "_CUSTOM")], vec![(10, "_CUSTOM")]);
+        case: unmanaged_window => (0, vec![(99, "_CUSTOM")], vec![]);
+        case: name_changes_are_excluded => (0, vec![(10, "WM_NAME")], vec![]);
+        case: unthrottled_repeats =>
+            (0, vec![(10, "_CUSTOM"), (10, "_CUSTOM")], vec![(10, "_CUSTOM"), (10, "_CUSTOM")]);
+        case: throttled_repeats =>
+            (60_000, vec![(10, "_CUSTOM"), (10, "_CUSTOM")], vec![(10, "_CUSTOM")]);
+        case: throttled_per_client_and_atom => (
+            60_000,
+            vec![(10, "_CUSTOM"), (10, "_OTHER"), (20, "_CUSTOM"), (10, "_CUSTOM")],
+            vec![(10, "_CUSTOM"), (10, "_OTHER"), (20, "_CUSTOM")]
+        );
+
+        body: {
+            let changes_seen = Rc::new(RefCell::new(vec![]));
+            let conn = MockXConn::new(test_screens(), vec![], vec![]);
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                property_change_throttle_ms: throttle_ms,
+                ..Default::default()
+            };
+            let hooks: Hooks<MockXConn> =
+                vec![Box::new(RecordPropertyChanges(Rc::clone(&changes_seen)))];
+            let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 2, 0);
+
+            for (id, atom) in changes {
+                let event = XEvent::PropertyNotify(PropertyEvent {
+                    id,
+                    atom: atom.to_string(),
+                    is_root: false,
+                });
+                let actions = process_next_event(event, &wm.state, &wm.conn);
+                wm.handle_event_actions(actions).unwrap();
+            }
+
+            let expected: Vec<PropertyChange> =
+                expected.into_iter().map(|(id, a)| (id, a.to_string())).collect();
+            assert_eq!(*changes_seen.borrow(), expected);
+        }
+    }
+
+    #[test]
+    fn the_last_property_change_in_a_burst_is_delivered() {
+        let changes_seen = Rc::new(RefCell::new(vec![]));
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            property_change_throttle_ms: 60_000,
+            ..Default::default()
+        };
+        let hooks: Hooks<MockXConn> =
+            vec![Box::new(RecordPropertyChanges(Rc::clone(&changes_seen)))];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 1, 0);
+
+        for _ in 0..3 {
+            let event = XEvent::PropertyNotify(PropertyEvent {
+                id: 10,
+                atom: "_CUSTOM".to_string(),
+                is_root: false,
+            });
+            let actions = process_next_event(event, &wm.state, &wm.conn);
+            wm.handle_event_actions(actions).unwrap();
+        }
+        let expected = vec![(10, "_CUSTOM".to_string())];
+        assert_eq!(*changes_seen.borrow(), expected);
+
+        // Still inside of the throttle window
+        wm.flush_pending_property_changes();
+        assert_eq!(*changes_seen.borrow(), expected);
+
+        let key = (10, "_CUSTOM".to_string());
+        let rewound = Instant::now() - Duration::from_secs(61);
+        wm.last_property_changes.insert(key.clone(), rewound);
+        wm.flush_pending_property_changes();
+        wm.flush_pending_property_changes();
+        assert_eq!(*changes_seen.borrow(), vec![key.clone(), key]);
+    }
+
+    test_cases! {
+        slow_hook_warning;
+        args: (threshold_ms: u64, elapsed_ms: u64, expect_warning: bool);
+
+        case: past_threshold => (5, 20, true);
+        case: at_threshold => (5, 5, false);
+        case: within_threshold => (1000, 0, false);
+        case: disabled => (0, 20, false);
+
+        body: {
+            let conf = Config {
+                layouts: test_layouts(),
+                hook_warning_threshold_ms: threshold_ms,
+                ..Default::default()
+            };
+            let conn = MockXConn::new(test_screens(), vec![], vec![]);
+            let wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+
+            assert_eq!(wm.hook_is_slow(Duration::from_millis(elapsed_ms)), expect_warning);
+        }
+    }
+
+    struct RecordRawEvents(Rc<RefCell<Vec<XEvent>>>);
+    impl<X: XConn> crate::core::hooks::Hook<X> for RecordRawEvents {
+        fn raw_event(&mut self, _: &mut WindowManager<X>, event: &XEvent) -> Result<()> {
+            self.0.borrow_mut().push(event.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn raw_event_hooks_see_every_event() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let conn = TestXConn::new(1, n_clients(1), vec![]);
+        let conf = Config {
+            layouts: test_layouts(),
+            ..Default::default()
+        };
+        let hooks: Hooks<TestXConn> = vec![Box::new(RecordRawEvents(Rc::clone(&seen)))];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+
+        wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
+            .unwrap();
+
+        assert_eq!(
+            *seen.borrow(),
+            vec![XEvent::MapRequest(0, false), XEvent::KeyPress(EXIT_CODE)]
+        );
+        assert!(wm.clients.is_known(0), "the event was still processed");
+    }
+
+    #[test]
+    fn per_event_hooks_are_dropped_while_suspended() {
+        let suspend = KeyCode { mask: 0, code: 100 };
+        let resume = KeyCode { mask: 0, code: 101 };
+        let seen = Rc::new(RefCell::new(vec![]));
+        let changes = Rc::new(RefCell::new(vec![]));
+
+        let mut events = vec![XEvent::KeyPress(suspend)];
+        events.extend((0..3).map(|_| XEvent::KeyPress(LAYOUT_CHANGE_CODE)));
+        events.push(XEvent::KeyPress(resume));
+        let conn = TestXConn::new(1, events, vec![]);
+        let conf = Config {
+            layouts: test_layouts(),
+            ..Default::default()
+        };
+        let hooks: Hooks<TestXConn> = vec![
+            Box::new(RecordRawEvents(Rc::clone(&seen))),
+            Box::new(RecordLayoutChanges(Rc::clone(&changes))),
+        ];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+
+        let mut bindings = test_key_bindings();
+        bindings.insert(
+            suspend,
+            Box::new(|wm: &mut WindowManager<TestXConn>| {
+                wm.suspend_hooks();
+                Ok(())
+            }),
+        );
+        bindings.insert(
+            resume,
+            Box::new(|wm: &mut WindowManager<TestXConn>| {
+                // Only the layout triggers from the three key presses, coalesced
+                assert!(wm.suspended_hooks.len() <= 2);
+                assert!(wm
+                    .suspended_hooks
+                    .keys()
+                    .all(|h| matches!(h, HookName::LayoutChange(_) | HookName::LayoutApplied(..))));
+                wm.resume_hooks(true);
+                Ok(())
+            }),
+        );
+        wm.grab_keys_and_run(bindings, HashMap::new()).unwrap();
+
+        assert_eq!(
+            *seen.borrow(),
+            vec![XEvent::KeyPress(suspend), XEvent::KeyPress(EXIT_CODE)]
+        );
+        assert_eq!(*changes.borrow(), vec![0]);
+        assert!(wm.suspended_hooks.is_empty());
+    }
+
+    test_cases! {
+        startup_workspaces;
+        args: (startup: Vec<usize>, expected: Vec<usize>);
+
+        case: default => (vec![], vec![0, 1]);
+        case: configured => (vec![1, 2], vec![1, 2]);
+        case: swapped => (vec![1, 0], vec![1, 0]);
+        case: partial => (vec![3], vec![3, 1]);
+        case: unknown_workspace_is_skipped => (vec![42, 4], vec![0, 4]);
+
+        body: {
+            let conn = TestXConn::new(2, vec![XEvent::KeyPress(EXIT_CODE)], vec![]);
+            let conf = Config {
+                layouts: test_layouts(),
+                startup_workspaces: startup,
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+
+            wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
+                .unwrap();
+
+            assert_eq!(wm.focused_workspaces(), expected);
+            assert_eq!(wm.active_screen_index(), 0);
+        }
+    }
+
+    test_cases! {
+        cycle_occupied_workspace;
+        args: (start: usize, direction: Direction, wraps: bool, expected: usize);
+
+        // workspaces 0, 3 and 5 have clients: all others are empty
+        case: forward_skips_empty => (0, Forward, true, 3);
+        case: forward_from_empty => (1, Forward, true, 3);
+        case: forward_wraps => (5, Forward, true, 0);
+        case: backward_skips_empty => (5, Backward, true, 3);
+        case: backward_wraps => (0, Backward, true, 5);
+        case: forward_without_wrapping => (5, Forward, false, 5);
+        case: backward_without_wrapping => (0, Backward, false, 0);
+
+        body: {
+            let conn = MockXConn::new(
+                vec![Screen::new(Region::new(0, 0, 1366, 768), 0)],
+                vec![],
+                vec![],
+            );
+            let conf = Config {
+                layouts: focus_test_layouts(false),
+                cycle_occupied_wraps: wraps,
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            for (i, &wix) in [0, 3, 5].iter().enumerate() {
+                wm.focus_workspace(&Selector::Index(wix)).unwrap();
+                add_n_clients(&mut wm, 1, i);
+            }
+            wm.focus_workspace(&Selector::Index(start)).unwrap();
+
+            wm.cycle_occupied_workspace(direction).unwrap();
+
+            assert_eq!(wm.screens.active_ws_index(), expected);
+        }
+    }
+
+    #[test]
+    fn cycle_occupied_workspace_with_no_other_occupied_is_noop() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+
+        wm.cycle_occupied_workspace(Forward).unwrap();
+        assert_eq!(wm.screens.active_ws_index(), 0);
+        wm.cycle_occupied_workspace(Backward).unwrap();
+        assert_eq!(wm.screens.active_ws_index(), 0);
+    }
+
+    #[test]
+    fn drag_workspace_move_focused_workspaces_between_screens() {
+        let mut wm = test_windowmanager(2, vec![]);
+
+        assert_eq!(wm.focused_workspaces(), vec![0, 1]);
+        wm.drag_workspace(Forward).unwrap();
+        assert_eq!(wm.focused_workspaces(), vec![1, 0]);
+    }
+
+    #[test]
+    fn cycle_client_updates_focus() {
+        let mut wm = test_windowmanager(1, n_clients(3));
+        wm.init().unwrap();
+        wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
+            .unwrap();
+        wm.focus_client(&Selector::WinId(0)).unwrap();
+
+        assert_eq!(wm.focused_client_id(), Some(0));
+        wm.cycle_client(Backward).unwrap();
+        assert_eq!(wm.focused_client_id(), Some(1));
+        wm.cycle_client(Backward).unwrap();
+        assert_eq!(wm.focused_client_id(), Some(2));
+        wm.cycle_client(Backward).unwrap();
+        assert_eq!(wm.focused_client_id(), Some(0));
+    }
+
+    #[test]
+    fn focus_client() {
+        let mut wm = test_windowmanager(1, n_clients(3));
+        wm.init().unwrap();
+        wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
+            .unwrap();
+
+        let focused = wm.focus_client(&Selector::WinId(0));
+        assert_eq!(focused.unwrap(), 0);
+        let focused = wm.focus_client(&Selector::WinId(42));
+        assert!(focused.is_err());
+        let focused = wm.focus_client(&Selector::WinId(1));
+        assert_eq!(focused.unwrap(), 1);
+        let focused = wm.focus_client(&Selector::WinId(42));
+        assert!(focused.is_err());
+    }
+
+    #[test]
+    fn merge_workspaces_moves_all_clients() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0); // [30, 20, 10]
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        add_n_clients(&mut wm, 1, 3); // [40]
+
+        wm.merge_workspaces(0, 2).unwrap();
+
+        assert!(wm.workspaces[0].is_empty());
+        assert_eq!(wm.workspaces[2].client_ids(), vec![40, 30, 20, 10]);
+        for id in [10, 20, 30].iter() {
+            assert_eq!(wm.clients.get(*id).unwrap().workspace(), 2);
+        }
+    }
+
+    #[test]
+    fn merge_workspaces_does_not_duplicate_clients() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0); // [20, 10]
+        wm.workspaces[1].add_client(10, &InsertPoint::Last).unwrap();
+
+        wm.merge_workspaces(0, 1).unwrap();
+
+        assert!(wm.workspaces[0].is_empty());
+        assert_eq!(wm.workspaces[1].client_ids(), vec![10, 20]);
+        for id in [10, 20].iter() {
+            assert_eq!(wm.clients.get(*id).unwrap().workspace(), 1);
+        }
+    }
+
+    #[test]
+    fn merge_workspaces_rejects_invalid_indices() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        assert!(wm.merge_workspaces(0, 42).is_err());
+        assert!(wm.merge_workspaces(42, 0).is_err());
+    }
+
+    test_cases! {
+        balance_visible_workspaces;
+        args: (on_first: usize, on_second: usize, expected: Vec<usize>);
+
+        case: five_and_one => (5, 1, vec![3, 3]);
+        case: one_and_five => (1, 5, vec![3, 3]);
+        case: odd_total => (4, 1, vec![3, 2]);
+        case: already_balanced => (2, 3, vec![2, 3]);
+        case: all_empty => (0, 0, vec![0, 0]);
+
+        body: {
+            let mut wm = wm_with_mock_conn(vec![], vec![]);
+            add_n_clients(&mut wm, on_first, 0);
+            wm.focus_screen(&Selector::Index(1));
+            add_n_clients(&mut wm, on_second, on_first);
+            wm.focus_screen(&Selector::Index(0));
+            wm.workspaces[2].add_client(100, &InsertPoint::Last).unwrap();
+
+            wm.balance_visible_workspaces().unwrap();
+
+            let counts: Vec<usize> = (0..2)
+                .map(|wix| {
+                    let ids = wm.workspaces[wix].client_ids();
+                    for id in ids.iter() {
+                        assert_eq!(wm.clients.get(*id).unwrap().workspace(), wix);
+                    }
+                    ids.len()
+                })
+                .collect();
+
+            assert_eq!(counts, expected);
+            assert_eq!(wm.workspaces[2].client_ids(), vec![100]);
+        }
+    }
+
+    test_cases! {
+        spread_clients;
+        args: (n_clients: usize, keep: usize, occupied: Option<usize>, expected: Vec<usize>);
+
+        case: one_per_workspace => (4, 1, None, vec![1, 1, 1, 1]);
+        case: balanced_round_robin => (7, 1, None, vec![1, 2, 2, 2]);
+        case: keep_more_than_one => (5, 2, None, vec![2, 1, 1, 1]);
+        case: fewer_clients_than_workspaces => (2, 1, None, vec![1, 1, 0, 0]);
+        case: occupied_workspaces_are_skipped => (5, 1, Some(2), vec![1, 2, 0, 2]);
+        case: keep_all => (3, 3, None, vec![3, 0, 0, 0]);
+
+        body: {
+            let conn = MockXConn::new(
+                vec![Screen::new(Region::new(0, 0, 1366, 768), 0)],
+                vec![],
+                vec![],
+            );
+            let conf = Config {
+                workspaces: vec!["1".into(), "2".into(), "3".into(), "4".into()],
+                layouts: focus_test_layouts(false),
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            if let Some(wix) = occupied {
+                wm.focus_workspace(&Selector::Index(wix)).unwrap();
+                add_n_clients(&mut wm, 1, 100);
+                wm.focus_workspace(&Selector::Index(0)).unwrap();
+            }
+            add_n_clients(&mut wm, n_clients, 0);
+
+            wm.spread_clients(keep).unwrap();
+
+            let added: Vec<usize> = (0..4)
+                .map(|wix| {
+                    let ids = wm.workspaces[wix].client_ids();
+                    for id in ids.iter() {
+                        assert_eq!(wm.clients.get(*id).unwrap().workspace(), wix);
+                    }
+                    ids.iter().filter(|&&id| id < 1000).count()
+                })
+                .collect();
+
+            assert_eq!(added, expected);
+        }
+    }
+
+    #[test]
+    fn client_geometry_matches_arranged_region() {
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(
+            conf,
+            RecordingXConn::init(),
+            vec![],
+            logging_error_handler(),
+        );
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 1, 0);
+        wm.clients.modify(10, |c| c.mapped = true); // RecordingXConn doesn't track map state
+        wm.conn().clear();
+        wm.layout_screen(0).unwrap();
+
+        let positioned: Vec<Vec<String>> = wm
+            .conn()
+            .calls()
+            .into_iter()
+            .filter(|(m, _)| m == "position_client")
+            .map(|(_, args)| args)
+            .collect();
+        let r = wm.client_geometry(10).unwrap();
+
+        assert_eq!(
+            positioned,
+            vec![strings!(10, r, wm.config.border_px, false)]
+        );
+        assert_eq!(wm.client_geometry(42), None);
+
+        wm.clients.modify(10, |c| c.mapped = false);
+        assert_eq!(wm.client_geometry(10), None);
+    }
+
+    #[test]
+    fn focus_workspace_n() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+
+        wm.focus_workspace_n(3).unwrap();
+        assert_eq!(wm.screens.active_ws_index(), 3);
+
+        let n = wm.workspaces.len();
+        assert!(wm.focus_workspace_n(n).is_err());
+        assert_eq!(wm.screens.active_ws_index(), 3);
+    }
+
+    #[test]
+    fn unmanaged_clients_are_skipped_by_layout() {
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(
+            conf,
+            RecordingXConn::init(),
+            vec![],
+            logging_error_handler(),
+        );
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 3, 0);
+
+        let positioned = |wm: &WindowManager<RecordingXConn>| -> Vec<String> {
+            wm.conn()
+                .calls()
+                .into_iter()
+                .filter(|(m, _)| m == "position_client")
+                .map(|(_, args)| args[0].clone())
+                .collect()
+        };
+
+        wm.conn().clear();
+        wm.set_client_managed(20, false).unwrap();
+        assert_eq!(positioned(&wm), strings!(30, 10));
+        assert_eq!(wm.workspaces[0].client_ids(), vec![30, 20, 10]);
+
+        wm.conn().clear();
+        wm.set_client_managed(20, true).unwrap();
+        assert_eq!(positioned(&wm), strings!(30, 20, 10));
+
+        assert!(wm.set_client_managed(42, false).is_err());
+    }
+
+    test_cases! {
+        cycle_tiled_client;
+        args: (focused: Xid, direction: Direction, expected: Xid);
+
+        // clients are [40, 30, 20, 10] with 30 and 10 floating
+        case: forward_skips_floating => (40, Forward, 20);
+        case: forward_wraps => (20, Forward, 40);
+        case: backward_skips_floating => (20, Backward, 40);
+        case: from_floating_forward => (30, Forward, 40);
+        case: from_floating_backward => (10, Backward, 20);
+
+        body: {
+            let mut wm = wm_with_mock_conn(vec![], vec![]);
+            add_n_clients(&mut wm, 4, 0);
+            wm.clients.modify(30, |c| c.floating = true);
+            wm.clients.modify(10, |c| c.floating = true);
+            wm.focus_client(&Selector::WinId(focused)).unwrap();
+
+            wm.cycle_tiled_client(direction).unwrap();
+
+            assert_eq!(wm.workspaces[0].focused_client(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn cycle_tiled_client_with_single_tiled_client_is_noop() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0);
+        wm.clients.modify(30, |c| c.floating = true);
+        wm.clients.modify(10, |c| c.floating = true);
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+
+        wm.cycle_tiled_client(Forward).unwrap();
+
+        assert_eq!(wm.workspaces[0].focused_client(), Some(20));
+    }
+
+    #[test]
+    fn focus_last_client_toggles_between_recent_clients() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0); // [30, 20, 10]
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+        wm.focus_client(&Selector::WinId(10)).unwrap();
+
+        wm.focus_last_client().unwrap();
+        assert_eq!(wm.focused_client_id(), Some(20));
+        wm.focus_last_client().unwrap();
+        assert_eq!(wm.focused_client_id(), Some(10));
+        wm.focus_last_client().unwrap();
+        assert_eq!(wm.focused_client_id(), Some(20));
+        assert_eq!(wm.active_workspace().focused_client(), Some(20));
+    }
+
+    #[test]
+    fn focus_last_client_ignores_removed_clients() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0); // [30, 20, 10]
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+        wm.focus_client(&Selector::WinId(10)).unwrap();
+        wm.remove_client(20).unwrap();
+
+        wm.focus_last_client().unwrap();
+
+        assert_eq!(wm.focused_client_id(), Some(10));
+    }
+
+    #[test]
+    fn focus_master() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0); // [30, 20, 10]
+        wm.focus_client(&Selector::WinId(10)).unwrap();
+        assert_eq!(wm.focused_client_id(), Some(10));
+
+        wm.focus_master().unwrap();
+
+        assert_eq!(wm.focused_client_id(), Some(30));
+        assert_eq!(wm.active_workspace().focused_client(), Some(30));
+    }
+
+    test_cases! {
+        cycle_floating_client;
+        args: (floating: &[Xid], focused: Xid, direction: Direction, expected: Xid);
+
+        case: forward_between_floating => (&[10, 30], 10, Forward, 30);
+        case: forward_wraps => (&[10, 30], 30, Forward, 10);
+        case: backward_skips_tiled => (&[10, 30], 30, Backward, 10);
+        case: from_tiled_forward => (&[10, 30], 20, Forward, 30);
+        case: from_tiled_backward => (&[10, 30], 20, Backward, 10);
+        case: single_floating_is_noop => (&[10], 20, Forward, 20);
+        case: no_floating_is_noop => (&[], 20, Forward, 20);
+
+        body: {
+            let mut wm = wm_with_mock_conn(vec![], vec![]);
+            add_n_clients(&mut wm, 3, 0);
+            for &id in floating {
+                wm.clients.modify(id, |c| c.floating = true);
+            }
+            wm.focus_client(&Selector::WinId(focused)).unwrap();
+
+            wm.cycle_floating_client(direction).unwrap();
+
+            assert_eq!(wm.workspaces[0].focused_client(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn focus_client_no_clients() {
+        let mut wm = test_windowmanager(1, vec![]);
+        let focused = wm.focus_client(&Selector::WinId(0));
+        assert!(focused.is_err());
+    }
+
+    #[test]
+    fn rotate_clients() {
+        let mut wm = test_windowmanager(1, n_clients(3));
+        wm.init().unwrap();
+        wm.grab_keys_and_run(test_key_bindings(), test_mouse_bindings())
+            .unwrap();
+
+        assert_eq!(wm.active_workspace().client_ids(), vec![2, 1, 0]);
+        wm.rotate_clients(Forward).unwrap();
+        assert_eq!(wm.active_workspace().client_ids(), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn drag_client() {
+        let mut wm = test_windowmanager(1, n_clients(4));
+        wm.init().unwrap();
+        wm.grab_keys_and_run(test_key_bindings(), test_mouse_bindings())
+            .unwrap();
+
+        assert_eq!(wm.active_workspace().client_ids(), vec![3, 2, 1, 0]);
+        wm.drag_client(Forward).unwrap();
+        assert_eq!(wm.active_workspace().client_ids(), vec![2, 3, 1, 0]);
+        wm.drag_client(Forward).unwrap();
+        assert_eq!(wm.active_workspace().client_ids(), vec![2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn cycle_layout() {
+        let mut wm = test_windowmanager(1, vec![]);
+
+        assert_eq!(wm.current_layout_symbol(), "first");
+        wm.cycle_layout(Forward).unwrap();
+        assert_eq!(wm.current_layout_symbol(), "second");
+        wm.cycle_layout(Forward).unwrap();
+        assert_eq!(wm.current_layout_symbol(), "first");
+    }
+
+    #[test]
+    fn cycle_layout_is_a_no_op_while_the_layout_is_locked() {
+        let mut wm = test_windowmanager(1, vec![]);
+
+        wm.toggle_layout_lock().unwrap();
+        wm.cycle_layout(Forward).unwrap();
+        wm.set_workspace_layout(0, "second").unwrap();
+        assert_eq!(wm.current_layout_symbol(), "first");
+
+        wm.toggle_layout_lock().unwrap();
+        wm.cycle_layout(Forward).unwrap();
+        assert_eq!(wm.current_layout_symbol(), "second");
+    }
+
+    #[test]
+    fn set_layout_all_workspaces_skips_locked_workspaces() {
+        let mut wm = test_windowmanager(1, vec![]);
+        wm.toggle_layout_lock().unwrap();
+
+        let missing = wm.set_layout_all_workspaces("second").unwrap();
+
+        assert!(missing.is_empty());
+        assert_eq!(wm.workspace_layout_symbol(0).unwrap(), "first");
+        assert_eq!(wm.workspace_layout_symbol(1).unwrap(), "second");
+    }
+
+    fn spanning_test_wm() -> WindowManager<MockXConn> {
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 1, 0);
+        wm.focus_screen(&Selector::Index(1));
+        add_n_clients(&mut wm, 1, 1);
+        wm.focus_screen(&Selector::Index(0));
+
+        wm
+    }
+
+    fn region_and_mapped(wm: &WindowManager<MockXConn>, id: Xid) -> (Option<Region>, bool) {
+        let c = wm.clients.get(id).unwrap();
+        (c.last_region(), c.mapped)
+    }
+
+    // mock_layout gives the full effective screen region to a single client: gaps are 5px and
+    // borders are 2px
+    const SINGLE_SCREEN: Region = Region {
+        x: 5,
+        y: 23,
+        w: 1352,
+        h: 736,
+    };
+    const BOTH_SCREENS: Region = Region {
+        x: 5,
+        y: 23,
+        w: 2718,
+        h: 736,
+    };
+
+    #[test]
+    fn span_workspace_all_screens_uses_the_combined_region() {
+        let mut wm = spanning_test_wm();
+
+        wm.span_workspace_all_screens(0).unwrap();
+
+        assert_eq!(region_and_mapped(&wm, 10), (Some(BOTH_SCREENS), true));
+        assert!(!region_and_mapped(&wm, 20).1);
+    }
+
+    #[test]
+    fn span_workspace_all_screens_toggles_off() {
+        let mut wm = spanning_test_wm();
+
+        wm.span_workspace_all_screens(0).unwrap();
+        wm.span_workspace_all_screens(0).unwrap();
+
+        assert_eq!(region_and_mapped(&wm, 10), (Some(SINGLE_SCREEN), true));
+        let r = Region::new(1366 + 5, 23, 1352, 736);
+        assert_eq!(region_and_mapped(&wm, 20), (Some(r), true));
+    }
+
+    #[test]
+    fn span_workspace_all_screens_ends_when_workspace_is_replaced() {
+        let mut wm = spanning_test_wm();
+
+        wm.span_workspace_all_screens(0).unwrap();
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+
+        assert!(!region_and_mapped(&wm, 10).1);
+        assert!(region_and_mapped(&wm, 20).1);
+        assert_eq!(wm.screens.spanning_workspace(), None);
+    }
+
+    #[test]
+    fn span_workspace_all_screens_keeps_focus_off_hidden_screens() {
+        let mut wm = spanning_test_wm();
+
+        wm.span_workspace_all_screens(0).unwrap();
+        wm.focus_screen_n(1).unwrap();
+        wm.cycle_screen(Direction::Forward).unwrap();
+        wm.set_screen_from_point(Some(Point::new(1400, 10)))
+            .unwrap();
+
+        assert_eq!(wm.screens.active_screen_index(), 0);
+        assert_eq!(wm.screens.spanning_workspace(), Some(0));
+        assert_eq!(region_and_mapped(&wm, 10), (Some(BOTH_SCREENS), true));
+    }
+
+    #[test]
+    fn span_workspace_all_screens_focuses_hidden_workspaces() {
+        let mut wm = spanning_test_wm();
+
+        wm.span_workspace_all_screens(4).unwrap();
+
+        assert_eq!(wm.screens.spanning_workspace(), Some(4));
+        assert_eq!(wm.focused_workspaces(), vec![4, 1]);
+        assert!(!region_and_mapped(&wm, 10).1);
+        assert!(!region_and_mapped(&wm, 20).1);
+    }
+
+    #[test]
+    fn init_focuses_the_primary_screen() {
+        let mut screens = test_screens();
+        screens[1].set_primary(true);
+        let conn = MockXConn::new(screens, vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        assert_eq!(wm.primary_screen_index(), Some(1));
+        assert_eq!(wm.screens.active_screen_index(), 1);
+
+        wm.handle_map_request(10).unwrap();
+        assert_eq!(wm.clients.get(10).unwrap().workspace(), 1);
+    }
+
+    // Clients 10 and 30 share a WM_CLASS while 20 has a different one and 30 holds focus
+    fn grouping_test_wm(group_by_class: bool) -> WindowManager<MockXConn> {
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            group_by_class,
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 3, 0);
+        for (id, class) in [(10, "browser"), (20, "terminal"), (30, "browser")] {
+            wm.clients.modify(id, |c| c.wm_class = vec![class.into()]);
+        }
+        wm.layout_screen(0).unwrap();
+
+        wm
+    }
+
+    fn mapped_ids(wm: &WindowManager<MockXConn>) -> Vec<Xid> {
+        [10, 20, 30]
+            .into_iter()
+            .filter(|&id| region_and_mapped(wm, id).1)
+            .collect()
+    }
+
+    #[test]
+    fn group_by_class_disabled_shows_all_clients() {
+        let wm = grouping_test_wm(false);
+
+        assert_eq!(mapped_ids(&wm), vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn group_by_class_shows_one_client_per_class() {
+        let wm = grouping_test_wm(true);
+
+        assert_eq!(mapped_ids(&wm), vec![20, 30]);
+    }
+
+    #[test]
+    fn cycle_client_group_swaps_the_visible_client() {
+        let mut wm = grouping_test_wm(true);
+
+        wm.cycle_client_group(Forward).unwrap();
+        assert_eq!(wm.focused_client_id(), Some(10));
+        assert_eq!(mapped_ids(&wm), vec![10, 20]);
+
+        // the group keeps showing its last focused member when focus moves elsewhere
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+        wm.layout_screen(0).unwrap();
+        assert_eq!(mapped_ids(&wm), vec![10, 20]);
+
+        wm.focus_client(&Selector::WinId(10)).unwrap();
+        wm.cycle_client_group(Backward).unwrap();
+        assert_eq!(wm.focused_client_id(), Some(30));
+        assert_eq!(mapped_ids(&wm), vec![20, 30]);
+    }
+
+    #[test]
+    fn cycle_client_group_with_no_other_members_is_a_noop() {
+        let mut wm = grouping_test_wm(true);
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+
+        wm.cycle_client_group(Forward).unwrap();
+
+        assert_eq!(wm.focused_client_id(), Some(20));
+        assert_eq!(mapped_ids(&wm), vec![20, 30]);
+    }
+
+    test_cases! {
+        cycle_layout_in_both_directions;
+        args: (directions: Vec<Direction>, expected: &str);
+
+        case: forward => (vec![Forward], "b");
+        case: backward => (vec![Backward], "c");
+        case: forward_wraps => (vec![Forward, Forward, Forward], "a");
+        case: backward_wraps => (vec![Backward, Backward, Backward], "a");
+        case: forward_then_backward => (vec![Forward, Forward, Backward], "b");
+
+        body: {
+            let conf = Config {
+                layouts: ["a", "b", "c"]
+                    .iter()
+                    .map(|s| Layout::new(*s, LayoutConf::default(), mock_layout, 1, 0.6))
+                    .collect(),
+                ..Default::default()
+            };
+            let conn = MockXConn::new(test_screens(), vec![], vec![]);
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+
+            for direction in directions {
+                wm.cycle_layout(direction).unwrap();
+            }
 
-        assert_eq!(wm.focused_workspaces(), vec![0, 1]);
-        wm.drag_workspace(Forward).unwrap();
-        assert_eq!(wm.focused_workspaces(), vec![1, 0]);
+            assert_eq!(wm.current_layout_symbol(), expected);
+        }
     }
 
     #[test]
-    fn cycle_client_updates_focus() {
-        let mut wm = test_windowmanager(1, n_clients(3));
+    fn resize_focused_takes_space_from_neighbour() {
+        let conf = Config {
+            layouts: vec![Layout::new(
+                "rows",
+                LayoutConf::default(),
+                side_stack,
+                0,
+                0.6,
+            )],
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(
+            conf,
+            RecordingXConn::init(),
+            vec![],
+            logging_error_handler(),
+        );
         wm.init().unwrap();
-        wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
-            .unwrap();
-        wm.focus_client(&Selector::WinId(0)).unwrap();
+        add_n_clients(&mut wm, 3, 0);
+        let heights = |wm: &WindowManager<RecordingXConn>| -> Vec<u32> {
+            [30, 20, 10]
+                .iter()
+                .map(|&id| wm.clients.get(id).unwrap().last_region().unwrap().h)
+                .collect()
+        };
 
-        assert_eq!(wm.focused_client_id(), Some(0));
-        wm.cycle_client(Backward).unwrap();
-        assert_eq!(wm.focused_client_id(), Some(1));
-        wm.cycle_client(Backward).unwrap();
-        assert_eq!(wm.focused_client_id(), Some(2));
-        wm.cycle_client(Backward).unwrap();
-        assert_eq!(wm.focused_client_id(), Some(0));
-    }
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+        let before = heights(&wm);
+        wm.resize_focused(Change::More, 50).unwrap();
+        let after = heights(&wm);
 
-    #[test]
-    fn focus_client() {
-        let mut wm = test_windowmanager(1, n_clients(3));
-        wm.init().unwrap();
-        wm.grab_keys_and_run(test_key_bindings(), HashMap::new())
-            .unwrap();
+        assert_eq!(after[0], before[0]);
+        assert_eq!(after[1], before[1] + 50);
+        assert_eq!(after[2], before[2] - 50);
+        assert_eq!(
+            after.iter().sum::<u32>(),
+            before.iter().sum::<u32>(),
+            "total height should be preserved"
+        );
 
-        let focused = wm.focus_client(&Selector::WinId(0));
-        assert_eq!(focused.unwrap(), 0);
-        let focused = wm.focus_client(&Selector::WinId(42));
-        assert!(focused.is_err());
-        let focused = wm.focus_client(&Selector::WinId(1));
-        assert_eq!(focused.unwrap(), 1);
-        let focused = wm.focus_client(&Selector::WinId(42));
-        assert!(focused.is_err());
+        wm.reset_client_sizes().unwrap();
+        assert_eq!(heights(&wm), before);
     }
 
     #[test]
-    fn focus_client_no_clients() {
+    fn set_workspace_layout() {
         let mut wm = test_windowmanager(1, vec![]);
-        let focused = wm.focus_client(&Selector::WinId(0));
-        assert!(focused.is_err());
+
+        assert_eq!(wm.workspace_layout_symbol(2).unwrap(), "first");
+        wm.set_workspace_layout(2, "second").unwrap();
+        assert_eq!(wm.workspace_layout_symbol(2).unwrap(), "second");
+        assert_eq!(wm.current_layout_symbol(), "first");
     }
 
     #[test]
-    fn rotate_clients() {
-        let mut wm = test_windowmanager(1, n_clients(3));
-        wm.init().unwrap();
-        wm.grab_keys_and_run(test_key_bindings(), test_mouse_bindings())
+    fn set_layout_all_workspaces() {
+        let mut wm = test_windowmanager(1, vec![]);
+        let n = wm.workspaces.len();
+        wm.push_workspace(Workspace::new("extra", focus_test_layouts(false)))
             .unwrap();
 
-        assert_eq!(wm.active_workspace().client_ids(), vec![2, 1, 0]);
-        wm.rotate_clients(Forward).unwrap();
-        assert_eq!(wm.active_workspace().client_ids(), vec![0, 2, 1]);
+        let missing = wm.set_layout_all_workspaces("second").unwrap();
+
+        assert_eq!(missing, vec![n]);
+        for wix in 0..n {
+            assert_eq!(wm.workspace_layout_symbol(wix).unwrap(), "second");
+        }
+        assert_eq!(wm.workspace_layout_symbol(n).unwrap(), "t");
     }
 
     #[test]
-    fn drag_client() {
-        let mut wm = test_windowmanager(1, n_clients(4));
-        wm.init().unwrap();
-        wm.grab_keys_and_run(test_key_bindings(), test_mouse_bindings())
-            .unwrap();
+    fn set_layout_all_workspaces_with_unknown_symbol_changes_nothing() {
+        let mut wm = test_windowmanager(1, vec![]);
+        let n = wm.workspaces.len();
 
-        assert_eq!(wm.active_workspace().client_ids(), vec![3, 2, 1, 0]);
-        wm.drag_client(Forward).unwrap();
-        assert_eq!(wm.active_workspace().client_ids(), vec![2, 3, 1, 0]);
-        wm.drag_client(Forward).unwrap();
-        assert_eq!(wm.active_workspace().client_ids(), vec![2, 1, 3, 0]);
+        let missing = wm.set_layout_all_workspaces("unknown").unwrap();
+
+        assert_eq!(missing, (0..n).collect::<Vec<_>>());
+        for wix in 0..n {
+            assert_eq!(wm.workspace_layout_symbol(wix).unwrap(), "first");
+        }
     }
 
     #[test]
-    fn cycle_layout() {
+    fn set_workspace_layout_with_unknown_symbol_is_an_error() {
         let mut wm = test_windowmanager(1, vec![]);
 
-        assert_eq!(wm.current_layout_symbol(), "first");
-        wm.cycle_layout(Forward).unwrap();
-        assert_eq!(wm.current_layout_symbol(), "second");
-        wm.cycle_layout(Forward).unwrap();
-        assert_eq!(wm.current_layout_symbol(), "first");
+        assert!(wm.set_workspace_layout(0, "unknown").is_err());
+        assert!(wm.set_workspace_layout(42, "second").is_err());
+        assert!(wm.workspace_layout_symbol(42).is_err());
+        assert_eq!(wm.workspace_layout_symbol(0).unwrap(), "first");
     }
 
     #[test]
@@ -2004,6 +6027,377 @@ mod tests {
         assert_eq!(wm.active_workspace().name(), "2");
     }
 
+    #[test]
+    fn toggle_workspace_is_tracked_per_screen() {
+        let mut wm = test_windowmanager(2, vec![]);
+
+        // screen 0: 0 -> 2
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+
+        // screen 1: 1 -> 3 -> 4
+        wm.focus_screen(&Selector::Index(1));
+        wm.focus_workspace(&Selector::Index(3)).unwrap();
+        wm.focus_workspace(&Selector::Index(4)).unwrap();
+
+        wm.toggle_workspace().unwrap();
+        assert_eq!(wm.screens.get(1).unwrap().wix, 3);
+        assert_eq!(wm.screens.get(0).unwrap().wix, 2);
+
+        wm.focus_screen(&Selector::Index(0));
+        wm.toggle_workspace().unwrap();
+        assert_eq!(wm.screens.get(0).unwrap().wix, 0);
+        assert_eq!(wm.screens.get(1).unwrap().wix, 3);
+    }
+
+    #[test]
+    fn pulling_a_workspace_from_another_screen_records_history_for_both() {
+        let mut wm = test_windowmanager(2, vec![]);
+
+        // screen 0 pulls workspace 1 from screen 1, which is given workspace 0
+        wm.focus_workspace(&Selector::Index(1)).unwrap();
+        assert_eq!(wm.screens.get(1).unwrap().wix, 0);
+
+        wm.focus_screen(&Selector::Index(1));
+        wm.toggle_workspace().unwrap();
+        assert_eq!(wm.screens.get(1).unwrap().wix, 1);
+        assert_eq!(wm.screens.get(0).unwrap().wix, 0);
+    }
+
+    #[test]
+    fn float_focused_centered() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        let id = wm.focused_client_id().unwrap();
+
+        wm.float_focused_centered(0.5).unwrap();
+
+        let screen = wm.screens.focused().region(wm.config.show_bar);
+        let c = wm.clients.get(id).unwrap();
+        let r = c.floating_region().unwrap();
+        assert!(c.floating);
+        assert_eq!((r.w, r.h), (screen.w / 2, screen.h / 2));
+        assert_eq!(r.x - screen.x, (screen.w - r.w) / 2);
+        assert_eq!(r.y - screen.y, (screen.h - r.h) / 2);
+    }
+
+    #[test]
+    fn float_focused_centered_rejects_invalid_ratio() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 1, 0);
+
+        assert!(wm.float_focused_centered(0.0).is_err());
+        assert!(wm.float_focused_centered(1.5).is_err());
+    }
+
+    #[test]
+    fn tile_all_floating() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0);
+        wm.float_focused_centered(0.5).unwrap();
+        wm.clients.modify(20, |c| c.floating = true);
+        wm.toggle_client_fullscreen(&Selector::WinId(10)).unwrap();
+
+        wm.tile_all_floating().unwrap();
+
+        for id in [10, 20, 30].iter() {
+            let c = wm.clients.get(*id).unwrap();
+            assert!(!c.floating, "{} is still floating", id);
+            assert!(!c.is_fullscreen(), "{} is still fullscreen", id);
+            assert_eq!(c.floating_region(), None);
+        }
+    }
+
+    #[test]
+    fn toggle_maximize_floating() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        wm.float_focused_centered(0.5).unwrap();
+        let id = wm.focused_client_id().unwrap();
+        let saved = wm.clients.get(id).unwrap().floating_region().unwrap();
+        let screen = wm.screens.focused().region(wm.config.show_bar);
+
+        wm.toggle_maximize_floating().unwrap();
+        let c = wm.clients.get(id).unwrap();
+        assert!(c.floating);
+        assert!(c.is_maximized());
+        assert_eq!(c.floating_region(), Some(screen));
+
+        wm.toggle_maximize_floating().unwrap();
+        let c = wm.clients.get(id).unwrap();
+        assert!(c.floating);
+        assert!(!c.is_maximized());
+        assert_eq!(c.floating_region(), Some(saved));
+    }
+
+    #[test]
+    fn toggle_maximize_floating_ignores_tiled_clients() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        let id = wm.focused_client_id().unwrap();
+
+        wm.toggle_maximize_floating().unwrap();
+
+        let c = wm.clients.get(id).unwrap();
+        assert!(!c.is_maximized());
+        assert_eq!(c.floating_region(), None);
+    }
+
+    struct FloatNewClients;
+    impl<X: XConn> crate::core::hooks::Hook<X> for FloatNewClients {
+        fn new_client(&mut self, wm: &mut WindowManager<X>, id: Xid) -> Result<()> {
+            wm.clients.modify(id, |c| c.floating = true);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn new_floats_use_workspace_default_floating_geometry() {
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let hooks: Hooks<RecordingXConn> = vec![Box::new(FloatNewClients)];
+        let mut wm =
+            WindowManager::new(conf, RecordingXConn::init(), hooks, logging_error_handler());
+        wm.init().unwrap();
+        wm.workspaces[0].set_default_floating_geometry(Some(FloatingGeometry::Absolute(
+            Region::new(10, 20, 300, 200),
+        )));
+        wm.conn().clear();
+
+        add_n_clients(&mut wm, 1, 0);
+
+        let expected = Region::new(10, 38, 300, 200); // offset from the bar
+        assert_eq!(
+            wm.clients.get(10).unwrap().floating_region(),
+            Some(expected)
+        );
+        let positioned: Vec<Vec<String>> = wm
+            .conn()
+            .calls()
+            .into_iter()
+            .filter(|(m, _)| m == "position_client")
+            .map(|(_, args)| args)
+            .collect();
+        assert_eq!(
+            positioned[0],
+            strings!(10, expected, wm.config.border_px, false)
+        );
+    }
+
+    #[test]
+    fn new_floats_without_workspace_default_keep_requested_geometry() {
+        let hooks: Hooks<MockXConn> = vec![Box::new(FloatNewClients)];
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let mut wm = WindowManager::new(Config::default(), conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+
+        add_n_clients(&mut wm, 1, 0);
+
+        let c = wm.clients.get(10).unwrap();
+        assert!(c.floating);
+        assert_eq!(c.floating_region(), None);
+    }
+
+    fn configure_request_wm() -> WindowManager<RecordingXConn> {
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(
+            conf,
+            RecordingXConn::init(),
+            vec![],
+            logging_error_handler(),
+        );
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 2, 0);
+        wm.conn().clear();
+
+        wm
+    }
+
+    fn positioned(wm: &WindowManager<RecordingXConn>) -> Vec<Vec<String>> {
+        wm.conn()
+            .calls()
+            .into_iter()
+            .filter(|(m, _)| m == "position_client")
+            .map(|(_, args)| args)
+            .collect()
+    }
+
+    #[test]
+    fn configure_request_for_tiled_client_is_overridden() {
+        let mut wm = configure_request_wm();
+        let tiled = wm.clients.get(10).unwrap().last_region().unwrap();
+        let requested = Region::new(5, 5, 100, 100);
+
+        wm.handle_configure_request(10, requested).unwrap();
+
+        assert_eq!(
+            positioned(&wm),
+            vec![strings!(10, tiled, wm.config.border_px, false)]
+        );
+        let c = wm.clients.get(10).unwrap();
+        assert_eq!(c.last_region(), Some(tiled));
+        assert_eq!(c.floating_region(), None);
+    }
+
+    #[test]
+    fn configure_request_for_floating_client_is_applied_and_saved() {
+        let mut wm = configure_request_wm();
+        wm.clients.modify(10, |c| c.floating = true);
+        let requested = Region::new(5, 5, 100, 100);
+
+        wm.handle_configure_request(10, requested).unwrap();
+
+        assert_eq!(
+            positioned(&wm),
+            vec![strings!(10, requested, wm.config.border_px, true)]
+        );
+        assert_eq!(
+            wm.clients.get(10).unwrap().floating_region(),
+            Some(requested)
+        );
+    }
+
+    #[test]
+    fn configure_request_for_unknown_client_is_ignored() {
+        let mut wm = configure_request_wm();
+
+        wm.handle_configure_request(99, Region::new(5, 5, 100, 100))
+            .unwrap();
+
+        assert!(positioned(&wm).is_empty());
+    }
+
+    #[test]
+    fn spawn_rejects_empty_commands() {
+        let wm = wm_with_mock_conn(vec![], vec![]);
+
+        assert!(matches!(wm.spawn("  "), Err(PenroseError::SpawnProc(_))));
+    }
+
+    #[test]
+    fn toggle_bar_updates_screen_sizes() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        let bar_height = wm.config.bar_height;
+        let full = Region::new(0, 0, 1366, 768);
+        let reduced = Region::new(0, bar_height, 1366, 768 - bar_height);
+        assert_eq!(wm.screen_size(0), Some(reduced));
+
+        wm.toggle_bar().unwrap();
+        assert_eq!(wm.screen_size(0), Some(full));
+        assert_eq!(wm.screen_size(1), Some(Region::new(1366, 0, 1366, 768)));
+
+        wm.toggle_bar().unwrap();
+        assert_eq!(wm.screen_size(0), Some(reduced));
+    }
+
+    test_cases! {
+        ignored_lock_modifiers;
+        args: (ignored: Vec<LockModifier>, mask: u16, should_run: bool);
+
+        case: no_locks_held => (vec![], 0, true);
+        case: numlock_ignored => (vec![LockModifier::NumLock], 1 << 4, true);
+        case: numlock_not_ignored => (vec![], 1 << 4, false);
+        case: capslock_ignored => (vec![LockModifier::CapsLock], 1 << 1, true);
+        case: both_ignored => (
+            vec![LockModifier::NumLock, LockModifier::CapsLock], (1 << 4) | (1 << 1), true
+        );
+        case: other_lock_ignored => (vec![LockModifier::CapsLock], 1 << 4, false);
+        case: both_held_numlock_ignored => (vec![LockModifier::NumLock], (1 << 4) | (1 << 1), false);
+        case: both_held_capslock_ignored => (vec![LockModifier::CapsLock], (1 << 4) | (1 << 1), false);
+        case: both_held_by_default => (Config::default().ignored_lock_modifiers, (1 << 4) | (1 << 1), false);
+
+        body: {
+            let code = KeyCode { mask, code: 42 };
+            let conn = TestXConn::new(1, vec![XEvent::KeyPress(code)], vec![]);
+            let conf = Config {
+                layouts: test_layouts(),
+                ignored_lock_modifiers: ignored,
+                ..Default::default()
+            };
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+
+            let ran = Rc::new(Cell::new(false));
+            let r = Rc::clone(&ran);
+            let mut bindings = test_key_bindings();
+            bindings.insert(
+                KeyCode { mask: 0, code: 42 },
+                Box::new(move |_: &mut WindowManager<TestXConn>| {
+                    r.set(true);
+                    Ok(())
+                }),
+            );
+            wm.grab_keys_and_run(bindings, HashMap::new()).unwrap();
+
+            assert_eq!(ran.get(), should_run);
+        }
+    }
+
+    #[test]
+    fn binding_errors_are_passed_to_the_error_handler() {
+        let errors = Rc::new(RefCell::new(vec![]));
+        let handler_errors = Rc::clone(&errors);
+        let error_handler: ErrorHandler =
+            Box::new(move |e: PenroseError| handler_errors.borrow_mut().push(e));
+
+        let code = KeyCode { mask: 0, code: 42 };
+        let conn = TestXConn::new(1, vec![XEvent::KeyPress(code)], vec![]);
+        let conf = Config {
+            layouts: test_layouts(),
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], error_handler);
+        wm.init().unwrap();
+
+        let mut bindings = test_key_bindings();
+        bindings.insert(
+            code,
+            Box::new(|wm: &mut WindowManager<TestXConn>| wm.focus_master()),
+        );
+        wm.grab_keys_and_run(bindings, HashMap::new()).unwrap();
+
+        assert!(matches!(
+            errors.borrow().as_slice(),
+            [PenroseError::EmptyWorkspace(0)]
+        ));
+    }
+
+    #[test]
+    fn mouse_bindings_match_regardless_of_modifier_order() {
+        let held = MouseState::new(
+            MouseButton::Left,
+            vec![ModifierKey::Shift, ModifierKey::Meta],
+        );
+        let e = MouseEvent::new(0, 0, 0, 0, 0, held, MouseEventKind::Press);
+        let conn = TestXConn::new(1, vec![XEvent::MouseEvent(e)], vec![]);
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        let ran = Rc::new(Cell::new(false));
+        let r = Rc::clone(&ran);
+        let mut mouse_bindings = test_mouse_bindings();
+        // not constructed using MouseState::new so the modifiers are not sorted
+        let bound = MouseState {
+            button: MouseButton::Left,
+            modifiers: vec![ModifierKey::Meta, ModifierKey::Shift],
+        };
+        mouse_bindings.insert(
+            (MouseEventKind::Press, bound),
+            Box::new(move |_: &mut WindowManager<TestXConn>, _: &MouseEvent| {
+                r.set(true);
+                Ok(())
+            }),
+        );
+        wm.grab_keys_and_run(test_key_bindings(), mouse_bindings)
+            .unwrap();
+
+        assert!(ran.get());
+    }
+
     #[test]
     fn client_to_workspace() {
         let mut wm = test_windowmanager(1, n_clients(3));
@@ -2048,6 +6442,63 @@ mod tests {
         assert!(!wm.client(&Selector::Focused).unwrap().is_fullscreen(),);
     }
 
+    test_cases! {
+        fullscreen_layout;
+        args: (fullscreen_layout: Option<&str>, steps: Vec<(Xid, bool)>, expected: &str);
+
+        case: disabled => (None, vec![(10, true)], "t");
+        case: unknown_symbol => (Some("missing"), vec![(10, true)], "t");
+        case: enter => (Some("mono"), vec![(10, true)], "mono");
+        case: enter_then_exit => (Some("mono"), vec![(10, true), (10, false)], "t");
+        case: another_remains_fullscreen =>
+            (Some("mono"), vec![(10, true), (20, true), (10, false)], "mono");
+        case: last_fullscreen_exits =>
+            (Some("mono"), vec![(10, true), (20, true), (10, false), (20, false)], "t");
+
+        body: {
+            let conf = Config {
+                layouts: vec![
+                    Layout::new("t", LayoutConf::default(), mock_layout, 1, 0.6),
+                    Layout::new("mono", LayoutConf::default(), mock_layout, 1, 0.6),
+                ],
+                fullscreen_layout: fullscreen_layout.map(|s| s.to_string()),
+                ..Default::default()
+            };
+            let conn = MockXConn::new(test_screens(), vec![], vec![]);
+            let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+            wm.init().unwrap();
+            add_n_clients(&mut wm, 3, 0);
+
+            for (id, fullscreen) in steps {
+                wm.set_fullscreen(id, fullscreen).unwrap();
+            }
+
+            assert_eq!(wm.current_layout_symbol(), expected);
+        }
+    }
+
+    #[test]
+    fn fullscreen_layout_is_restored_when_the_fullscreen_client_is_removed() {
+        let conf = Config {
+            layouts: vec![
+                Layout::new("t", LayoutConf::default(), mock_layout, 1, 0.6),
+                Layout::new("mono", LayoutConf::default(), mock_layout, 1, 0.6),
+            ],
+            fullscreen_layout: Some("mono".into()),
+            ..Default::default()
+        };
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 3, 0);
+
+        wm.set_fullscreen(10, true).unwrap();
+        assert_eq!(wm.current_layout_symbol(), "mono");
+
+        wm.remove_client(10).unwrap();
+        assert_eq!(wm.current_layout_symbol(), "t");
+    }
+
     #[test]
     fn screen() {
         let mut wm = test_windowmanager(2, n_clients(3));
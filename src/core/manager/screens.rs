@@ -1,11 +1,11 @@
 //! State and management of screens being layed out by Penrose.
 use crate::{
     core::{
-        data_types::Region,
+        data_types::{Region, RelativePosition},
         hooks::HookName,
         manager::event::EventAction,
         ring::{Direction, Ring, Selector},
-        screen::Screen,
+        screen::{Screen, ScreenBar},
         xconnection::XState,
     },
     Result,
@@ -18,14 +18,32 @@ pub(super) struct Screens {
     pub(super) inner: Ring<Screen>,
     bar_height: u32,
     top_bar: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    screen_bars: Vec<Option<ScreenBar>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    spanning: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    previous: Option<usize>,
 }
 
 impl Screens {
-    pub fn new(bar_height: u32, top_bar: bool) -> Self {
+    pub fn new(bar_height: u32, top_bar: bool, screen_bars: Vec<Option<ScreenBar>>) -> Self {
         Self {
             inner: Ring::default(),
             bar_height,
             top_bar,
+            screen_bars,
+            spanning: None,
+            previous: None,
+        }
+    }
+
+    // The bar height and position for the screen at 'index', falling back to the global values
+    // if there is no override for it.
+    fn bar_for_screen(&self, index: usize) -> (u32, bool) {
+        match self.screen_bars.get(index) {
+            Some(Some(bar)) => (bar.height, bar.top),
+            _ => (self.bar_height, self.top_bar),
         }
     }
 
@@ -60,6 +78,60 @@ impl Screens {
         self.inner.element(selector)
     }
 
+    // The workspace that is currently spanning all screens. This is cleared implicitly once the
+    // workspace is no longer visible on any screen.
+    pub fn spanning_workspace(&self) -> Option<usize> {
+        self.spanning
+            .filter(|wix| self.visible_workspaces().contains(wix))
+    }
+
+    pub fn set_spanning_workspace(&mut self, wix: Option<usize>) {
+        self.spanning = wix;
+    }
+
+    // The bounding region of all connected screens
+    pub fn combined_region(&self, effective_only: bool) -> Option<Region> {
+        let regions = self.inner.vec_map(|s| s.region(effective_only));
+        let x = regions.iter().map(|r| r.x).min()?;
+        let y = regions.iter().map(|r| r.y).min()?;
+        let x_max = regions.iter().map(|r| r.x + r.w).max()?;
+        let y_max = regions.iter().map(|r| r.y + r.h).max()?;
+
+        Some(Region::new(x, y, x_max - x, y_max - y))
+    }
+
+    // The index of the screen physically next to the focused screen in the given direction. When
+    // there are multiple candidates the closest is chosen, preferring screens that are best
+    // aligned with the focused screen.
+    pub fn screen_in_direction(&self, position: RelativePosition) -> Option<usize> {
+        let (x, y, w, h) = self.focused().region(false).values();
+        let (cx, cy) = ((2 * x + w) as i64, (2 * y + h) as i64);
+
+        self.inner
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.focused_index())
+            .filter_map(|(i, s)| {
+                let (sx, sy, sw, sh) = s.region(false).values();
+                let (scx, scy) = ((2 * sx + sw) as i64, (2 * sy + sh) as i64);
+                let (gap, offset) = match position {
+                    RelativePosition::Left if sx + sw <= x => (x - (sx + sw), scy - cy),
+                    RelativePosition::Right if sx >= x + w => (sx - (x + w), scy - cy),
+                    RelativePosition::Above if sy + sh <= y => (y - (sy + sh), scx - cx),
+                    RelativePosition::Below if sy >= y + h => (sy - (y + h), scx - cx),
+                    _ => return None,
+                };
+                Some((gap, offset.abs(), i))
+            })
+            .min()
+            .map(|(_, _, i)| i)
+    }
+
+    // The index of the screen that was focused before the current one, if it is still connected
+    pub fn previous_index(&self) -> Option<usize> {
+        self.previous.filter(|&i| i < self.n_screens())
+    }
+
     pub fn n_screens(&self) -> usize {
         self.inner.len()
     }
@@ -68,6 +140,11 @@ impl Screens {
         self.inner.get(index).map(|s| s.region(bar_visible))
     }
 
+    // The index of the screen marked as primary by the XConn, if there is one
+    pub fn primary_index(&self) -> Option<usize> {
+        self.inner.index(&Selector::Condition(&|s| s.is_primary()))
+    }
+
     pub fn active_screen_index(&self) -> usize {
         self.inner.focused_index()
     }
@@ -95,15 +172,16 @@ impl Screens {
 
         let mut detected: Vec<Screen> = state.current_screens()?;
 
-        // sort the screens to be in the correct order
-        detected.sort_by_key(|s| s.true_region.x);
+        // sort the screens left to right, breaking ties (stacked monitors) top to bottom
+        detected.sort_by_key(|s| (s.true_region.x, s.true_region.y));
 
-        let detected = detected
+        let detected: Ring<Screen> = detected
             .into_iter()
             .zip(workspace_ordering)
             .enumerate()
             .map(|(ix, (mut s, wix))| {
-                s.update_effective_region(self.bar_height, self.top_bar);
+                let (bar_height, top_bar) = self.bar_for_screen(ix);
+                s.update_effective_region(bar_height, top_bar);
                 trace!(screen = ix, workspace = wix, "setting workspace for screen");
                 s.wix = wix;
 
@@ -113,8 +191,8 @@ impl Screens {
             })
             .collect();
 
-        Ok(if self.inner.as_vec() != detected {
-            self.inner = Ring::new(detected);
+        Ok(if !self.inner.same_elements(&detected) {
+            self.inner = detected;
             vec![
                 EventAction::LayoutVisible,
                 EventAction::RunHook(HookName::ScreenUpdated),
@@ -125,11 +203,16 @@ impl Screens {
     }
 
     pub fn focus_screen(&mut self, sel: &Selector<'_, Screen>) -> Vec<EventAction> {
+        let prev = self.inner.focused_index();
         match self.inner.focus(sel) {
-            Some((true, focused)) => vec![
-                EventAction::SetActiveWorkspace(focused.wix),
-                EventAction::RunHook(HookName::ScreenChange),
-            ],
+            Some((true, focused)) => {
+                let wix = focused.wix;
+                self.previous = Some(prev);
+                vec![
+                    EventAction::SetActiveWorkspace(wix),
+                    EventAction::RunHook(HookName::ScreenChange),
+                ]
+            }
             _ => vec![],
         }
     }
@@ -139,6 +222,7 @@ impl Screens {
         S: XState,
     {
         if !self.inner.would_wrap(direction) {
+            self.previous = Some(self.inner.focused_index());
             self.inner.cycle_focus(direction);
             let focused = self.inner.focused_unchecked();
             state.warp_cursor(None, focused)?;
@@ -167,7 +251,7 @@ mod tests {
 
     #[test]
     fn update_known_screens_generates_events_when_there_is_a_change() {
-        let mut s = Screens::new(10, true);
+        let mut s = Screens::new(10, true, vec![]);
         let conn = MockXConn::new(raw_screens(), vec![], vec![]);
         let events = s.update_known_screens(&conn, 10).unwrap();
 
@@ -182,7 +266,7 @@ mod tests {
 
     #[test]
     fn update_known_screens_doesnt_generates_events_when_screens_are_unchanged() {
-        let mut s = Screens::new(10, true);
+        let mut s = Screens::new(10, true, vec![]);
         let conn = MockXConn::new(raw_screens(), vec![], vec![]);
         s.update_known_screens(&conn, 10).unwrap();
         let events = s.update_known_screens(&conn, 10).unwrap();
@@ -190,9 +274,46 @@ mod tests {
         assert!(events.is_empty());
     }
 
+    #[test]
+    fn update_known_screens_orders_screens_by_position() {
+        let mut s = Screens::new(10, true, vec![]);
+        let detected = vec![
+            Screen::new(Region::new(1366, 768, 1366, 768), 0),
+            Screen::new(Region::new(1366, 0, 1366, 768), 1),
+            Screen::new(Region::new(0, 0, 1366, 768), 2),
+        ];
+        let conn = MockXConn::new(detected, vec![], vec![]);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        let positions: Vec<(u32, u32)> = s
+            .inner
+            .iter()
+            .map(|s| (s.true_region.x, s.true_region.y))
+            .collect();
+        let workspaces: Vec<usize> = s.inner.iter().map(|s| s.wix).collect();
+
+        assert_eq!(positions, vec![(0, 0), (1366, 0), (1366, 768)]);
+        assert_eq!(workspaces, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn primary_screen_is_tracked_after_reordering() {
+        let mut s = Screens::new(10, true, vec![]);
+        let mut primary = Screen::new(Region::new(1366, 0, 1366, 768), 0);
+        primary.set_primary(true);
+        let detected = vec![primary, Screen::new(Region::new(0, 0, 1366, 768), 1)];
+        let conn = MockXConn::new(detected, vec![], vec![]);
+
+        assert_eq!(s.primary_index(), None);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        assert_eq!(s.primary_index(), Some(1));
+        assert!(!s.get(0).unwrap().is_primary());
+    }
+
     #[test]
     fn changing_focus_generates_event_actions() {
-        let mut s = Screens::new(10, true);
+        let mut s = Screens::new(10, true, vec![]);
         let conn = MockXConn::new(raw_screens(), vec![], vec![]);
         s.update_known_screens(&conn, 10).unwrap();
         let events = s.focus_screen(&Selector::Index(1));
@@ -208,7 +329,7 @@ mod tests {
 
     #[test]
     fn changing_focus_only_generates_event_actions_on_change() {
-        let mut s = Screens::new(10, true);
+        let mut s = Screens::new(10, true, vec![]);
         let conn = MockXConn::new(raw_screens(), vec![], vec![]);
         s.update_known_screens(&conn, 10).unwrap();
         let events = s.focus_screen(&Selector::Index(0));
@@ -218,7 +339,7 @@ mod tests {
 
     #[test]
     fn cycle_screen_generates_event_actions() {
-        let mut s = Screens::new(10, true);
+        let mut s = Screens::new(10, true, vec![]);
         let conn = MockXConn::new(raw_screens(), vec![], vec![]);
         s.update_known_screens(&conn, 10).unwrap();
         let events = s.cycle_screen(Direction::Forward, &conn).unwrap();
@@ -234,7 +355,7 @@ mod tests {
 
     #[test]
     fn cycle_screen_does_not_generate_event_actions_when_unable_to_cycle() {
-        let mut s = Screens::new(10, true);
+        let mut s = Screens::new(10, true, vec![]);
         let conn = MockXConn::new(raw_screens(), vec![], vec![]);
         s.update_known_screens(&conn, 10).unwrap();
         let events = s.cycle_screen(Direction::Backward, &conn);
@@ -289,7 +410,10 @@ mod tests {
                     ).collect()
                 ),
                 bar_height,
-                top_bar
+                top_bar,
+                screen_bars: vec![],
+                spanning: None,
+                previous: None,
             };
 
             s.update_known_screens(&conn, n_workspaces).unwrap();
@@ -298,4 +422,67 @@ mod tests {
             assert_eq!(focused, expected);
         }
     }
+
+    #[test]
+    fn screens_use_their_own_bar_config() {
+        let conn = OutputsXConn(test_screens(0, true));
+        let mut s = Screens::new(10, true, vec![None, Some(ScreenBar::new(30, false))]);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        let regions: Vec<Region> = s.inner.iter().map(|s| s.region(true)).collect();
+        assert_eq!(
+            regions,
+            vec![
+                Region::new(0, 10, 1000, 790),
+                Region::new(1000, 0, 1400, 870),
+            ]
+        );
+    }
+
+    #[test]
+    fn screens_without_a_bar_use_their_full_region() {
+        let conn = OutputsXConn(test_screens(0, true));
+        let mut s = Screens::new(10, true, vec![Some(ScreenBar::new(0, true))]);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        let regions: Vec<Region> = s.inner.iter().map(|s| s.region(true)).collect();
+        assert_eq!(
+            regions,
+            vec![
+                Region::new(0, 0, 1000, 800),
+                Region::new(1000, 10, 1400, 890),
+            ]
+        );
+    }
+
+    // A row of two screens on top with a single wide screen below the second of them
+    //   [0][1]
+    //      [ 2  ]
+    test_cases! {
+        screen_in_direction;
+        args: (focused: usize, position: RelativePosition, expected: Option<usize>);
+
+        case: left_of_first => (0, RelativePosition::Left, None);
+        case: right_of_first => (0, RelativePosition::Right, Some(1));
+        case: below_first => (0, RelativePosition::Below, Some(2));
+        case: left_of_second => (1, RelativePosition::Left, Some(0));
+        case: below_second => (1, RelativePosition::Below, Some(2));
+        case: above_second => (1, RelativePosition::Above, None);
+        case: above_third_prefers_aligned => (2, RelativePosition::Above, Some(1));
+        case: left_of_third_is_diagonal => (2, RelativePosition::Left, Some(0));
+
+        body: {
+            let mut s = Screens::new(10, true, vec![]);
+            let detected = vec![
+                Screen::new(Region::new(0, 0, 1000, 800), 0),
+                Screen::new(Region::new(1000, 0, 1000, 800), 1),
+                Screen::new(Region::new(1000, 800, 2000, 800), 2),
+            ];
+            let conn = MockXConn::new(detected, vec![], vec![]);
+            s.update_known_screens(&conn, 10).unwrap();
+            s.focus_screen(&Selector::Index(focused));
+
+            assert_eq!(s.screen_in_direction(position), expected);
+        }
+    }
 }
@@ -1,6 +1,14 @@
 use crate::core::{
     config::Config,
-    manager::{clients::Clients, screens::Screens, workspaces::Workspaces, WindowManager},
+    manager::{
+        clients::Clients,
+        description::{ScreenDescription, StateDescription, WorkspaceDescription},
+        event_log::EventLog,
+        metrics::Metrics,
+        screens::Screens,
+        workspaces::Workspaces,
+        WindowManager,
+    },
     xconnection::XConn,
 };
 use std::ops::{Deref, DerefMut};
@@ -12,6 +20,53 @@ pub struct WmState {
     pub(super) clients: Clients,
     pub(super) screens: Screens,
     pub(super) workspaces: Workspaces,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) metrics: Metrics,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) event_log: EventLog,
+}
+
+impl WmState {
+    /// A read-only [StateDescription] of the current screens, workspaces and clients.
+    ///
+    /// This is intended for debugging and for use in tests: see [StateDescription] for how it
+    /// differs from the state that is serialized on restart.
+    pub fn describe(&self) -> StateDescription {
+        let screens = self
+            .screens
+            .inner
+            .iter()
+            .map(|s| ScreenDescription {
+                workspace: s.wix,
+                region: s.region(false),
+                effective_region: s.region(true),
+                primary: s.is_primary(),
+            })
+            .collect();
+
+        let workspaces = self
+            .workspaces
+            .iter()
+            .map(|ws| WorkspaceDescription {
+                name: ws.name().to_string(),
+                layout: ws.layout_symbol().to_string(),
+                layout_locked: ws.layout_locked(),
+                focused_client: ws.focused_client(),
+                clients: ws
+                    .iter()
+                    .flat_map(|&id| self.clients.get(id))
+                    .map(Into::into)
+                    .collect(),
+            })
+            .collect();
+
+        StateDescription {
+            focused_screen: self.screens.focused_index(),
+            focused_client: self.clients.focused_client_id(),
+            screens,
+            workspaces,
+        }
+    }
 }
 
 impl<X> Deref for WindowManager<X>
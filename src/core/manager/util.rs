@@ -1,6 +1,6 @@
 use crate::{
     core::{
-        data_types::Region,
+        data_types::{Gaps, Region},
         xconnection::{XClientConfig, XState, Xid},
     },
     Result,
@@ -12,19 +12,32 @@ use crate::{
     PenroseError,
 };
 
-pub(super) fn pad_region(region: &Region, gapless: bool, gap_px: u32, border_px: u32) -> Region {
-    let gpx = if gapless { 0 } else { gap_px };
-    let padding = 2 * (border_px + gpx);
+pub(super) fn pad_region(region: &Region, gapless: bool, gaps: Gaps, border_px: u32) -> Region {
+    let g = if gapless { Gaps::default() } else { gaps };
+    let w_padding = g.left + g.right + 2 * border_px;
+    let h_padding = g.top + g.bottom + 2 * border_px;
     let (x, y, w, h) = region.values();
 
     // Check that the resulting size would not be zero or negative
     // Do not allow zero-size as this is chosen by the WM
-    if w <= padding || h <= padding {
+    if w <= w_padding || h <= h_padding {
         warn!("not padding region to avoid integer underflow");
         return *region;
     }
 
-    Region::new(x + gpx, y + gpx, w - padding, h - padding)
+    Region::new(x + g.left, y + g.top, w - w_padding, h - h_padding)
+}
+
+// Transient errors for a window that has already been destroyed should not prevent the remaining
+// clients in a batch from being processed.
+pub(super) fn skip_if_transient(id: Xid, res: Result<()>) -> Result<()> {
+    match res {
+        Err(e) if e.is_transient() => {
+            debug!(id, %e, "window is no longer present: skipping");
+            Ok(())
+        }
+        other => other,
+    }
 }
 
 pub(super) fn position_floating_client<X>(
@@ -37,10 +50,7 @@ where
     X: XClientConfig + XState,
 {
     let default_position = conn.client_geometry(id)?;
-    let (mut x, mut y, w, h) = default_position.values();
-    let (sx, sy, _, _) = screen_region.values();
-    x = if x < sx { sx } else { x };
-    y = if y < sy { sy } else { y };
+    let (x, y, w, h) = default_position.clamp_within(&screen_region).values();
 
     // Check that the resulting size would not be negative
     // Allow zero-size here as it is chosen by the client
@@ -59,6 +69,27 @@ where
     Ok(conn.position_client(id, reg, border_px, false)?)
 }
 
+// Replace the current process with a fresh copy of the running binary, passing through the
+// original command line arguments.
+#[cfg(feature = "serde")]
+pub(super) fn exec_current_binary() -> Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStringExt};
+
+    let to_cstring = |s: std::ffi::OsString| {
+        CString::new(s.into_vec()).map_err(|e| perror!("invalid restart argument: {}", e))
+    };
+
+    let prog = to_cstring(std::env::current_exe()?.into_os_string())?;
+    let args = std::env::args_os()
+        .map(to_cstring)
+        .collect::<Result<Vec<CString>>>()?;
+
+    match nix::unistd::execv(&prog, &args) {
+        Ok(never) => match never {},
+        Err(e) => Err(perror!("unable to re-exec penrose: {}", e)),
+    }
+}
+
 #[cfg(feature = "serde")]
 pub(super) fn validate_hydrated_wm_state<X>(wm: &mut WindowManager<X>) -> Result<()>
 where
@@ -110,7 +141,7 @@ mod tests {
     #[test]
     fn pad_region_centered() {
         let r = Region::new(0, 0, 200, 100);
-        let g = 10;
+        let g = Gaps::uniform(10);
         let b = 3;
         assert_eq!(pad_region(&r, false, g, b), Region::new(10, 10, 174, 74));
         assert_eq!(pad_region(&r, true, g, b), Region::new(0, 0, 194, 94));
@@ -119,19 +150,36 @@ mod tests {
     #[test]
     fn pad_region_tiny() {
         let r = Region::new(0, 0, 3, 3);
-        let g = 10;
+        let g = Gaps::uniform(10);
         let b = 3;
         assert_eq!(pad_region(&r, false, g, b), r);
         assert_eq!(pad_region(&r, true, g, b), r);
     }
 
+    test_cases! {
+        pad_region_asymmetric;
+        args: (gaps: Gaps, expected: Region);
+
+        case: top_only => (Gaps::new(10, 0, 0, 0), Region::new(0, 10, 194, 84));
+        case: bottom_only => (Gaps::new(0, 10, 0, 0), Region::new(0, 0, 194, 84));
+        case: left_only => (Gaps::new(0, 0, 10, 0), Region::new(10, 0, 184, 94));
+        case: right_only => (Gaps::new(0, 0, 0, 10), Region::new(0, 0, 184, 94));
+        case: all_different => (Gaps::new(1, 2, 3, 4), Region::new(3, 1, 187, 91));
+
+        body: {
+            let r = Region::new(0, 0, 200, 100);
+            assert_eq!(pad_region(&r, false, gaps, 3), expected);
+            assert_eq!(pad_region(&r, true, gaps, 3), Region::new(0, 0, 194, 94));
+        }
+    }
+
     #[test]
     fn position_floating() {
         let conn = TestXConn::new(1, vec![], vec![]);
         conn.position_client(0, Region::new(0, 0, 400, 300), 2, false)
             .unwrap();
 
-        position_floating_client(&conn, 0, Region::default(), 2).unwrap();
+        position_floating_client(&conn, 0, Region::new(0, 0, 1000, 800), 2).unwrap();
 
         assert_eq!(
             conn.client_geometry(0).unwrap(),
@@ -139,13 +187,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn position_floating_off_screen() {
+        let conn = TestXConn::new(1, vec![], vec![]);
+        conn.position_client(0, Region::new(900, 700, 400, 300), 2, false)
+            .unwrap();
+
+        position_floating_client(&conn, 0, Region::new(0, 0, 1000, 800), 2).unwrap();
+
+        assert_eq!(
+            conn.client_geometry(0).unwrap(),
+            Region::new(602, 502, 396, 296)
+        );
+    }
+
     #[test]
     fn position_floating_tiny() {
         let conn = TestXConn::new(1, vec![], vec![]);
         conn.position_client(0, Region::new(0, 0, 4, 3), 2, false)
             .unwrap();
 
-        position_floating_client(&conn, 0, Region::default(), 2).unwrap();
+        position_floating_client(&conn, 0, Region::new(0, 0, 1000, 800), 2).unwrap();
 
         assert_eq!(conn.client_geometry(0).unwrap(), Region::new(0, 0, 4, 3));
     }
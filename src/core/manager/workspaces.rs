@@ -129,9 +129,13 @@ impl Workspaces {
         self.inner.len()
     }
 
-    pub fn add_client(&mut self, wix: usize, id: Xid) -> Result<Option<EventAction>> {
+    pub fn add_client(&mut self, wix: usize, id: Xid, focus: bool) -> Result<Option<EventAction>> {
         if let Some(ws) = self.inner.get_mut(wix) {
-            ws.add_client(id, &self.client_insert_point)?;
+            if focus {
+                ws.add_and_focus_client(id, &self.client_insert_point)?;
+            } else {
+                ws.add_client(id, &self.client_insert_point)?;
+            }
             Ok(Some(EventAction::RunHook(
                 HookName::ClientAddedToWorkspace(id, wix),
             )))
@@ -169,15 +173,16 @@ impl Workspaces {
         wix: usize,
         region: Region,
         clients: &[&Client],
+        group_by_class: bool,
     ) -> Result<(LayoutConf, ArrangeActions)> {
         let ws = self
             .inner
-            .get(wix)
+            .get_mut(wix)
             .ok_or_else(|| perror!("attempt to layout unknown workspace: {}", wix))?;
 
         let lc = ws.layout_conf();
         if !lc.floating {
-            Ok((lc, ws.arrange(region, clients)))
+            Ok((lc, ws.arrange(region, clients, group_by_class)))
         } else {
             Ok((
                 lc,
@@ -212,23 +217,30 @@ impl Workspaces {
         });
     }
 
-    pub fn cycle_layout(&mut self, wix: usize, direction: Direction) {
-        self.inner.apply_to(&Selector::Index(wix), |ws| {
-            ws.cycle_layout(direction);
-        });
+    // Returns false if there is no workspace at wix or its layout is locked
+    pub fn cycle_layout(&mut self, wix: usize, direction: Direction) -> bool {
+        match self.inner.get_mut(wix) {
+            Some(ws) if !ws.layout_locked() => {
+                ws.cycle_layout(direction);
+                true
+            }
+            _ => false,
+        }
     }
 
-    pub fn update_max_main(&mut self, wix: usize, change: Change) {
-        self.inner.apply_to(&Selector::Index(wix), |ws| {
-            ws.update_max_main(change);
-        });
+    pub fn update_max_main(&mut self, wix: usize, change: Change) -> bool {
+        match self.inner.get_mut(wix) {
+            Some(ws) => ws.update_max_main(change),
+            None => false,
+        }
     }
 
-    pub fn update_main_ratio(&mut self, wix: usize, change: Change) {
+    pub fn update_main_ratio(&mut self, wix: usize, change: Change) -> bool {
         let step = self.main_ratio_step;
-        self.inner.apply_to(&Selector::Index(wix), |ws| {
-            ws.update_main_ratio(change, step);
-        });
+        match self.inner.get_mut(wix) {
+            Some(ws) => ws.update_main_ratio(change, step),
+            None => false,
+        }
     }
 
     pub fn current_layout_symbol(&self, wix: usize) -> &str {
@@ -325,11 +337,11 @@ mod tests {
         assert_eq!(wss[0].client_ids(), vec![0, 1]);
 
         wss.set_client_insert_point(InsertPoint::First);
-        wss.add_client(0, 2).unwrap();
+        wss.add_client(0, 2, false).unwrap();
         assert_eq!(wss[0].client_ids(), vec![2, 0, 1]);
 
         wss.set_client_insert_point(InsertPoint::Last);
-        wss.add_client(0, 3).unwrap();
+        wss.add_client(0, 3, false).unwrap();
         assert_eq!(wss[0].client_ids(), vec![2, 0, 1, 3]);
     }
 
@@ -338,7 +350,7 @@ mod tests {
         let mut wss = Workspaces::new(vec![test_workspace("test", 1)], 0.1);
         assert_eq!(wss[0].client_ids(), vec![0]);
 
-        let res = wss.add_client(0, 0);
+        let res = wss.add_client(0, 0, false);
         assert!(res.is_err());
     }
 }
@@ -138,6 +138,12 @@ impl<T> Ring<T> {
         self.elements.get(self.focused)
     }
 
+    // Guards against a stale focus index (pointing past the end of a ring that has shrunk)
+    // by falling back to the first element.
+    pub fn focused_or_first(&self) -> Option<&T> {
+        self.focused().or_else(|| self.elements.front())
+    }
+
     pub fn focused_unchecked(&self) -> &T {
         &self.elements[self.focused]
     }
@@ -151,12 +157,30 @@ impl<T> Ring<T> {
     }
 
     pub fn rotate(&mut self, direction: Direction) {
+        self.rotate_n(direction, 1)
+    }
+
+    // Rotate the ring by 'n' steps in a single pass, wrapping if 'n' is larger than the ring
+    pub fn rotate_n(&mut self, direction: Direction, n: usize) {
         if self.elements.is_empty() {
             return;
         }
+        let n = n % self.elements.len();
+        if n == 0 {
+            return;
+        }
         match direction {
-            Direction::Forward => self.elements.rotate_right(1),
-            Direction::Backward => self.elements.rotate_left(1),
+            Direction::Forward => self.elements.rotate_right(n),
+            Direction::Backward => self.elements.rotate_left(n),
+        }
+    }
+
+    // Reverse the order of the ring, with focus following the focused element to its new position
+    pub fn reverse(&mut self) {
+        let len = self.elements.len();
+        self.elements.make_contiguous().reverse();
+        if self.focused < len {
+            self.focused = len - 1 - self.focused;
         }
     }
 
@@ -180,11 +204,33 @@ impl<T> Ring<T> {
         }
     }
 
+    // Move focus directly to the element at `index`, leaving focus unchanged if it is out of
+    // bounds.
+    pub fn focus_index(&mut self, index: usize) -> Option<&T> {
+        if index >= self.elements.len() {
+            return None;
+        }
+        self.focused = index;
+        self.focused()
+    }
+
     pub fn cycle_focus(&mut self, direction: Direction) -> Option<&T> {
         self.focused = self.next_index(direction);
         self.focused()
     }
 
+    // Cycle focus in the given direction, returning the indices of the previously and newly
+    // focused elements.
+    pub fn cycle_focus_with_prev(&mut self, direction: Direction) -> Option<(usize, usize)> {
+        if self.elements.is_empty() {
+            return None;
+        }
+        let prev = self.focused;
+        self.focused = self.next_index(direction);
+
+        Some((prev, self.focused))
+    }
+
     pub fn drag_focused(&mut self, direction: Direction) -> Option<&T> {
         match (self.focused, self.next_index(direction), direction) {
             (0, _, Direction::Backward) => self.rotate(direction),
@@ -195,27 +241,56 @@ impl<T> Ring<T> {
         self.cycle_focus(direction)
     }
 
+    // Swap the focused element with the one matching 's', with focus following the previously
+    // focused element to its new position.
+    pub fn swap_focused_with(&mut self, s: &Selector<'_, T>) -> Option<&T> {
+        let other = self.index(s)?;
+        if self.focused >= self.elements.len() {
+            return None;
+        }
+        self.elements.swap(self.focused, other);
+        self.focused = other;
+
+        self.focused()
+    }
+
+    // Move the focused element to the front of the ring, shifting the elements before it back
+    // by one, with focus following the moved element.
+    pub fn move_focused_to_front(&mut self) -> Option<&T> {
+        let el = self.elements.remove(self.focused)?;
+        self.elements.push_front(el);
+        self.focused = 0;
+
+        self.focused()
+    }
+
     pub fn len(&self) -> usize {
         self.elements.len()
     }
 
-    pub fn insert_at(&mut self, insert_point: &InsertPoint, element: T) {
+    // The index that an element inserted at 'insert_point' will end up at
+    fn resolve_insert_point(&self, insert_point: &InsertPoint) -> usize {
         match insert_point {
-            InsertPoint::Index(ix) => self.elements.insert(*ix, element),
-            InsertPoint::Focused => self.elements.insert(self.focused_index(), element),
-            InsertPoint::First => self.elements.push_front(element),
-            InsertPoint::Last => self.elements.push_back(element),
-            InsertPoint::AfterFocused => {
-                let ix = self.focused_index() + 1;
-                if ix > self.elements.len() {
-                    self.elements.push_back(element)
-                } else {
-                    self.elements.insert(ix, element)
-                }
-            }
+            InsertPoint::Index(ix) => *ix,
+            InsertPoint::Focused => self.focused_index(),
+            InsertPoint::First => 0,
+            InsertPoint::Last => self.elements.len(),
+            InsertPoint::AfterFocused => (self.focused_index() + 1).min(self.elements.len()),
         }
     }
 
+    pub fn insert_at(&mut self, insert_point: &InsertPoint, element: T) {
+        let ix = self.resolve_insert_point(insert_point);
+        self.elements.insert(ix, element);
+    }
+
+    // Insert 'element' at 'insert_point' and move focus to it
+    pub fn insert_and_focus(&mut self, insert_point: &InsertPoint, element: T) {
+        let ix = self.resolve_insert_point(insert_point);
+        self.elements.insert(ix, element);
+        self.focused = ix;
+    }
+
     pub fn insert(&mut self, index: usize, element: T) {
         self.elements.insert(index, element);
     }
@@ -224,10 +299,37 @@ impl<T> Ring<T> {
         self.elements.push_back(element);
     }
 
+    // Append all of 'items' to the back of the ring, leaving focus unchanged
+    pub fn extend_back(&mut self, items: impl IntoIterator<Item = T>) {
+        self.elements.extend(items);
+    }
+
     pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, T> {
         self.elements.iter()
     }
 
+    // Iterate over the ring starting from the focused element, wrapping around to the elements
+    // before it
+    pub fn iter_from_focused(&self) -> impl Iterator<Item = &T> {
+        let focused = self.focused.min(self.elements.len());
+        self.elements
+            .iter()
+            .skip(focused)
+            .chain(self.elements.iter().take(focused))
+    }
+
+    pub fn iter_rev(&self) -> impl Iterator<Item = &T> {
+        self.elements.iter().rev()
+    }
+
+    // Compare the contents of two rings, ignoring which element is focused
+    pub fn same_elements(&self, other: &Ring<T>) -> bool
+    where
+        T: PartialEq,
+    {
+        self.elements == other.elements
+    }
+
     pub fn iter_mut(&mut self) -> std::collections::vec_deque::IterMut<'_, T> {
         self.elements.iter_mut()
     }
@@ -328,19 +430,12 @@ impl<T> Ring<T> {
 
         match s {
             Selector::Focused | Selector::Any => self.focused().map(|t| (true, t)),
-            Selector::Index(i) => {
-                self.focused = *i;
-                self.focused().map(|t| (true, t))
-            }
+            Selector::Index(i) => self.focus_index(*i).map(|t| (true, t)),
             Selector::WinId(_) => None, // ignored
-            Selector::Condition(f) => {
-                if let Some((i, _)) = self.element_by(f) {
-                    self.focused = i;
-                    Some((true, &self.elements[self.focused]))
-                } else {
-                    None
-                }
-            }
+            Selector::Condition(f) => match self.element_by(f) {
+                Some((i, _)) => self.focus_index(i).map(|t| (true, t)),
+                None => None,
+            },
         }
     }
 
@@ -368,6 +463,11 @@ impl<T> Ring<T> {
             }
         }
     }
+
+    // Remove the first element matching 'predicate', clamping focus in the same way as 'remove'
+    pub fn remove_where<P: Fn(&T) -> bool>(&mut self, predicate: P) -> Option<T> {
+        self.remove(&Selector::Condition(&predicate))
+    }
 }
 
 impl<T: PartialEq> Ring<T> {
@@ -403,9 +503,7 @@ impl<T> IndexMut<usize> for Ring<T> {
 impl<T> FromIterator<T> for Ring<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut ring = Ring::new(Vec::new());
-        for element in iter {
-            ring.push(element);
-        }
+        ring.extend_back(iter);
 
         ring
     }
@@ -444,6 +542,24 @@ impl<'a, T> IntoIterator for &'a mut Ring<T> {
 mod tests {
     use super::*;
 
+    test_cases! {
+        focus_index;
+        args: (elements: Vec<u8>, index: usize, expected: Option<u8>, expected_focus: usize);
+
+        case: empty_ring => (vec![], 0, None, 0);
+        case: valid_index => (vec![1, 2, 3], 2, Some(3), 2);
+        case: currently_focused => (vec![1, 2, 3], 1, Some(2), 1);
+        case: out_of_bounds => (vec![1, 2, 3], 3, None, 1);
+
+        body: {
+            let mut r = Ring::new(elements);
+            r.focused = r.len().min(1); // the second element if there is one
+
+            assert_eq!(r.focus_index(index).copied(), expected);
+            assert_eq!(r.focused_index(), expected_focus);
+        }
+    }
+
     #[test]
     fn rotate_holds_focus_but_permutes_order() {
         let mut r = Ring::new(vec![1, 2, 3]);
@@ -497,6 +613,86 @@ mod tests {
         assert_eq!(r.focused(), Some(&1));
     }
 
+    #[test]
+    fn focused_or_first() {
+        let mut r = Ring::new(vec![1, 2, 3]);
+        r.focused = 1;
+        assert_eq!(r.focused_or_first(), Some(&2));
+
+        r.focused = 42;
+        assert_eq!(r.focused(), None);
+        assert_eq!(r.focused_or_first(), Some(&1));
+
+        let empty: Ring<u8> = Ring::new(vec![]);
+        assert_eq!(empty.focused_or_first(), None);
+    }
+
+    #[test]
+    fn iter_rev() {
+        let r = Ring::new(vec![1, 2, 3]);
+        assert_eq!(r.iter_rev().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    }
+
+    #[test]
+    fn extend_back_preserves_focus() {
+        let mut r = Ring::new(vec![1, 2, 3]);
+        r.focused = 1;
+
+        r.extend_back(vec![4, 5]);
+
+        assert_eq!(r.as_vec(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(r.focused(), Some(&2));
+    }
+
+    #[test]
+    fn extend_back_on_empty_ring() {
+        let mut r: Ring<u8> = Ring::new(vec![]);
+
+        r.extend_back(1..4);
+
+        assert_eq!(r.as_vec(), vec![1, 2, 3]);
+        assert_eq!(r.focused(), Some(&1));
+    }
+
+    #[test]
+    fn same_elements_ignores_focus() {
+        let mut r = Ring::new(vec![1, 2, 3]);
+        let mut other = Ring::new(vec![1, 2, 3]);
+        r.focused = 0;
+        other.focused = 2;
+
+        assert!(r.same_elements(&other));
+        assert_ne!(r, other);
+    }
+
+    #[test]
+    fn same_elements_with_different_contents() {
+        let r = Ring::new(vec![1, 2, 3]);
+
+        assert!(!r.same_elements(&Ring::new(vec![3, 2, 1])));
+        assert!(!r.same_elements(&Ring::new(vec![1, 2])));
+    }
+
+    #[test]
+    fn swap_focused_with() {
+        let mut r = Ring::new(vec![1, 2, 3, 4]);
+        r.focused = 1;
+
+        assert_eq!(
+            r.swap_focused_with(&Selector::Condition(&|e| *e == 4)),
+            Some(&2)
+        );
+        assert_eq!(r.as_vec(), vec![1, 4, 3, 2]);
+        assert_eq!(r.focused_index(), 3);
+
+        assert_eq!(
+            r.swap_focused_with(&Selector::Condition(&|e| *e == 42)),
+            None
+        );
+        assert_eq!(r.as_vec(), vec![1, 4, 3, 2]);
+        assert_eq!(r.focused_index(), 3);
+    }
+
     #[test]
     fn remove_focused() {
         let mut r = Ring::new(vec![1, 2, 3]);
@@ -633,6 +829,141 @@ mod tests {
         assert_eq!(r.as_vec(), vec![1, 4, 5, 0, 0, 3, 2, 6]);
     }
 
+    test_cases! {
+        insert_and_focus;
+        args: (initial: Vec<u8>, focused: usize, ip: InsertPoint, expected: Vec<u8>, expected_focus: usize);
+
+        case: first => (vec![1, 2, 3], 1, InsertPoint::First, vec![0, 1, 2, 3], 0);
+        case: last => (vec![1, 2, 3], 1, InsertPoint::Last, vec![1, 2, 3, 0], 3);
+        case: index => (vec![1, 2, 3], 0, InsertPoint::Index(2), vec![1, 2, 0, 3], 2);
+        case: index_at_end => (vec![1, 2, 3], 0, InsertPoint::Index(3), vec![1, 2, 3, 0], 3);
+        case: focused => (vec![1, 2, 3], 1, InsertPoint::Focused, vec![1, 0, 2, 3], 1);
+        case: after_focused => (vec![1, 2, 3], 1, InsertPoint::AfterFocused, vec![1, 2, 0, 3], 2);
+        case: after_focused_at_end => (vec![1, 2, 3], 2, InsertPoint::AfterFocused, vec![1, 2, 3, 0], 3);
+        case: empty_first => (vec![], 0, InsertPoint::First, vec![0], 0);
+        case: empty_after_focused => (vec![], 0, InsertPoint::AfterFocused, vec![0], 0);
+
+        body: {
+            let mut r = Ring::new(initial);
+            r.focused = focused;
+            r.insert_and_focus(&ip, 0);
+
+            assert_eq!(r.as_vec(), expected);
+            assert_eq!(r.focused_index(), expected_focus);
+            assert_eq!(r.focused(), Some(&0));
+        }
+    }
+
+    test_cases! {
+        rotate_n;
+        args: (initial: Vec<u8>, direction: Direction, n: usize, expected: Vec<u8>);
+
+        case: forward => (vec![1, 2, 3, 4], Direction::Forward, 2, vec![3, 4, 1, 2]);
+        case: backward => (vec![1, 2, 3, 4], Direction::Backward, 1, vec![2, 3, 4, 1]);
+        case: forward_wrapping => (vec![1, 2, 3, 4], Direction::Forward, 5, vec![4, 1, 2, 3]);
+        case: backward_wrapping => (vec![1, 2, 3, 4], Direction::Backward, 11, vec![4, 1, 2, 3]);
+        case: multiple_of_len => (vec![1, 2, 3, 4], Direction::Forward, 8, vec![1, 2, 3, 4]);
+        case: zero => (vec![1, 2, 3, 4], Direction::Forward, 0, vec![1, 2, 3, 4]);
+        case: empty => (vec![], Direction::Backward, 3, vec![]);
+
+        body: {
+            let mut r = Ring::new(initial);
+            r.rotate_n(direction, n);
+
+            assert_eq!(r.as_vec(), expected);
+        }
+    }
+
+    test_cases! {
+        iter_from_focused;
+        args: (focused: usize, expected: Vec<u8>);
+
+        case: first => (0, vec![1, 2, 3, 4]);
+        case: middle => (2, vec![3, 4, 1, 2]);
+        case: last => (3, vec![4, 1, 2, 3]);
+
+        body: {
+            let mut r = Ring::new(vec![1, 2, 3, 4]);
+            r.focused = focused;
+
+            assert_eq!(r.iter_from_focused().copied().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn iter_from_focused_when_empty() {
+        let r: Ring<u8> = Ring::new(vec![]);
+        assert_eq!(r.iter_from_focused().count(), 0);
+    }
+
+    #[test]
+    fn iter_from_focused_borrows_elements() {
+        #[derive(Debug, PartialEq)]
+        struct NotClone(u8);
+
+        let mut r = Ring::new(vec![NotClone(1), NotClone(2), NotClone(3)]);
+        r.focused = 1;
+        let expected: Vec<&NotClone> =
+            vec![r.get(1).unwrap(), r.get(2).unwrap(), r.get(0).unwrap()];
+
+        let yielded: Vec<&NotClone> = r.iter_from_focused().collect();
+        assert_eq!(yielded, expected);
+        assert!(yielded
+            .iter()
+            .zip(expected)
+            .all(|(a, b)| std::ptr::eq(*a, b)));
+    }
+
+    test_cases! {
+        remove_where;
+        args: (focused: usize, target: u8, removed: Option<u8>, expected: Vec<u8>, expected_focus: usize);
+
+        case: before_focus => (2, 1, Some(1), vec![2, 3, 4], 1);
+        case: at_focus => (1, 2, Some(2), vec![1, 3, 4], 1);
+        case: after_focus => (1, 4, Some(4), vec![1, 2, 3], 1);
+        case: last_while_focused => (3, 4, Some(4), vec![1, 2, 3], 2);
+        case: no_match => (2, 9, None, vec![1, 2, 3, 4], 2);
+
+        body: {
+            let mut r = Ring::new(vec![1, 2, 3, 4]);
+            r.focused = focused;
+
+            assert_eq!(r.remove_where(|&e| e == target), removed);
+            assert_eq!(r.as_vec(), expected);
+            assert_eq!(r.focused_index(), expected_focus);
+        }
+    }
+
+    #[test]
+    fn remove_where_only_removes_the_first_match() {
+        let mut r = Ring::new(vec![1, 2, 1, 2]);
+
+        assert_eq!(r.remove_where(|&e| e == 2), Some(2));
+        assert_eq!(r.as_vec(), vec![1, 1, 2]);
+    }
+
+    test_cases! {
+        cycle_focus_with_prev;
+        args: (elements: Vec<u8>, focused: usize, direction: Direction, expected: Option<(usize, usize)>);
+
+        case: forward => (vec![1, 2, 3], 0, Direction::Forward, Some((0, 1)));
+        case: backward => (vec![1, 2, 3], 1, Direction::Backward, Some((1, 0)));
+        case: forward_wrapping => (vec![1, 2, 3], 2, Direction::Forward, Some((2, 0)));
+        case: backward_wrapping => (vec![1, 2, 3], 0, Direction::Backward, Some((0, 2)));
+        case: single_element => (vec![1], 0, Direction::Forward, Some((0, 0)));
+        case: empty => (vec![], 0, Direction::Forward, None);
+
+        body: {
+            let mut r = Ring::new(elements);
+            r.focused = focused;
+
+            assert_eq!(r.cycle_focus_with_prev(direction), expected);
+            if let Some((_, new)) = expected {
+                assert_eq!(r.focused_index(), new);
+            }
+        }
+    }
+
     #[test]
     fn vec_map() {
         let contents = vec!["this", "is", "a", "lot", "nicer"];
@@ -9,6 +9,28 @@ pub struct Screen {
     pub wix: usize,
     pub(crate) true_region: Region,
     effective_region: Region,
+    #[cfg_attr(feature = "serde", serde(default))]
+    primary: bool,
+}
+
+/// The space reserved for a status bar on an individual screen, overriding the `bar_height` and
+/// `top_bar` values set in the [Config][1].
+///
+/// [1]: crate::core::config::Config
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ScreenBar {
+    /// The height of the space to reserve in pixels (0 for no bar)
+    pub height: u32,
+    /// Whether the space is reserved at the top or bottom of the screen
+    pub top: bool,
+}
+
+impl ScreenBar {
+    /// Create a new ScreenBar
+    pub fn new(height: u32, top: bool) -> Self {
+        Self { height, top }
+    }
 }
 
 impl Screen {
@@ -18,6 +40,7 @@ impl Screen {
             true_region: region,
             effective_region: region,
             wix,
+            primary: false,
         }
     }
 
@@ -47,4 +70,14 @@ impl Screen {
     pub fn contains(&self, p: Point) -> bool {
         self.true_region.contains_point(&p)
     }
+
+    /// Whether or not this screen is the primary output as reported by RandR
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /// Mark this screen as being (or not being) the primary output
+    pub fn set_primary(&mut self, primary: bool) {
+        self.primary = primary;
+    }
 }
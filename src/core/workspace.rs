@@ -10,8 +10,8 @@
 use crate::{
     core::{
         client::Client,
-        data_types::{Change, Region, ResizeAction},
-        layout::{Layout, LayoutConf},
+        data_types::{Change, FloatingGeometry, Region, ResizeAction},
+        layout::{apply_size_deltas, Layout, LayoutConf},
         ring::{Direction, InsertPoint, Ring, Selector},
         xconnection::Xid,
     },
@@ -21,8 +21,7 @@ use crate::{
 #[cfg(feature = "serde")]
 use crate::{core::layout::LayoutFunc, PenroseError};
 
-#[cfg(feature = "serde")]
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap};
 
 pub(crate) struct ArrangeActions {
     pub(crate) actions: Vec<ResizeAction>,
@@ -43,6 +42,17 @@ pub struct Workspace {
     name: String,
     clients: Ring<Xid>,
     layouts: Ring<Layout>,
+    default_floating: Option<FloatingGeometry>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    size_deltas: HashMap<Xid, i32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    previous_focus: Option<Xid>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pre_fullscreen_layout: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    group_heads: HashMap<String, Xid>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    layout_locked: bool,
 }
 
 impl Workspace {
@@ -58,6 +68,12 @@ impl Workspace {
             name: name.into(),
             clients: Ring::new(Vec::new()),
             layouts: Ring::new(layouts),
+            default_floating: None,
+            size_deltas: HashMap::new(),
+            previous_focus: None,
+            pre_fullscreen_layout: None,
+            group_heads: HashMap::new(),
+            layout_locked: false,
         }
     }
 
@@ -70,6 +86,18 @@ impl Workspace {
         self.name = name.into();
     }
 
+    /// The position used for new floating clients on this workspace that do not already have
+    /// a floating position of their own.
+    pub fn default_floating_geometry(&self) -> Option<FloatingGeometry> {
+        self.default_floating
+    }
+
+    /// Set the position used for new floating clients on this workspace. If `None`, new
+    /// floating clients are placed where they request to be.
+    pub fn set_default_floating_geometry(&mut self, geometry: Option<FloatingGeometry>) {
+        self.default_floating = geometry;
+    }
+
     #[cfg(feature = "serde")]
     pub(crate) fn restore_layout_functions(
         &mut self,
@@ -119,6 +147,44 @@ impl Workspace {
         self.clients.iter()
     }
 
+    /// Iterate over the clients on this workspace in reverse position order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// let ids: Vec<Xid> = workspace.iter_rev().map(|id| *id).collect();
+    ///
+    /// assert_eq!(ids, vec![4, 3, 2, 1, 0]);
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 5)).unwrap();
+    /// ```
+    pub fn iter_rev(&self) -> impl Iterator<Item = &Xid> {
+        self.clients.iter_rev()
+    }
+
+    /// Iterate over the clients on this workspace starting from the focused client and wrapping
+    /// around to the clients before it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// workspace.focus_client(3);
+    /// let ids: Vec<Xid> = workspace.iter_from_focused().map(|id| *id).collect();
+    ///
+    /// assert_eq!(ids, vec![3, 4, 0, 1, 2]);
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 5)).unwrap();
+    /// ```
+    pub fn iter_from_focused(&self) -> impl Iterator<Item = &Xid> {
+        self.clients.iter_from_focused()
+    }
+
     /// The ordered list of [Client] IDs currently contained in this workspace
     ///
     /// # Example
@@ -135,6 +201,25 @@ impl Workspace {
         self.clients.as_vec()
     }
 
+    /// The position of the client with the given id in the stack of this workspace, if it is
+    /// present.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// assert_eq!(workspace.client_index(0), Some(0));
+    /// assert_eq!(workspace.client_index(3), Some(3));
+    /// assert_eq!(workspace.client_index(42), None);
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 5)).unwrap();
+    /// ```
+    pub fn client_index(&self, id: Xid) -> Option<usize> {
+        self.clients.index(&Selector::Condition(&|&c| c == id))
+    }
+
     /// A reference to the currently focused client if there is one
     ///
     /// # Example
@@ -151,10 +236,10 @@ impl Workspace {
     /// # example(test_workspace("example", 5)).unwrap();
     /// ```
     pub fn focused_client(&self) -> Option<Xid> {
-        self.clients.focused().copied()
+        self.clients.focused_or_first().copied()
     }
 
-    /// Add a new client to this workspace at the top of the stack and focus it
+    /// Add a new client to this workspace at the given [InsertPoint] without changing focus
     ///
     /// # Example
     ///
@@ -173,11 +258,43 @@ impl Workspace {
     /// # example(test_workspace("example", 1)).unwrap();
     /// ```
     pub fn add_client(&mut self, id: Xid, ip: &InsertPoint) -> Result<()> {
+        self.check_not_present(id)?;
+        self.clients.insert_at(ip, id);
+
+        Ok(())
+    }
+
+    /// Add a new client to this workspace at the given [InsertPoint] and focus it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> penrose::Result<()> {
+    /// assert_eq!(workspace.focused_client(), Some(0));
+    ///
+    /// workspace.add_and_focus_client(2, &InsertPoint::First)?;
+    /// assert_eq!(workspace.client_ids(), vec![2, 0, 1]);
+    /// assert_eq!(workspace.focused_client(), Some(2));
+    /// assert_eq!(workspace.focus_last(), Some(0));
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 2)).unwrap();
+    /// ```
+    pub fn add_and_focus_client(&mut self, id: Xid, ip: &InsertPoint) -> Result<()> {
+        self.check_not_present(id)?;
+        let prev = self.clients.focused().copied();
+        self.clients.insert_and_focus(ip, id);
+        self.record_focus_change(prev);
+
+        Ok(())
+    }
+
+    fn check_not_present(&self, id: Xid) -> Result<()> {
         let existing = self.clients.element(&Selector::Condition(&|c| *c == id));
         if existing.is_some() {
             return Err(perror!("{} is already in this workspace", id));
         }
-        self.clients.insert_at(ip, id);
 
         Ok(())
     }
@@ -201,10 +318,71 @@ impl Workspace {
     pub fn focus_client(&mut self, id: Xid) -> Option<Xid> {
         let prev = self.clients.focused().copied();
         self.clients.focus(&Selector::Condition(&|c| *c == id));
+        self.record_focus_change(prev);
 
         prev
     }
 
+    /// Focus the client that was focused on this workspace before the current one, returning its
+    /// id. Repeated calls toggle between the two most recently focused clients. Returns `None` if
+    /// there is no previous client or it is no longer on this workspace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// assert_eq!(workspace.focus_last(), None);
+    ///
+    /// workspace.focus_client(3);
+    /// assert_eq!(workspace.focus_last(), Some(0));
+    /// assert_eq!(workspace.focus_last(), Some(3));
+    /// assert_eq!(workspace.focused_client(), Some(3));
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 5)).unwrap();
+    /// ```
+    pub fn focus_last(&mut self) -> Option<Xid> {
+        let id = self
+            .previous_focus
+            .filter(|&id| self.clients.iter().any(|&c| c == id))?;
+        self.focus_client(id);
+
+        Some(id)
+    }
+
+    fn record_focus_change(&mut self, prev: Option<Xid>) {
+        let current = self.clients.focused().copied();
+        if prev.is_some() && prev != current {
+            self.previous_focus = prev;
+        }
+    }
+
+    /// Focus the master (first) client on this workspace, returning its id. Returns `None` if
+    /// this workspace is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// workspace.focus_client(3);
+    /// assert_eq!(workspace.focused_client(), Some(3));
+    ///
+    /// assert_eq!(workspace.focus_master(), Some(0));
+    /// assert_eq!(workspace.focused_client(), Some(0));
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 5)).unwrap();
+    /// ```
+    pub fn focus_master(&mut self) -> Option<Xid> {
+        let prev = self.clients.focused().copied();
+        let id = self.clients.focus(&Selector::Index(0)).map(|(_, &id)| id);
+        self.record_focus_change(prev);
+
+        id
+    }
+
     /// Remove a target client, retaining focus at the same position in the stack.
     /// Returns the removed client if there was one to remove.
     ///
@@ -225,7 +403,11 @@ impl Workspace {
     /// # example(test_workspace("example", 5)).unwrap();
     /// ```
     pub fn remove_client(&mut self, id: Xid) -> Option<Xid> {
-        self.clients.remove(&Selector::Condition(&|c| *c == id))
+        self.size_deltas.remove(&id);
+        if self.previous_focus == Some(id) {
+            self.previous_focus = None;
+        }
+        self.clients.remove_where(|&c| c == id)
     }
 
     /// Remove the currently focused client, keeping focus at the same position in the stack.
@@ -248,20 +430,34 @@ impl Workspace {
     /// # example(test_workspace("example", 2)).unwrap();
     /// ```
     pub fn remove_focused_client(&mut self) -> Option<Xid> {
-        self.clients.remove(&Selector::Focused)
+        let id = self.clients.remove(&Selector::Focused)?;
+        self.size_deltas.remove(&id);
+        if self.previous_focus == Some(id) {
+            self.previous_focus = None;
+        }
+        Some(id)
     }
 
     // Run the current layout function, generating a list of resize actions to be
-    // applied byt the window manager.
+    // applied byt the window manager. If group_by_class is set then only one tiled client for
+    // each WM_CLASS is passed to the layout and the rest of each group are hidden.
     pub(crate) fn arrange(
-        &self,
+        &mut self,
         screen_region: Region,
         managed_workspace_clients: &[&Client],
+        group_by_class: bool,
     ) -> ArrangeActions {
         if self.clients.len() > 0 {
+            let (floating, tiled): (Vec<&Client>, Vec<&Client>) = managed_workspace_clients
+                .iter()
+                .filter(|c| c.wm_managed)
+                .partition(|c| c.floating);
+            let (tiled, hidden) = if group_by_class {
+                self.collapse_class_groups(tiled)
+            } else {
+                (tiled, vec![])
+            };
             let layout = self.layouts.focused_unchecked();
-            let (floating, tiled): (Vec<&Client>, Vec<&Client>) =
-                managed_workspace_clients.iter().partition(|c| c.floating);
 
             debug!(
                 layout = ?layout.symbol,
@@ -270,8 +466,12 @@ impl Workspace {
                 "applying layout",
             );
 
+            let mut actions = layout.arrange(&tiled, self.focused_client(), &screen_region);
+            apply_size_deltas(&mut actions, &self.size_deltas);
+            actions.extend(hidden.into_iter().map(|id| (id, None)));
+
             ArrangeActions {
-                actions: layout.arrange(&tiled, self.focused_client(), &screen_region),
+                actions,
                 floating: floating.iter().map(|c| c.id()).collect(),
             }
         } else {
@@ -282,9 +482,39 @@ impl Workspace {
         }
     }
 
+    // Pick a single client to display for each WM_CLASS, returning the chosen clients in their
+    // original order along with the IDs of the rest. The focused client is always chosen and
+    // remains the head of its group until another member is focused. Clients without a
+    // WM_CLASS are never grouped.
+    fn collapse_class_groups<'a>(&mut self, tiled: Vec<&'a Client>) -> (Vec<&'a Client>, Vec<Xid>) {
+        if let Some(c) = tiled.iter().find(|c| Some(c.id) == self.focused_client()) {
+            if let Some(class) = c.wm_class.first() {
+                self.group_heads.insert(class.clone(), c.id);
+            }
+        }
+
+        let heads = &mut self.group_heads;
+        heads.retain(|class, id| {
+            tiled
+                .iter()
+                .any(|c| c.id == *id && c.wm_class.first() == Some(class))
+        });
+
+        let (shown, hidden): (Vec<&Client>, Vec<&Client>) =
+            tiled.into_iter().partition(|c| match c.wm_class.first() {
+                Some(class) => *heads.entry(class.clone()).or_insert(c.id) == c.id,
+                None => true,
+            });
+
+        (shown, hidden.iter().map(|c| c.id).collect())
+    }
+
     /// Set the active layout by symbol name if it is available. Returns a reference to active
     /// layout if it was able to be set.
     ///
+    /// Returns `None` without making any changes if the layout for this workspace is
+    /// [locked][Workspace::set_layout_locked].
+    ///
     /// # Example
     ///
     /// ```
@@ -302,13 +532,162 @@ impl Workspace {
     /// # example(test_workspace("example", 2)).unwrap();
     /// ```
     pub fn try_set_layout(&mut self, symbol: &str) -> Option<&Layout> {
+        if self.layout_locked {
+            return None;
+        }
+        self.focus_layout(symbol)
+    }
+
+    // Set the active layout by symbol regardless of whether or not the layout is locked
+    fn focus_layout(&mut self, symbol: &str) -> Option<&Layout> {
         self.layouts
             .focus(&Selector::Condition(&|l| l.symbol == symbol))
             .map(|(_, layout)| layout)
     }
 
+    /// Set the active layout by its position in the list of layouts for this workspace. Returns
+    /// a reference to the active layout if it was able to be set.
+    ///
+    /// Unlike [cycle_layout][Workspace::cycle_layout], layouts that are excluded from cycling can
+    /// be selected this way. Returns `None` if the layout for this workspace is
+    /// [locked][Workspace::set_layout_locked].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// assert_eq!(workspace.layout_symbol(), "first");
+    ///
+    /// assert!(workspace.set_layout_by_index(1).is_some());
+    /// assert_eq!(workspace.layout_symbol(), "second");
+    ///
+    /// assert!(workspace.set_layout_by_index(42).is_none());
+    /// assert_eq!(workspace.layout_symbol(), "second");
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 2)).unwrap();
+    /// ```
+    pub fn set_layout_by_index(&mut self, index: usize) -> Option<&Layout> {
+        if self.layout_locked {
+            return None;
+        }
+        self.layouts.focus_index(index)
+    }
+
+    /// Whether or not the active layout for this workspace is currently locked.
+    pub fn layout_locked(&self) -> bool {
+        self.layout_locked
+    }
+
+    /// Lock or unlock the active layout for this workspace.
+    ///
+    /// While locked, [cycle_layout][Workspace::cycle_layout],
+    /// [try_set_layout][Workspace::try_set_layout] and
+    /// [set_layout_by_index][Workspace::set_layout_by_index] leave the active layout unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// workspace.set_layout_locked(true);
+    /// assert_eq!(workspace.cycle_layout(Forward), "first");
+    /// assert!(workspace.try_set_layout("second").is_none());
+    ///
+    /// workspace.set_layout_locked(false);
+    /// assert_eq!(workspace.cycle_layout(Forward), "second");
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 2)).unwrap();
+    /// ```
+    pub fn set_layout_locked(&mut self, locked: bool) {
+        self.layout_locked = locked;
+    }
+
+    /// Add a new [Layout] to the end of the list of layouts available on this workspace. The
+    /// active layout is unchanged.
+    pub fn add_layout(&mut self, layout: Layout) {
+        self.layouts.insert_at(&InsertPoint::Last, layout);
+    }
+
+    /// Remove the [Layout] with the given symbol from this workspace, returning it if it was
+    /// present.
+    ///
+    /// A workspace always has at least one layout so this returns `None` without making any
+    /// changes if `symbol` is the only remaining layout. If the active layout is removed then
+    /// the next layout (or the previous one if it was the last) becomes active.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// assert_eq!(workspace.layout_symbol(), "first");
+    ///
+    /// assert!(workspace.remove_layout("first").is_some());
+    /// assert_eq!(workspace.layout_symbol(), "second");
+    ///
+    /// assert!(workspace.remove_layout("second").is_none());
+    /// assert_eq!(workspace.layout_symbol(), "second");
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 2)).unwrap();
+    /// ```
+    pub fn remove_layout(&mut self, symbol: &str) -> Option<Layout> {
+        if self.layouts.len() <= 1 {
+            return None;
+        }
+
+        let ix = self
+            .layouts
+            .index(&Selector::Condition(&|l| l.symbol == symbol))?;
+        let active = self.layouts.focused_index();
+        let removed = self.layouts.remove(&Selector::Index(ix))?;
+
+        let focus = match ix.cmp(&active) {
+            Ordering::Less => active - 1,
+            Ordering::Equal => ix.min(self.layouts.len() - 1),
+            Ordering::Greater => active,
+        };
+        self.layouts.focus_index(focus);
+
+        Some(removed)
+    }
+
+    // Switch to the layout with the given symbol while a client is fullscreen, remembering the
+    // current layout so that it can be restored. Returns true if the layout was changed.
+    pub(crate) fn enter_fullscreen_layout(&mut self, symbol: &str) -> bool {
+        if self.pre_fullscreen_layout.is_some() {
+            return false;
+        }
+
+        let prev = self.layout_symbol().to_string();
+        if prev == symbol || self.try_set_layout(symbol).is_none() {
+            return false;
+        }
+        self.pre_fullscreen_layout = Some(prev);
+
+        true
+    }
+
+    // Restore the layout that was active before enter_fullscreen_layout was called. Returns true
+    // if the layout was changed.
+    pub(crate) fn restore_pre_fullscreen_layout(&mut self) -> bool {
+        match self.pre_fullscreen_layout.take() {
+            Some(symbol) => self.focus_layout(&symbol).is_some(),
+            None => false,
+        }
+    }
+
     /// Cycle through the available layouts on this workspace
     ///
+    /// Layouts with [cycle_exclude][1] set are skipped. If every other layout is excluded, or the
+    /// layout for this workspace is [locked][Workspace::set_layout_locked], then the current layout
+    /// is left unchanged.
+    ///
+    /// [1]: crate::core::layout::LayoutConf::cycle_exclude
+    ///
     /// # Example
     ///
     /// ```
@@ -322,7 +701,15 @@ impl Workspace {
     /// # example(test_workspace("example", 2)).unwrap();
     /// ```
     pub fn cycle_layout(&mut self, direction: Direction) -> &str {
-        self.layouts.cycle_focus(direction);
+        if self.layout_locked {
+            return self.layout_symbol();
+        }
+        for _ in 0..self.layouts.len() {
+            self.layouts.cycle_focus(direction);
+            if !self.layouts.focused_unchecked().conf.cycle_exclude {
+                break;
+            }
+        }
         self.layout_symbol()
     }
 
@@ -377,15 +764,53 @@ impl Workspace {
     /// # example(test_workspace("example", 3)).unwrap();
     /// ```
     pub fn cycle_client(&mut self, direction: Direction) -> Option<(Xid, Xid)> {
+        self.move_focus(direction)
+    }
+
+    /// Move focus to the next client in the given direction without changing the order of the
+    /// stack, returning the newly focused client id. Returns `None` if focus did not change.
+    ///
+    /// Focus will not wrap from one end of the stack to the other if the current layout does not
+    /// [allow wrapping][1].
+    ///
+    /// [1]: crate::core::layout::LayoutConf::allow_wrapping
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// assert_eq!(workspace.focused_client(), Some(0));
+    /// assert_eq!(workspace.focus_direction(Backward), Some(2));
+    /// assert_eq!(workspace.focus_direction(Backward), Some(1));
+    /// assert_eq!(workspace.client_ids(), vec![0, 1, 2]);
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 3)).unwrap();
+    /// ```
+    pub fn focus_direction(&mut self, direction: Direction) -> Option<Xid> {
+        self.move_focus(direction).map(|(_, new)| new)
+    }
+
+    // Whether or not moving in 'direction' is disallowed because it would wrap around the ends of
+    // the stack when the current layout does not allow wrapping.
+    fn wrapping_blocked(&self, direction: Direction) -> bool {
+        !self.layout_conf().allow_wrapping && self.clients.would_wrap(direction)
+    }
+
+    // Move focus one step through the stack, returning the previous and new focused client ids
+    // if focus changed.
+    fn move_focus(&mut self, direction: Direction) -> Option<(Xid, Xid)> {
         if self.clients.len() < 2 {
             return None; // need at least two clients to cycle
         }
-        if !self.layout_conf().allow_wrapping && self.clients.would_wrap(direction) {
+        if self.wrapping_blocked(direction) {
             return None;
         }
 
-        let prev = *self.clients.focused()?;
-        let new = *self.clients.cycle_focus(direction)?;
+        let (prev_ix, new_ix) = self.clients.cycle_focus_with_prev(direction)?;
+        let (prev, new) = (*self.clients.get(prev_ix)?, *self.clients.get(new_ix)?);
+        self.record_focus_change(Some(prev));
 
         if prev != new {
             Some((prev, new))
@@ -412,12 +837,66 @@ impl Workspace {
     /// # example(test_workspace("example", 3)).unwrap();
     /// ```
     pub fn drag_client(&mut self, direction: Direction) -> Option<Xid> {
-        if !self.layout_conf().allow_wrapping && self.clients.would_wrap(direction) {
+        if self.wrapping_blocked(direction) {
             return None;
         }
         self.clients.drag_focused(direction).copied()
     }
 
+    /// Swap the focused client with the client `id`, retaining focus on the previously focused
+    /// client in its new position. Returns the focused client id, or `None` if `id` is not on
+    /// this workspace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// assert_eq!(workspace.client_ids(), vec![0, 1, 2]);
+    /// assert_eq!(workspace.focused_client(), Some(0));
+    ///
+    /// assert_eq!(workspace.swap_focused_with(2), Some(0));
+    /// assert_eq!(workspace.client_ids(), vec![2, 1, 0]);
+    /// assert_eq!(workspace.focused_client(), Some(0));
+    ///
+    /// assert_eq!(workspace.swap_focused_with(42), None);
+    /// assert_eq!(workspace.client_ids(), vec![2, 1, 0]);
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 3)).unwrap();
+    /// ```
+    pub fn swap_focused_with(&mut self, id: Xid) -> Option<Xid> {
+        self.clients
+            .swap_focused_with(&Selector::Condition(&|c| *c == id))
+            .copied()
+    }
+
+    /// Promote the focused client to be the new master client, with the previous master moving to
+    /// the top of the stack and the relative order of all other clients preserved. Focus remains
+    /// on the promoted client. Returns the focused client id, or `None` if the workspace is empty.
+    ///
+    /// Unlike [swap_focused_with][Workspace::swap_focused_with], the previous master does not
+    /// take the position of the promoted client.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// workspace.focus_client(3);
+    /// assert_eq!(workspace.client_ids(), vec![0, 1, 2, 3, 4]);
+    ///
+    /// assert_eq!(workspace.promote_focused_rotate(), Some(3));
+    /// assert_eq!(workspace.client_ids(), vec![3, 0, 1, 2, 4]);
+    /// assert_eq!(workspace.focused_client(), Some(3));
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 5)).unwrap();
+    /// ```
+    pub fn promote_focused_rotate(&mut self) -> Option<Xid> {
+        self.clients.move_focused_to_front().copied()
+    }
+
     /// Rotate the client stack in the given direction
     ///
     /// # Example
@@ -443,19 +922,92 @@ impl Workspace {
         self.clients.rotate(direction)
     }
 
+    /// Reverse the order of the client stack so that the main client becomes the last client and
+    /// vice versa. The focused client remains focused.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// workspace.focus_client(1);
+    /// assert_eq!(workspace.client_ids(), vec![0, 1, 2, 3]);
+    ///
+    /// workspace.reverse_clients();
+    /// assert_eq!(workspace.client_ids(), vec![3, 2, 1, 0]);
+    /// assert_eq!(workspace.focused_client(), Some(1));
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 4)).unwrap();
+    /// ```
+    pub fn reverse_clients(&mut self) {
+        self.clients.reverse()
+    }
+
     /// Increase or decrease the number of possible clients in the main area of the current Layout
-    pub fn update_max_main(&mut self, change: Change) {
-        if let Some(layout) = self.layouts.focused_mut() {
-            layout.update_max_main(change);
+    ///
+    /// Returns `true` if the current Layout was modified.
+    pub fn update_max_main(&mut self, change: Change) -> bool {
+        match self.layouts.focused_mut() {
+            Some(layout) => layout.update_max_main(change),
+            None => false,
         }
     }
 
     /// Increase or decrease the size of the main area for the current Layout
-    pub fn update_main_ratio(&mut self, change: Change, step: f32) {
-        if let Some(layout) = self.layouts.focused_mut() {
-            layout.update_main_ratio(change, step);
+    ///
+    /// Returns `true` if the current Layout was modified.
+    pub fn update_main_ratio(&mut self, change: Change, step: f32) -> bool {
+        match self.layouts.focused_mut() {
+            Some(layout) => layout.update_main_ratio(change, step),
+            None => false,
+        }
+    }
+
+    /// The number of pixels that the client `id` has been grown (or shrunk if negative) by
+    /// relative to the space allotted to it by the current Layout.
+    pub fn client_size_delta(&self, id: Xid) -> i32 {
+        self.size_deltas.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Grow or shrink the client `id` by `amount` pixels within the current Layout.
+    ///
+    /// The space is taken from (or given to) the client that shares an edge with it in the same
+    /// column or row when the layout is next applied, so the total area used is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use penrose::__test_helpers::*;
+    /// # fn example(mut workspace: Workspace) -> Result<()> {
+    /// workspace.update_client_size(1, More, 20);
+    /// workspace.update_client_size(1, Less, 5);
+    /// assert_eq!(workspace.client_size_delta(1), 15);
+    ///
+    /// workspace.reset_client_sizes();
+    /// assert_eq!(workspace.client_size_delta(1), 0);
+    /// # Ok(())
+    /// # }
+    /// # example(test_workspace("example", 3)).unwrap();
+    /// ```
+    pub fn update_client_size(&mut self, id: Xid, change: Change, amount: u32) {
+        let amount = amount as i32;
+        let delta = self.size_deltas.entry(id).or_insert(0);
+        match change {
+            Change::More => *delta = delta.saturating_add(amount),
+            Change::Less => *delta = delta.saturating_sub(amount),
+        }
+
+        if *delta == 0 {
+            self.size_deltas.remove(&id);
         }
     }
+
+    /// Clear any manual client size adjustments so that all clients are given the space
+    /// allotted to them by the current Layout.
+    pub fn reset_client_sizes(&mut self) {
+        self.size_deltas.clear();
+    }
 }
 
 #[cfg(test)]
@@ -532,7 +1084,7 @@ mod tests {
             Client::new(&conn, 3, 0, &[]),
         ];
         let refs: Vec<&Client> = clients.iter().collect();
-        let res = ws.arrange(Region::new(0, 0, 2000, 1000), &refs[..]);
+        let res = ws.arrange(Region::new(0, 0, 2000, 1000), &refs[..], false);
         assert_eq!(res.actions.len(), 3, "actions are not 1-1 for clients")
     }
 
@@ -578,4 +1130,188 @@ mod tests {
 
         assert_eq!(ws.focused_client(), Some(3));
     }
+
+    test_cases! {
+        promote_focused_rotate;
+        args: (focused: Xid, expected: Vec<Xid>);
+
+        case: master_is_unchanged => (1, vec![1, 2, 3, 4, 5]);
+        case: top_of_stack => (2, vec![2, 1, 3, 4, 5]);
+        case: middle_of_stack => (3, vec![3, 1, 2, 4, 5]);
+        case: bottom_of_stack => (5, vec![5, 1, 2, 3, 4]);
+
+        body: {
+            let mut ws = Workspace::new("test", test_layouts());
+            ws.clients = Ring::new(vec![1, 2, 3, 4, 5]);
+            ws.focus_client(focused);
+
+            assert_eq!(ws.promote_focused_rotate(), Some(focused));
+            assert_eq!(ws.clients.as_vec(), expected);
+            assert_eq!(ws.focused_client(), Some(focused));
+        }
+    }
+
+    fn cycle_test_layouts(excluded: &[&str]) -> Vec<Layout> {
+        ["a", "b", "c"]
+            .iter()
+            .map(|&symbol| {
+                let conf = LayoutConf {
+                    cycle_exclude: excluded.contains(&symbol),
+                    ..Default::default()
+                };
+                Layout::new(symbol, conf, mock_layout, 1, 0.6)
+            })
+            .collect()
+    }
+
+    test_cases! {
+        cycle_layout_skips_excluded;
+        args: (excluded: &[&str], direction: Direction, expected: Vec<&str>);
+
+        case: none_excluded => (&[], Direction::Forward, vec!["b", "c", "a"]);
+        case: forward => (&["b"], Direction::Forward, vec!["c", "a", "c"]);
+        case: backward => (&["b"], Direction::Backward, vec!["c", "a", "c"]);
+        case: all_others_excluded => (&["b", "c"], Direction::Forward, vec!["a", "a"]);
+
+        body: {
+            let mut ws = Workspace::new("test", cycle_test_layouts(excluded));
+            let symbols: Vec<String> = expected
+                .iter()
+                .map(|_| ws.cycle_layout(direction).to_string())
+                .collect();
+
+            assert_eq!(symbols, expected);
+        }
+    }
+
+    #[test]
+    fn locked_layouts_are_not_changed() {
+        let mut ws = Workspace::new("test", cycle_test_layouts(&[]));
+        ws.set_layout_locked(true);
+
+        assert_eq!(ws.cycle_layout(Direction::Forward), "a");
+        assert!(ws.try_set_layout("b").is_none());
+        assert!(ws.set_layout_by_index(2).is_none());
+        assert_eq!(ws.layout_symbol(), "a");
+
+        ws.set_layout_locked(false);
+        assert_eq!(ws.cycle_layout(Direction::Forward), "b");
+    }
+
+    #[test]
+    fn locking_while_fullscreen_still_restores_the_previous_layout() {
+        let mut ws = Workspace::new("test", cycle_test_layouts(&[]));
+        assert!(ws.enter_fullscreen_layout("c"));
+        ws.set_layout_locked(true);
+
+        assert!(ws.restore_pre_fullscreen_layout());
+        assert_eq!(ws.layout_symbol(), "a");
+    }
+
+    #[test]
+    fn added_layouts_can_be_cycled_to() {
+        let mut ws = Workspace::new("test", cycle_test_layouts(&[]));
+        ws.add_layout(Layout::new("d", LayoutConf::default(), mock_layout, 1, 0.6));
+
+        assert_eq!(ws.layout_symbol(), "a");
+        assert_eq!(ws.cycle_layout(Direction::Backward), "d");
+    }
+
+    test_cases! {
+        remove_layout;
+        args: (active: &str, symbol: &str, removed: Option<&str>, expected_active: &str);
+
+        case: inactive_before => ("b", "a", Some("a"), "b");
+        case: inactive_after => ("b", "c", Some("c"), "b");
+        case: active_middle => ("b", "b", Some("b"), "c");
+        case: active_last => ("c", "c", Some("c"), "b");
+        case: unknown => ("b", "x", None, "b");
+
+        body: {
+            let mut ws = Workspace::new("test", cycle_test_layouts(&[]));
+            ws.try_set_layout(active);
+
+            let res = ws.remove_layout(symbol);
+
+            assert_eq!(res.map(|l| l.symbol), removed.map(String::from));
+            assert_eq!(ws.layout_symbol(), expected_active);
+        }
+    }
+
+    #[test]
+    fn the_last_layout_can_not_be_removed() {
+        let mut ws = Workspace::new("test", cycle_test_layouts(&[]));
+        assert!(ws.remove_layout("a").is_some());
+        assert!(ws.remove_layout("b").is_some());
+
+        assert!(ws.remove_layout("c").is_none());
+        assert_eq!(ws.layout_symbol(), "c");
+    }
+
+    #[test]
+    fn excluded_layouts_can_be_set_directly() {
+        let mut ws = Workspace::new("test", cycle_test_layouts(&["b"]));
+
+        assert!(ws.try_set_layout("b").is_some());
+        assert_eq!(ws.layout_symbol(), "b");
+        assert_eq!(ws.cycle_layout(Direction::Forward), "c");
+    }
+
+    test_cases! {
+        focus_direction;
+        args: (allow_wrapping: bool, focused: Xid, direction: Direction, expected: Option<Xid>);
+
+        case: forward => (false, 30, Direction::Forward, Some(20));
+        case: backward => (false, 20, Direction::Backward, Some(30));
+        case: forward_at_end_without_wrapping => (false, 10, Direction::Forward, None);
+        case: backward_at_start_without_wrapping => (false, 30, Direction::Backward, None);
+        case: forward_at_end_with_wrapping => (true, 10, Direction::Forward, Some(30));
+        case: backward_at_start_with_wrapping => (true, 30, Direction::Backward, Some(10));
+
+        body: {
+            let conf = LayoutConf {
+                allow_wrapping,
+                ..Default::default()
+            };
+            let layouts = vec![Layout::new("t", conf, mock_layout, 1, 0.6)];
+            let mut ws = Workspace::new("test", layouts);
+            add_n_clients(&mut ws, 3);
+            ws.focus_client(focused);
+
+            assert_eq!(ws.focus_direction(direction), expected);
+            assert_eq!(ws.focused_client(), Some(expected.unwrap_or(focused)));
+            assert_eq!(ws.client_ids(), vec![30, 20, 10]);
+        }
+    }
+
+    #[test]
+    fn reverse_clients_keeps_focus_on_the_same_client() {
+        let mut ws = Workspace::new("test", test_layouts());
+        add_n_clients(&mut ws, 4);
+        ws.focus_client(30);
+        assert_eq!(ws.client_ids(), vec![40, 30, 20, 10]);
+
+        ws.reverse_clients();
+
+        assert_eq!(ws.client_ids(), vec![10, 20, 30, 40]);
+        assert_eq!(ws.focused_client(), Some(30));
+        assert_eq!(ws.clients.focused_index(), 2);
+    }
+
+    #[test]
+    fn reverse_clients_when_empty() {
+        let mut ws = Workspace::new("test", test_layouts());
+        ws.reverse_clients();
+
+        assert!(ws.is_empty());
+        assert_eq!(ws.focused_client(), None);
+    }
+
+    #[test]
+    fn promote_focused_rotate_when_empty() {
+        let mut ws = Workspace::new("test", test_layouts());
+
+        assert_eq!(ws.promote_focused_rotate(), None);
+        assert!(ws.is_empty());
+    }
 }
@@ -98,9 +98,21 @@ pub enum Atom {
     /// _NET_WM_STATE
     #[strum(serialize = "_NET_WM_STATE")]
     NetWmState,
+    /// _NET_WM_STATE_ABOVE
+    #[strum(serialize = "_NET_WM_STATE_ABOVE")]
+    NetWmStateAbove,
+    /// _NET_WM_STATE_BELOW
+    #[strum(serialize = "_NET_WM_STATE_BELOW")]
+    NetWmStateBelow,
+    /// _NET_WM_STATE_DEMANDS_ATTENTION
+    #[strum(serialize = "_NET_WM_STATE_DEMANDS_ATTENTION")]
+    NetWmStateDemandsAttention,
     /// _NET_WM_STATE_FULLSCREEN
     #[strum(serialize = "_NET_WM_STATE_FULLSCREEN")]
     NetWmStateFullscreen,
+    /// _NET_WM_USER_TIME
+    #[strum(serialize = "_NET_WM_USER_TIME")]
+    NetWmUserTime,
     /// _NET_WM_WINDOW_TYPE
     #[strum(serialize = "_NET_WM_WINDOW_TYPE")]
     NetWmWindowType,
@@ -142,6 +154,9 @@ pub enum Atom {
     /// _NET_WM_WINDOW_TYPE_NOTIFICATION
     #[strum(serialize = "_NET_WM_WINDOW_TYPE_NOTIFICATION")]
     NetWindowTypeNotification,
+    /// _NET_WM_WINDOW_TYPE_TOOLTIP
+    #[strum(serialize = "_NET_WM_WINDOW_TYPE_TOOLTIP")]
+    NetWindowTypeTooltip,
     /// _NET_WM_WINDOW_TYPE_COMBO
     #[strum(serialize = "_NET_WM_WINDOW_TYPE_COMBO")]
     NetWindowTypeCombo,
@@ -170,9 +185,11 @@ pub const AUTO_FLOAT_WINDOW_TYPES: &[Atom] = &[
 
 /// Windows with a type in this array will not be managed by penrose
 pub const UNMANAGED_WINDOW_TYPES: &[Atom] = &[
+    Atom::NetWindowTypeDesktop,
     Atom::NetWindowTypeDock,
     Atom::NetWindowTypeNotification,
     Atom::NetWindowTypeToolbar,
+    Atom::NetWindowTypeTooltip,
     Atom::NetWindowTypeUtility,
 ];
 
@@ -191,6 +208,9 @@ pub const EWMH_SUPPORTED_ATOMS: &[Atom] = &[
     // Atom::NetSystemTrayOrientationHorz,
     Atom::NetWmName,
     Atom::NetWmState,
+    Atom::NetWmStateAbove,
+    Atom::NetWmStateBelow,
+    Atom::NetWmStateDemandsAttention,
     Atom::NetWmStateFullscreen,
     Atom::NetWmWindowType,
 ];
@@ -9,7 +9,7 @@
 //! [1]: crate::core::manager::WindowManager
 use crate::{
     core::{
-        bindings::{KeyBindings, KeyPress, MouseBindings},
+        bindings::{KeyBindings, KeyPress, LockModifier, MouseBindings},
         client::Client,
         data_types::{Point, Region},
         screen::Screen,
@@ -74,6 +74,10 @@ pub enum XError {
     #[error("{0} is not a known client")]
     UnknownClient(Xid),
 
+    /// The target window no longer exists on the X server (BadWindow / BadDrawable)
+    #[error("window {0} no longer exists")]
+    WindowGone(Xid),
+
     /*
      * Conversions from other penrose error types
      */
@@ -92,6 +96,37 @@ pub enum XError {
     X11rb(#[from] crate::x11rb::X11rbError),
 }
 
+impl XError {
+    /// Whether or not this error is a transient failure caused by operating on a window that has
+    /// already been destroyed (BadWindow / BadDrawable).
+    ///
+    /// These are expected when a client closes while penrose is still processing events for it,
+    /// so they are logged rather than being passed to the [ErrorHandler][crate::ErrorHandler].
+    pub fn is_transient(&self) -> bool {
+        match self {
+            XError::WindowGone(_) => true,
+            #[cfg(feature = "xcb")]
+            XError::Xcb(e) => e.is_window_gone(),
+            #[cfg(feature = "x11rb")]
+            XError::X11rb(e) => e.is_window_gone(),
+            _ => false,
+        }
+    }
+
+    /// Whether or not this error means that the connection to the X server has been lost, in
+    /// which case no further requests or events can be processed.
+    pub fn is_connection_closed(&self) -> bool {
+        match self {
+            XError::ConnectionClosed => true,
+            #[cfg(feature = "xcb")]
+            XError::Xcb(e) => matches!(e, crate::xcb::XcbError::Connection(_)),
+            #[cfg(feature = "x11rb")]
+            XError::X11rb(e) => matches!(e, crate::x11rb::X11rbError::Connection(_)),
+            _ => false,
+        }
+    }
+}
+
 /// Result type for errors raised by X traits
 pub type Result<T> = std::result::Result<T, XError>;
 
@@ -105,6 +140,8 @@ pub enum ClientConfig {
     Position(Region),
     /// Mark this window as stacking on top of its peers
     StackAbove,
+    /// Mark this window as stacking below its peers
+    StackBelow,
 }
 
 /// Attributes for an X11 client window (not all are curently implemented)
@@ -155,6 +192,11 @@ pub trait XState: XAtomQuerier {
     #[stub(Ok(Point::default()))]
     fn cursor_position(&self) -> Result<Point>;
 
+    /// Determine the current (x,y) position of the cursor relative to the root window along with
+    /// the top level window that it is currently over (if any).
+    #[stub(Ok((Point::default(), None)))]
+    fn query_pointer(&self) -> Result<(Point, Option<Xid>)>;
+
     /// Warp the cursor to be within the specified window. If id == None then behaviour is
     /// definined by the implementor (e.g. warp cursor to active window, warp to center of screen)
     #[stub(Ok(()))]
@@ -301,6 +343,44 @@ pub trait XClientProperties {
         }
     }
 
+    /// Update the `_NET_WM_STATE_ABOVE` and `_NET_WM_STATE_BELOW` atoms in the `_NET_WM_STATE`
+    /// property of the given client ID, leaving any other state atoms in place
+    fn set_client_stacking_state(&self, id: Xid, above: bool, below: bool) -> Result<()> {
+        let (a, b) = (
+            Atom::NetWmStateAbove.as_ref(),
+            Atom::NetWmStateBelow.as_ref(),
+        );
+        let mut wm_state = match self.get_prop(id, Atom::NetWmState.as_ref()) {
+            Ok(Prop::Atom(atoms)) => atoms,
+            _ => vec![],
+        };
+        wm_state.retain(|s| s != a && s != b);
+        if above {
+            wm_state.push(a.to_string());
+        }
+        if below {
+            wm_state.push(b.to_string());
+        }
+
+        self.change_prop(id, Atom::NetWmState.as_ref(), Prop::Atom(wm_state))
+    }
+
+    /// Add or remove the `_NET_WM_STATE_DEMANDS_ATTENTION` atom in the `_NET_WM_STATE` property
+    /// of the given client ID, leaving any other state atoms in place
+    fn set_client_attention(&self, id: Xid, demands_attention: bool) -> Result<()> {
+        let attention = Atom::NetWmStateDemandsAttention.as_ref();
+        let mut wm_state = match self.get_prop(id, Atom::NetWmState.as_ref()) {
+            Ok(Prop::Atom(atoms)) => atoms,
+            _ => vec![],
+        };
+        wm_state.retain(|s| s != attention);
+        if demands_attention {
+            wm_state.push(attention.to_string());
+        }
+
+        self.change_prop(id, Atom::NetWmState.as_ref(), Prop::Atom(wm_state))
+    }
+
     /// Toggle the fullscreen state of the given client ID with the X server
     fn toggle_client_fullscreen(&self, id: Xid, client_is_fullscreen: bool) -> Result<()> {
         let data = if client_is_fullscreen {
@@ -387,6 +467,11 @@ pub trait XClientConfig {
         self.configure_client(id, &[ClientConfig::StackAbove])
     }
 
+    /// Lower the window to the bottom of the stack so it renders below peers
+    fn lower_client(&self, id: Xid) -> Result<()> {
+        self.configure_client(id, &[ClientConfig::StackBelow])
+    }
+
     /// Change the border color for the given client
     fn set_client_border_color(&self, id: Xid, color: Color) -> Result<()> {
         self.set_client_attributes(id, &[ClientAttr::BorderColor(color.rgb_u32())])
@@ -459,12 +544,17 @@ pub trait XConn:
     /// them being passed through to the underlying applications.
     ///
     /// This is what determines which key press events end up being sent through in the main event
-    /// loop for the WindowManager.
+    /// loop for the WindowManager. Each binding should also be grabbed with every combination of
+    /// `lock_modifiers` held (see [lock_modifier_masks][1]) so that it is still delivered while
+    /// those locks are active.
+    ///
+    /// [1]: crate::core::bindings::lock_modifier_masks
     #[stub(Ok(()))]
     fn grab_keys(
         &self,
         key_bindings: &KeyBindings<Self>,
         mouse_bindings: &MouseBindings<Self>,
+        lock_modifiers: &[LockModifier],
     ) -> Result<()>;
 
     /*
@@ -567,18 +657,25 @@ pub trait XConn:
     }
 
     /// Check to see if this client is one that we should be handling or not
+    ///
+    /// Clients with any of the given `_NET_WM_WINDOW_TYPE` values are left unmanaged.
     #[tracing::instrument(level = "trace", skip(self))]
-    fn is_managed_client(&self, c: &Client) -> bool {
-        let unmanaged_types: Vec<String> = UNMANAGED_WINDOW_TYPES
-            .iter()
-            .map(|t| t.as_ref().to_string())
-            .collect();
+    fn is_managed_client(&self, c: &Client, unmanaged_types: &[&str]) -> bool {
         trace!(ty = ?c.wm_type, "checking window type to see we should manage");
-        return c.wm_type.iter().all(|ty| !unmanaged_types.contains(ty));
+        !c.wm_type
+            .iter()
+            .any(|t| unmanaged_types.contains(&t.as_str()))
     }
 
-    /// The subset of active clients that are considered managed by penrose
-    fn active_managed_clients(&self, floating_classes: &[&str]) -> Result<Vec<Client>> {
+    /// The subset of active clients that are considered managed by penrose, along with the
+    /// workspace index each of them requested via `_NET_WM_DESKTOP` (if any).
+    ///
+    /// Requested indices are returned as-is: callers need to check that they are in range.
+    fn active_managed_clients(
+        &self,
+        floating_classes: &[&str],
+        unmanaged_types: &[&str],
+    ) -> Result<Vec<(Client, Option<usize>)>> {
         Ok(self
             .active_clients()?
             .into_iter()
@@ -590,14 +687,15 @@ pub trait XConn:
                 });
                 if attrs_ok {
                     trace!(id, "parsing existing client");
-                    let wix = match self.get_prop(id, Atom::NetWmDesktop.as_ref()) {
-                        Ok(Prop::Cardinal(wix)) => wix,
-                        _ => 0, // Drop unknown clients onto ws 0 as we know that is always there
+                    let requested = match self.get_prop(id, Atom::NetWmDesktop.as_ref()) {
+                        Ok(Prop::Cardinal(wix)) => Some(wix as usize),
+                        _ => None,
                     };
 
-                    let c = Client::new(self, id, wix as usize, floating_classes);
-                    if self.is_managed_client(&c) {
-                        return Some(c);
+                    // The workspace is assigned by the caller once placement has been resolved
+                    let c = Client::new(self, id, 0, floating_classes);
+                    if self.is_managed_client(&c, unmanaged_types) {
+                        return Some((c, requested));
                     }
                 }
                 None
@@ -621,6 +719,7 @@ mod mock_conn {
         events: Cell<Vec<XEvent>>,
         focused: Cell<Xid>,
         unmanaged_ids: Vec<Xid>,
+        pointer: Cell<(Point, Option<Xid>)>,
     }
 
     impl fmt::Debug for MockXConn {
@@ -642,9 +741,15 @@ mod mock_conn {
                 events: Cell::new(events),
                 focused: Cell::new(0),
                 unmanaged_ids,
+                pointer: Cell::new((Point::default(), None)),
             }
         }
 
+        /// Set the cursor position and window under the cursor reported by query_pointer
+        pub fn set_pointer(&self, position: Point, window: Option<Xid>) {
+            self.pointer.set((position, window));
+        }
+
         fn remaining_events(&self) -> Vec<XEvent> {
             let remaining = self.events.replace(vec![]);
             self.events.set(remaining.clone());
@@ -699,9 +804,13 @@ mod mock_conn {
             fn mock_focused_client(&self) -> Result<Xid> {
                 Ok(self.focused.get())
             }
+
+            fn mock_query_pointer(&self) -> Result<(Point, Option<Xid>)> {
+                Ok(self.pointer.get())
+            }
         }
         conn: {
-            fn mock_is_managed_client(&self, c: &Client) -> bool {
+            fn mock_is_managed_client(&self, c: &Client, _: &[&str]) -> bool {
                 !self.unmanaged_ids.contains(&c.id())
             }
         }
@@ -754,4 +863,46 @@ mod tests {
             assert_eq!(&conn.client_name(42).unwrap(), expected);
         }
     }
+
+    test_cases! {
+        query_pointer;
+        args: (position: Point, window: Option<Xid>);
+
+        case: over_root => (Point::new(10, 20), None);
+        case: over_window => (Point::new(300, 150), Some(42));
+
+        body: {
+            let conn = MockXConn::new(vec![], vec![], vec![]);
+            conn.set_pointer(position, window);
+
+            assert_eq!(conn.query_pointer().unwrap(), (position, window));
+        }
+    }
+
+    test_cases! {
+        is_transient;
+        args: (err: XError, expected: bool);
+
+        case: window_gone => (XError::WindowGone(42), true);
+        case: unknown_client => (XError::UnknownClient(42), false);
+        case: missing_property => (XError::MissingProperty("WM_NAME".into(), 42), false);
+        case: raw => (XError::Raw("boom".into()), false);
+
+        body: {
+            assert_eq!(err.is_transient(), expected);
+        }
+    }
+
+    test_cases! {
+        is_connection_closed;
+        args: (err: XError, expected: bool);
+
+        case: connection_closed => (XError::ConnectionClosed, true);
+        case: window_gone => (XError::WindowGone(42), false);
+        case: raw => (XError::Raw("boom".into()), false);
+
+        body: {
+            assert_eq!(err.is_connection_closed(), expected);
+        }
+    }
 }
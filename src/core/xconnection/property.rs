@@ -234,6 +234,7 @@ pub struct WmNormalHints {
     pub(crate) min: Option<Region>,
     pub(crate) max: Option<Region>,
     pub(crate) user_specified: Option<Region>,
+    pub(crate) resize_inc: Option<(u32, u32)>,
 }
 
 impl WmNormalHints {
@@ -251,9 +252,45 @@ impl WmNormalHints {
             min,
             max,
             user_specified,
+            resize_inc: None,
         }
     }
 
+    /// Set the resize increments (width, height) requested by the client
+    pub fn with_resize_increments(mut self, width_inc: u32, height_inc: u32) -> Self {
+        self.resize_inc = if width_inc > 0 && height_inc > 0 {
+            Some((width_inc, height_inc))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Shrink `r` so that its width and height are a whole number of resize increments larger
+    /// than the client's base size, centering the result within `r`.
+    ///
+    /// If the client has not set any resize increments then `r` is returned unchanged. The base
+    /// size falls back to the minimum size (if set) as described in the ICCCM.
+    pub fn apply_resize_increments(&self, r: Region) -> Region {
+        let (w_inc, h_inc) = match self.resize_inc {
+            Some(inc) => inc,
+            None => return r,
+        };
+
+        let (base_w, base_h) = self.base.or(self.min).map_or((0, 0), |b| (b.w, b.h));
+        let snap = |len: u32, base: u32, inc: u32| {
+            if len <= base {
+                len
+            } else {
+                base + ((len - base) / inc) * inc
+            }
+        };
+
+        let (w, h) = (snap(r.w, base_w, w_inc), snap(r.h, base_h, h_inc));
+
+        Region::new(r.x + (r.w - w) / 2, r.y + (r.h - h) / 2, w, h)
+    }
+
     /// Try to construct a [WmNormalHints] instance from raw bytes.
     ///
     /// This method expects a slice of 18 u32s corresponding to the C struct layout shown below.
@@ -292,9 +329,14 @@ impl WmNormalHints {
 
         let (min_w, min_h) = (raw[5], raw[6]);
         let (max_w, max_h) = (raw[7], raw[8]);
+        let (w_inc, h_inc) = if flags.contains(WmNormalHintsFlags::P_RESIZE_INC) {
+            (raw[9], raw[10])
+        } else {
+            (0, 0)
+        };
         let (base_w, base_h) = (raw[15], raw[16]);
 
-        // ignoring increment, aspect ratio, gravity as they are not used in
+        // ignoring aspect ratio, gravity as they are not used in
         // the main WindowManager logic
 
         let if_set = |x, y, w, h| {
@@ -311,7 +353,9 @@ impl WmNormalHints {
             min: if_set(x, y, min_w, min_h),
             max: if_set(x, y, max_w, max_h),
             user_specified: if_set(x, y, user_w, user_h),
-        })
+            resize_inc: None,
+        }
+        .with_resize_increments(w_inc, h_inc))
     }
 }
 
@@ -185,6 +185,10 @@ pub enum PenroseError {
     #[error(transparent)]
     Draw(#[from] crate::draw::DrawError),
 
+    /// An operation that requires at least one client was attempted on an empty workspace
+    #[error("workspace {0} has no clients")]
+    EmptyWorkspace(usize),
+
     /// Something was inconsistant when attempting to re-create a serialised [WindowManager]
     #[error("unable to rehydrate from serialized state: {0}")]
     HydrationState(String),
@@ -223,7 +227,7 @@ pub enum PenroseError {
     Raw(String),
 
     /// An attempt to spawn an external process failed
-    #[error("unable to get stdout handle for child process: {0}")]
+    #[error("unable to spawn child process: {0}")]
     SpawnProc(String),
 
     /// Parsing an [Atom][core::xconnection::Atom] from a str failed.
@@ -236,6 +240,10 @@ pub enum PenroseError {
     #[error("{0} is not a known client")]
     UnknownClient(Xid),
 
+    /// A user specified key binding contained a key name that is not known to the X server
+    #[error("Unknown key name: {0}")]
+    UnknownKeyName(String),
+
     /// A user specified key binding contained an invalid modifier key
     #[error("Unknown modifier key: {0}")]
     UnknownModifier(String),
@@ -259,6 +267,21 @@ pub enum PenroseError {
     X(#[from] crate::core::xconnection::XError),
 }
 
+impl PenroseError {
+    /// Whether or not this error is a transient X error for a window that has already been
+    /// destroyed. See [XError::is_transient][crate::core::xconnection::XError::is_transient].
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PenroseError::X(e) => e.is_transient(),
+            #[cfg(feature = "xcb")]
+            PenroseError::Xcb(e) => e.is_window_gone(),
+            #[cfg(feature = "x11rb")]
+            PenroseError::X11rb(e) => e.is_window_gone(),
+            _ => false,
+        }
+    }
+}
+
 /// Top level penrose Result type
 pub type Result<T> = std::result::Result<T, PenroseError>;
 
@@ -15,7 +15,7 @@ use strum::IntoEnumIterator;
 use x11rb::{
     connection::Connection,
     protocol::{
-        xproto::{ClientMessageEvent, ConnectionExt as _, ModMask},
+        xproto::{ClientMessageEvent, ConnectionExt as _},
         Event,
     },
 };
@@ -61,16 +61,10 @@ pub(crate) fn convert_event<C: Connection>(
                 MouseEventKind::Motion,
             ))
         })),
-        Event::KeyPress(event) => {
-            let code = KeyCode {
-                mask: event.state,
-                code: event.detail,
-            };
-            let numlock = ModMask::M2;
-            Ok(Some(XEvent::KeyPress(
-                code.ignoring_modifier(numlock.into()),
-            )))
-        }
+        Event::KeyPress(event) => Ok(Some(XEvent::KeyPress(KeyCode {
+            mask: event.state,
+            code: event.detail,
+        }))),
         Event::MapRequest(event) => {
             let attr = conn
                 .connection()
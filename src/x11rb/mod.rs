@@ -22,6 +22,7 @@ use x11rb::xcb_ffi::XCBConnection;
 use x11rb::{
     connection::Connection,
     errors::{ConnectError, ConnectionError, ReplyError, ReplyOrIdError},
+    protocol::ErrorKind,
     rust_connection::RustConnection,
     x11_utils::X11Error,
 };
@@ -119,6 +120,20 @@ pub enum X11rbError {
     X11Error(X11Error),
 }
 
+impl X11rbError {
+    // BadWindow / BadDrawable errors are returned for windows that have already been destroyed
+    pub(crate) fn is_window_gone(&self) -> bool {
+        let err = match self {
+            X11rbError::X11Error(e)
+            | X11rbError::ReplyError(ReplyError::X11Error(e))
+            | X11rbError::ReplyOrIdError(ReplyOrIdError::X11Error(e)) => e,
+            _ => return false,
+        };
+
+        matches!(err.error_kind, ErrorKind::Window | ErrorKind::Drawable)
+    }
+}
+
 macro_rules! from_error {
     ($type:ident) => {
         impl From<$type> for XError {
@@ -9,7 +9,10 @@
 
 use crate::{
     core::{
-        bindings::{KeyBindings, KeyCode, MouseBindings, MouseState},
+        bindings::{
+            lock_modifier_masks, KeyBindings, KeyCode, KeyCodeMask, LockModifier, MouseBindings,
+            MouseState,
+        },
         data_types::{Point, Region},
         screen::Screen,
         xconnection::{
@@ -145,6 +148,7 @@ impl<C: Connection> XClientConfig for X11rbConnection<C> {
                     aux = aux.x(x as i32).y(y as i32).width(w).height(h);
                 }
                 ClientConfig::StackAbove => aux = aux.stack_mode(StackMode::ABOVE),
+                ClientConfig::StackBelow => aux = aux.stack_mode(StackMode::BELOW),
             }
         }
         self.conn.configure_window(id, &aux)?;
@@ -450,20 +454,37 @@ impl<C: Connection> XState for X11rbConnection<C> {
             })
             .collect::<Result<Vec<_>>>()?;
 
+        // The CRTC driving the primary output (if one is set). Failing to query the primary
+        // output is treated the same as there not being one.
+        let primary_crtc = self
+            .conn
+            .randr_get_output_primary(self.root)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.output)
+            .filter(|&output| output != 0)
+            .and_then(|output| self.conn.randr_get_output_info(output, 0).ok())
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.crtc);
+
         // Get the replies and construct screens
-        let screens = crtcs
-            .into_iter()
-            .flat_map(|cookie| cookie.reply().ok())
+        let screens = resources
+            .crtcs
+            .iter()
+            .zip(crtcs)
+            .flat_map(|(crtc, cookie)| cookie.reply().ok().map(|reply| (*crtc, reply)))
             .enumerate()
-            .filter(|(_, reply)| reply.width > 0)
-            .map(|(i, reply)| {
+            .filter(|(_, (_, reply))| reply.width > 0)
+            .map(|(i, (crtc, reply))| {
                 let region = Region::new(
                     reply.x as u32,
                     reply.y as u32,
                     reply.width as u32,
                     reply.height as u32,
                 );
-                Screen::new(region, i)
+                let mut screen = Screen::new(region, i);
+                screen.set_primary(Some(crtc) == primary_crtc);
+                screen
             })
             .collect();
         Ok(screens)
@@ -474,6 +495,16 @@ impl<C: Connection> XState for X11rbConnection<C> {
         Ok(Point::new(reply.root_x as u32, reply.root_y as u32))
     }
 
+    fn query_pointer(&self) -> Result<(Point, Option<Xid>)> {
+        let reply = self.conn.query_pointer(self.root)?.reply()?;
+        let child = if reply.child == x11rb::NONE {
+            None
+        } else {
+            Some(reply.child)
+        };
+        Ok((Point::new(reply.root_x as u32, reply.root_y as u32), child))
+    }
+
     fn warp_cursor(&self, win_id: Option<Xid>, screen: &Screen) -> Result<()> {
         let (x, y, id) = match win_id {
             Some(id) => {
@@ -543,13 +574,16 @@ impl<C: Connection> XConn for X11rbConnection<C> {
         &self,
         key_bindings: &KeyBindings<Self>,
         mouse_bindings: &MouseBindings<Self>,
+        lock_modifiers: &[LockModifier],
     ) -> Result<()> {
-        self.grab_key_bindings(&key_bindings.keys().collect::<Vec<_>>())?;
+        let lock_masks = lock_modifier_masks(lock_modifiers);
+        self.grab_key_bindings(&key_bindings.keys().collect::<Vec<_>>(), &lock_masks)?;
         self.grab_mouse_buttons(
             &mouse_bindings
                 .keys()
                 .map(|(_, state)| state)
                 .collect::<Vec<_>>(),
+            &lock_masks,
         )?;
         self.flush();
 
@@ -558,11 +592,10 @@ impl<C: Connection> XConn for X11rbConnection<C> {
 }
 
 impl<C: Connection> X11rbConnection<C> {
-    fn grab_key_bindings(&self, keys: &[&KeyCode]) -> Result<()> {
-        // We need to explicitly grab NumLock as an additional modifier and then drop it later on
-        // when we are passing events through to the WindowManager as NumLock alters the modifier
-        // mask when it is active.
-        let modifiers = &[0, u16::from(ModMask::M2)];
+    fn grab_key_bindings(&self, keys: &[&KeyCode], lock_masks: &[KeyCodeMask]) -> Result<()> {
+        // Lock modifiers alter the modifier mask when they are active so each binding needs to be
+        // grabbed with every combination of them that should be ignored.
+        let modifiers = lock_masks;
         let mode = GrabMode::ASYNC;
 
         for m in modifiers.iter() {
@@ -582,11 +615,10 @@ impl<C: Connection> X11rbConnection<C> {
         Ok(())
     }
 
-    fn grab_mouse_buttons(&self, states: &[&MouseState]) -> Result<()> {
-        // We need to explicitly grab NumLock as an additional modifier and then drop it later on
-        // when we are passing events through to the WindowManager as NumLock alters the modifier
-        // mask when it is active.
-        let modifiers = &[0, u16::from(ModMask::M2)];
+    fn grab_mouse_buttons(&self, states: &[&MouseState], lock_masks: &[KeyCodeMask]) -> Result<()> {
+        // Lock modifiers alter the modifier mask when they are active so each binding needs to be
+        // grabbed with every combination of them that should be ignored.
+        let modifiers = lock_masks;
         let mode = GrabMode::ASYNC;
         let mask = EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::BUTTON_MOTION;
         let mask = u16::try_from(u32::from(mask)).unwrap();
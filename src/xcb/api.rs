@@ -379,7 +379,6 @@ impl Api {
 
     fn generic_xcb_to_xevent(&self, event: XcbGenericEvent) -> Result<Option<XEvent>> {
         let xcb_response_type_mask: u8 = 0x7F;
-        let numlock = xcb::MOD_MASK_2 as u16;
 
         let etype = event.response_type() & xcb_response_type_mask;
 
@@ -401,9 +400,7 @@ impl Api {
                 }
             }
 
-            xcb::KEY_PRESS => Some(XEvent::KeyPress(
-                KeyCode::try_from(event)?.ignoring_modifier(numlock),
-            )),
+            xcb::KEY_PRESS => Some(XEvent::KeyPress(KeyCode::try_from(event)?)),
 
             xcb::MAP_REQUEST => {
                 let e: &xcb::MapRequestEvent = unsafe { xcb::cast_event(&event) };
@@ -826,21 +823,41 @@ impl Api {
         let check_win = self.check_window();
         let resources = xcb::randr::get_screen_resources(&self.conn, check_win);
 
+        // xcb docs: https://www.mankier.com/3/xcb_randr_get_output_primary
+        // Failing to query the primary output is treated the same as there not being one
+        let primary_crtc = xcb::randr::get_output_primary(&self.conn, self.root)
+            .get_reply()
+            .ok()
+            .map(|r| r.output())
+            .filter(|&output| output != 0)
+            .and_then(|output| {
+                xcb::randr::get_output_info(&self.conn, output, 0)
+                    .get_reply()
+                    .ok()
+            })
+            .map(|info| info.crtc());
+
         // xcb docs: https://www.mankier.com/3/xcb_randr_get_crtc_info
         let screens = resources
             .get_reply()?
             .crtcs()
             .iter()
-            .flat_map(|c| xcb::randr::get_crtc_info(&self.conn, *c, 0).get_reply())
+            .flat_map(|c| {
+                xcb::randr::get_crtc_info(&self.conn, *c, 0)
+                    .get_reply()
+                    .map(|r| (*c, r))
+            })
             .enumerate()
-            .map(|(i, r)| {
+            .map(|(i, (c, r))| {
                 let region = Region::new(
                     r.x() as u32,
                     r.y() as u32,
                     r.width() as u32,
                     r.height() as u32,
                 );
-                Screen::new(region, i)
+                let mut screen = Screen::new(region, i);
+                screen.set_primary(Some(c) == primary_crtc);
+                screen
             })
             .filter(|s| {
                 let (_, _, w, _) = s.region(false).values();
@@ -872,6 +889,18 @@ impl Api {
             .map(|reply| Point::new(reply.root_x() as u32, reply.root_y() as u32))?)
     }
 
+    /// The current (x, y) position of the cursor relative to the root window and the top level
+    /// window that it is over (if any)
+    pub fn query_pointer(&self) -> Result<(Point, Option<Xid>)> {
+        Ok(xcb::query_pointer(&self.conn, self.root)
+            .get_reply()
+            .map(|reply| {
+                let p = Point::new(reply.root_x() as u32, reply.root_y() as u32);
+                let child = reply.child();
+                (p, if child == 0 { None } else { Some(child) })
+            })?)
+    }
+
     /// Flush pending actions to the X event loop
     pub fn flush(&self) -> bool {
         self.conn.flush()
@@ -883,12 +912,12 @@ impl Api {
         Ok(xcb::get_input_focus(&self.conn).get_reply()?.focus())
     }
 
-    /// Register intercepts for each given [KeyCode]
-    pub fn grab_keys(&self, keys: &[&KeyCode]) -> Result<()> {
-        // We need to explicitly grab NumLock as an additional modifier and then drop it later on
-        // when we are passing events through to the WindowManager as NumLock alters the modifier
-        // mask when it is active.
-        let modifiers = &[0, xcb::MOD_MASK_2 as u16];
+    /// Register intercepts for each given [KeyCode], once for each of the given lock modifier
+    /// masks.
+    pub fn grab_keys(&self, keys: &[&KeyCode], lock_masks: &[KeyCodeMask]) -> Result<()> {
+        // Lock modifiers alter the modifier mask when they are active so each binding needs to be
+        // grabbed with every combination of them that should be ignored.
+        let modifiers = lock_masks;
         let mode = xcb::GRAB_MODE_ASYNC as u8;
 
         for m in modifiers.iter() {
@@ -911,12 +940,16 @@ impl Api {
         Ok(())
     }
 
-    /// Register intercepts for each given [MouseState]
-    pub fn grab_mouse_buttons(&self, states: &[&MouseState]) -> Result<()> {
-        // We need to explicitly grab NumLock as an additional modifier and then drop it later on
-        // when we are passing events through to the WindowManager as NumLock alters the modifier
-        // mask when it is active.
-        let modifiers = &[0, xcb::MOD_MASK_2 as u16];
+    /// Register intercepts for each given [MouseState], once for each of the given lock modifier
+    /// masks.
+    pub fn grab_mouse_buttons(
+        &self,
+        states: &[&MouseState],
+        lock_masks: &[KeyCodeMask],
+    ) -> Result<()> {
+        // Lock modifiers alter the modifier mask when they are active so each binding needs to be
+        // grabbed with every combination of them that should be ignored.
+        let modifiers = lock_masks;
         let mode = xcb::GRAB_MODE_ASYNC as u8;
         let mask = (xcb::EVENT_MASK_BUTTON_PRESS
             | xcb::EVENT_MASK_BUTTON_RELEASE
@@ -17,17 +17,6 @@ impl ModifierKey {
     }
 }
 
-impl From<ModifierKey> for u16 {
-    fn from(m: ModifierKey) -> u16 {
-        (match m {
-            ModifierKey::Ctrl => xcb::MOD_MASK_CONTROL,
-            ModifierKey::Alt => xcb::MOD_MASK_1,
-            ModifierKey::Shift => xcb::MOD_MASK_SHIFT,
-            ModifierKey::Meta => xcb::MOD_MASK_4,
-        }) as u16
-    }
-}
-
 impl From<xcb::KeyPressEvent> for KeyCode {
     fn from(e: xcb::KeyPressEvent) -> Self {
         Self {
@@ -96,18 +85,6 @@ impl MouseState {
             modifiers: ModifierKey::iter().filter(|m| m.was_held(state)).collect(),
         })
     }
-
-    /// The xcb bitmask for this [MouseState]
-    pub fn mask(&self) -> u16 {
-        self.modifiers
-            .iter()
-            .fold(0, |acc, &val| acc | u16::from(val))
-    }
-
-    /// The xcb button ID for this [MouseState]
-    pub fn button(&self) -> u8 {
-        self.button.into()
-    }
 }
 
 impl TryFrom<XcbGenericEvent> for MouseEvent {
@@ -190,6 +167,9 @@ impl From<&ClientConfig> for Vec<(u16, u32)> {
             ClientConfig::StackAbove => {
                 vec![(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_ABOVE)]
             }
+            ClientConfig::StackBelow => {
+                vec![(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_BELOW)]
+            }
         }
     }
 }
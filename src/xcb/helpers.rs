@@ -1,5 +1,8 @@
 //! XCB based helper functions
-use crate::core::bindings::{CodeMap, KeyCode};
+use crate::{
+    core::bindings::{self, CodeMap, KeyCode},
+    PenroseError,
+};
 
 /**
  * Convert user friendly key bindings into X keycodes.
@@ -19,26 +22,12 @@ use crate::core::bindings::{CodeMap, KeyCode};
  * pair that is then grabbed by penrose to trigger the bound action.
  */
 pub fn parse_key_binding(pattern: String, known_codes: &CodeMap) -> Option<KeyCode> {
-    let mut parts: Vec<&str> = pattern.split('-').collect();
-    match known_codes.get(parts.remove(parts.len() - 1)) {
-        Some(code) => {
-            let mask = parts
-                .iter()
-                .map(|&s| match s {
-                    "A" => xcb::MOD_MASK_1,
-                    "M" => xcb::MOD_MASK_4,
-                    "S" => xcb::MOD_MASK_SHIFT,
-                    "C" => xcb::MOD_MASK_CONTROL,
-                    _ => panic!("invalid key binding prefix: {}", s),
-                })
-                .fold(0, |acc, v| acc | v);
-
-            trace!(?pattern, mask, code, "parsed keybinding");
-            Some(KeyCode {
-                mask: mask as u16,
-                code: *code,
-            })
+    match bindings::parse_key_binding(&pattern, known_codes) {
+        Ok(k) => {
+            trace!(?pattern, mask = k.mask, code = k.code, "parsed keybinding");
+            Some(k)
         }
-        None => None,
+        Err(PenroseError::UnknownModifier(s)) => panic!("invalid key binding prefix: {}", s),
+        Err(_) => None,
     }
 }
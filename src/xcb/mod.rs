@@ -178,6 +178,20 @@ pub enum XcbError {
     XcbUnknown(u8, u8),
 }
 
+impl XcbError {
+    // BadWindow / BadDrawable errors are returned for windows that have already been destroyed
+    pub(crate) fn is_window_gone(&self) -> bool {
+        let gone =
+            |code: u8| code == XErrorCode::BadWindow as u8 || code == XErrorCode::BadDrawable as u8;
+
+        match self {
+            XcbError::XcbKnown(code) => gone(*code as u8),
+            XcbError::X11Error(_, code, _, _, _) => gone(*code),
+            _ => false,
+        }
+    }
+}
+
 fn from_error_code(code: u8, response_type: u8) -> XcbError {
     match code {
         1..=11 => XcbError::XcbKnown(unsafe { std::mem::transmute(code) }),
@@ -259,6 +273,10 @@ macro_rules! __xcb_impl_xstate {
                 Ok(self.api.cursor_position()?)
             }
 
+            fn query_pointer(&self) -> $crate::core::xconnection::Result<(Point, Option<Xid>)> {
+                Ok(self.api.query_pointer()?)
+            }
+
             fn warp_cursor(&self, win_id: Option<Xid>, screen: &Screen) -> $crate::core::xconnection::Result<()> {
                 let (x, y, id) = match win_id {
                     Some(id) => {
@@ -13,7 +13,7 @@
  */
 use crate::{
     core::{
-        bindings::{KeyBindings, MouseBindings},
+        bindings::{lock_modifier_masks, KeyBindings, LockModifier, MouseBindings},
         data_types::{Point, Region},
         manager::WindowManager,
         screen::Screen,
@@ -124,14 +124,17 @@ impl XConn for XcbConnection {
         &self,
         key_bindings: &KeyBindings<Self>,
         mouse_bindings: &MouseBindings<Self>,
+        lock_modifiers: &[LockModifier],
     ) -> Result<()> {
+        let lock_masks = lock_modifier_masks(lock_modifiers);
         self.api
-            .grab_keys(&key_bindings.keys().collect::<Vec<_>>())?;
+            .grab_keys(&key_bindings.keys().collect::<Vec<_>>(), &lock_masks)?;
         self.api.grab_mouse_buttons(
             &mouse_bindings
                 .keys()
                 .map(|(_, state)| state)
                 .collect::<Vec<_>>(),
+            &lock_masks,
         )?;
         self.flush();
 
@@ -83,7 +83,7 @@ __impl_stub_xcon! {
         }
     }
     conn: {
-        fn mock_is_managed_client(&self, c: &Client) -> bool {
+        fn mock_is_managed_client(&self, c: &Client, _: &[&str]) -> bool {
             !self.unmanaged_ids.contains(&c.id())
         }
     }
@@ -78,7 +78,7 @@ __impl_stub_xcon! {
         }
     }
     conn: {
-        fn mock_is_managed_client(&self, _c: &Client) -> bool {
+        fn mock_is_managed_client(&self, _c: &Client, _: &[&str]) -> bool {
             true
         }
     }
@@ -210,3 +210,74 @@ fn serde_running_after_hydration_works() {
         .grab_keys_and_run(common::test_bindings(), HashMap::new())
         .unwrap();
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_restart_state_round_trips_through_state_file() {
+    let mut wm = get_seeded_wm(true);
+    wm.grab_keys_and_run(common::test_bindings(), HashMap::new())
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("penrose-restart-{}.json", std::process::id()));
+    wm.write_restart_state(&path, serde_json::to_string)
+        .unwrap();
+
+    let state = penrose::core::helpers::take_restart_state(&path)
+        .unwrap()
+        .expect("state file should have been written");
+    assert!(!path.exists(), "state file should be removed once read");
+
+    let mut restored: WindowManager<EarlyExitConn> = serde_json::from_str(&state).unwrap();
+    let res = restored.hydrate_and_init(vec![], logging_error_handler(), layout_funcs());
+    assert!(res.is_ok());
+    assert_eq!(restored.active_workspace(), wm.active_workspace());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_take_restart_state_is_none_without_a_state_file() {
+    let path = std::env::temp_dir().join("penrose-restart-missing.json");
+    assert!(penrose::core::helpers::take_restart_state(&path)
+        .unwrap()
+        .is_none());
+}
+
+// Simulate state written by an older version of penrose that did not have 'field'
+#[cfg(feature = "serde")]
+fn remove_field(value: &mut serde_json::Value, field: &str) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.remove(field);
+            map.values_mut().for_each(|v| remove_field(v, field));
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|v| remove_field(v, field)),
+        _ => (),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_state_without_client_stacking_flags_can_be_deserialized() {
+    let mut wm = get_seeded_wm(true);
+    wm.grab_keys_and_run(common::test_bindings(), HashMap::new())
+        .unwrap();
+    let mut value = serde_json::to_value(&wm).unwrap();
+    remove_field(&mut value, "above");
+    remove_field(&mut value, "below");
+
+    let restored: std::result::Result<WindowManager<EarlyExitConn>, serde_json::Error> =
+        serde_json::from_value(value);
+    assert!(restored.is_ok(), "{:?}", restored.err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_state_without_workspace_history_can_be_deserialized() {
+    let wm = get_seeded_wm(true);
+    let mut value = serde_json::to_value(&wm).unwrap();
+    remove_field(&mut value, "previous_workspaces");
+
+    let restored: std::result::Result<WindowManager<EarlyExitConn>, serde_json::Error> =
+        serde_json::from_value(value);
+    assert!(restored.is_ok(), "{:?}", restored.err());
+}